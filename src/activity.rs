@@ -0,0 +1,121 @@
+use crate::journal::{LoadProblem, append_line};
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Keystrokes counted during a single wall-clock minute.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActivitySample {
+    /// Start of the minute (UTC, truncated to the minute).
+    pub minute: DateTime<Utc>,
+    pub keystrokes: u32,
+}
+
+impl ActivitySample {
+    pub fn wpm(&self) -> u32 {
+        self.keystrokes / 5
+    }
+}
+
+/// Truncates a timestamp to the start of its minute.
+pub fn minute_of(time: DateTime<Utc>) -> DateTime<Utc> {
+    time.duration_trunc(TimeDelta::minutes(1)).unwrap_or(time)
+}
+
+/// Where per-minute activity is kept.
+pub trait ActivityStore {
+    /// Records `sample`, adding to any keystrokes already stored for its minute.
+    fn append(&self, sample: &ActivitySample) -> std::io::Result<()>;
+
+    /// Every readable sample, oldest first, and the records that were skipped.
+    fn load_checked(&self) -> std::io::Result<(Vec<ActivitySample>, Vec<LoadProblem>)>;
+
+    fn load_all(&self) -> std::io::Result<Vec<ActivitySample>> {
+        Ok(self.load_checked()?.0)
+    }
+}
+
+/// Per-minute activity history, stored as one JSON-lines file per UTC day.
+pub struct ActivityLog {
+    pub path: PathBuf,
+}
+
+impl Default for ActivityLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ActivityLog {
+    pub fn new() -> Self {
+        ActivityLog {
+            path: PathBuf::from("activity"),
+        }
+    }
+
+    pub fn append(&self, sample: &ActivitySample) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.path)?;
+        let filename = self
+            .path
+            .join(format!("{}.jsonl", sample.minute.format("%Y-%m-%d")));
+        append_line(&filename, &serde_json::to_string(sample)?)?;
+        Ok(())
+    }
+
+    /// Every readable sample, oldest first. Lines that fail to parse are
+    /// skipped; see [`ActivityLog::load_checked`].
+    pub fn load_all(&self) -> std::io::Result<Vec<ActivitySample>> {
+        Ok(self.load_checked()?.0)
+    }
+
+    /// Every readable sample, oldest first, and the lines that were skipped.
+    pub fn load_checked(&self) -> std::io::Result<(Vec<ActivitySample>, Vec<LoadProblem>)> {
+        let (mut samples, problems) = read_records::<ActivitySample>(&self.path)?;
+        samples.sort_by_key(|s| s.minute);
+        Ok((samples, problems))
+    }
+}
+
+impl ActivityStore for ActivityLog {
+    fn append(&self, sample: &ActivitySample) -> std::io::Result<()> {
+        ActivityLog::append(self, sample)
+    }
+
+    fn load_checked(&self) -> std::io::Result<(Vec<ActivitySample>, Vec<LoadProblem>)> {
+        ActivityLog::load_checked(self)
+    }
+}
+
+/// Every record in the `.jsonl` files of `dir`, and the lines that could not be parsed.
+pub(crate) fn read_records<T: DeserializeOwned>(
+    dir: &Path,
+) -> std::io::Result<(Vec<T>, Vec<LoadProblem>)> {
+    use std::fs;
+    let mut records = Vec::new();
+    let mut problems = Vec::new();
+    if !dir.exists() {
+        return Ok((records, problems));
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("jsonl") {
+            continue;
+        }
+        let content = fs::read_to_string(&path)?;
+        for (i, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(record) => records.push(record),
+                Err(e) => problems.push(LoadProblem {
+                    path: path.clone(),
+                    line: Some(i + 1),
+                    error: e.to_string(),
+                }),
+            }
+        }
+    }
+    Ok((records, problems))
+}
@@ -0,0 +1,523 @@
+use crate::activity::ActivityLog;
+use crate::config::Config;
+use crate::crypto;
+use crate::editor;
+use crate::export;
+use crate::flow::FlowLog;
+use crate::goals::GoalLog;
+use crate::import;
+use crate::journal::{self, Backend, Journal, JournalEntry};
+use crate::jsonl_journal::JsonlJournal;
+use crate::keymap::Section;
+use crate::schema;
+use crate::sqlite_journal::SqliteJournal;
+use crate::stats::{self, Period, Records};
+use chrono::{Days, Local, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+/// Command-line interface. Without a subcommand the TUI is started.
+#[derive(Parser, Debug)]
+#[command(name = "captains_log", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Add a journal entry; without text, write it in $VISUAL or $EDITOR.
+    Add(AddArgs),
+    /// Print activity and journal statistics for a day, week or month.
+    Report(ReportArgs),
+    /// Copy entries from the one-file-per-entry layout into another backend.
+    Migrate(MigrateArgs),
+    /// Print journal entries matching a query.
+    Search(SearchArgs),
+    /// Check the journal and logs for unreadable records.
+    Doctor(DoctorArgs),
+    /// Import entries from jrnl, Day One, Obsidian daily notes or text logs.
+    Import(ImportArgs),
+    /// Write the journal as JSON, CSV, an HTML page or an iCalendar file.
+    Export(ExportArgs),
+    /// Rewrite journal entries stored in an older format in the current one.
+    Upgrade,
+    /// Encrypt every journal entry under a new passphrase.
+    Encrypt,
+    /// Decrypt every journal entry and remove the key file.
+    Decrypt,
+    /// List the TUI key bindings from the config, and any conflicts.
+    Keys,
+}
+
+#[derive(Args, Debug)]
+#[group(multiple = false)]
+pub struct ReportArgs {
+    /// Report on today.
+    #[arg(long)]
+    pub day: bool,
+    /// Report on the current week (default).
+    #[arg(long)]
+    pub week: bool,
+    /// Report on the current month.
+    #[arg(long)]
+    pub month: bool,
+    /// Emit JSON instead of text.
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl ReportArgs {
+    pub fn period(&self) -> Period {
+        if self.day {
+            Period::Day
+        } else if self.month {
+            Period::Month
+        } else {
+            Period::Week
+        }
+    }
+}
+
+/// Backend a migration writes to.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    Jsonl,
+    Sqlite,
+}
+
+#[derive(Args, Debug)]
+pub struct AddArgs {
+    /// Entry text. Leave out to open an editor.
+    pub text: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct MigrateArgs {
+    /// Directory of per-entry `.json` files.
+    #[arg(long, default_value = "journals")]
+    pub from: PathBuf,
+    /// Backend to migrate into.
+    #[arg(long, value_enum, default_value = "jsonl")]
+    pub backend: Target,
+    /// Location of the new store (`journal_log` or `captains_log.db` by default).
+    #[arg(long)]
+    pub to: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct SearchArgs {
+    /// Words to look for. The SQLite backend accepts FTS5 query syntax.
+    #[arg(required = true)]
+    pub query: Vec<String>,
+    /// Only entries tagged `#TAG`.
+    #[arg(long)]
+    pub tag: Option<String>,
+}
+
+/// Formats `import` understands.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// A jrnl journal file or `jrnl --export json` output.
+    Jrnl,
+    /// A Day One JSON export.
+    DayOne,
+    /// A folder of Obsidian daily notes (`YYYY-MM-DD.md`).
+    Obsidian,
+    /// A Markdown or `.txt` log, or a folder of them.
+    Text,
+}
+
+impl Source {
+    fn format(self) -> import::Format {
+        match self {
+            Source::Jrnl => import::Format::Jrnl,
+            Source::DayOne => import::Format::DayOne,
+            Source::Obsidian => import::Format::Obsidian,
+            Source::Text => import::Format::Text,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    #[arg(value_enum)]
+    pub format: Source,
+    /// File or folder to import.
+    pub path: PathBuf,
+    /// Show what would be imported without saving anything.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Formats `export` writes.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Output {
+    /// A single JSON array.
+    Json,
+    /// `timestamp,tags,content` rows.
+    Csv,
+    /// A standalone web page.
+    Html,
+    /// VJOURNAL items for calendar apps.
+    Ical,
+}
+
+impl Output {
+    fn format(self) -> export::Format {
+        match self {
+            Output::Json => export::Format::Json,
+            Output::Csv => export::Format::Csv,
+            Output::Html => export::Format::Html,
+            Output::Ical => export::Format::Ical,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    #[arg(value_enum)]
+    pub format: Output,
+    /// First day to include (YYYY-MM-DD, local time).
+    #[arg(long)]
+    pub from: Option<NaiveDate>,
+    /// Last day to include (YYYY-MM-DD, local time).
+    #[arg(long)]
+    pub to: Option<NaiveDate>,
+    /// Only entries tagged `#TAG`.
+    #[arg(long)]
+    pub tag: Option<String>,
+    /// File to write instead of standard output.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct DoctorArgs {
+    /// Move unreadable journal records into a `quarantine` directory.
+    #[arg(long)]
+    pub fix: bool,
+}
+
+pub fn run(command: Command, config: &Config) -> Result<(), Box<dyn Error>> {
+    match command {
+        Command::Add(args) => add(&args, config),
+        Command::Report(args) => report(&args, config),
+        Command::Migrate(args) => migrate(&args),
+        Command::Search(args) => search(&args, config),
+        Command::Doctor(args) => doctor(&args, config),
+        Command::Import(args) => import(&args, config),
+        Command::Export(args) => export(&args, config),
+        Command::Upgrade => upgrade(config),
+        Command::Encrypt => encrypt(config),
+        Command::Decrypt => decrypt(config),
+        Command::Keys => keys(config),
+    }
+}
+
+fn report(args: &ReportArgs, config: &Config) -> Result<(), Box<dyn Error>> {
+    let (samples, mut problems) = config.journal.open_activity().load_checked()?;
+    let entries = config.journal.open().load_all()?;
+    let (flows, flow_problems) = FlowLog::new().load_checked()?;
+    problems.extend(flow_problems);
+    for problem in &problems {
+        eprintln!("Warning: skipped {}", problem);
+    }
+    let report = stats::build_report(
+        args.period(),
+        Local::now().date_naive(),
+        &Local,
+        Records {
+            samples: &samples,
+            entries: &entries,
+            flows: &flows,
+        },
+    );
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", report.to_text());
+    }
+    Ok(())
+}
+
+fn add(args: &AddArgs, config: &Config) -> Result<(), Box<dyn Error>> {
+    let content = if args.text.is_empty() {
+        editor::edit("")?
+    } else {
+        Some(args.text.join(" "))
+    };
+    let Some(content) = content.filter(|c| !c.trim().is_empty()) else {
+        return Err("empty entry, nothing saved".into());
+    };
+    let journal = config.journal.open();
+    journal.save(&JournalEntry {
+        timestamp: Utc::now(),
+        content,
+    })?;
+    println!("Saved.");
+    if let Some(status) = journal.status() {
+        println!("Warning: {}", status);
+    }
+    Ok(())
+}
+
+fn migrate(args: &MigrateArgs) -> Result<(), Box<dyn Error>> {
+    let from = Journal {
+        path: args.from.clone(),
+    };
+    let (result, to, backend) = match args.backend {
+        Target::Jsonl => {
+            let mut to = JsonlJournal::new();
+            if let Some(path) = &args.to {
+                to.path = path.clone();
+            }
+            (journal::migrate(&from, &to)?, to.path, "jsonl")
+        }
+        Target::Sqlite => {
+            let mut to = SqliteJournal::new();
+            if let Some(path) = &args.to {
+                to.path = path.clone();
+            }
+            let result = to.import(&from.load_named()?)?;
+            let samples = to.import_activity(&ActivityLog::new().load_all()?)?;
+            println!("Imported {} activity minutes.", samples);
+            (result, to.path, "sqlite")
+        }
+    };
+    println!(
+        "Migrated {} entries to {} ({} already present).",
+        result.migrated,
+        to.display(),
+        result.skipped
+    );
+    println!(
+        "Set `backend = \"{}\"` under [journal] in captains_log.toml to use it.",
+        backend
+    );
+    Ok(())
+}
+
+fn search(args: &SearchArgs, config: &Config) -> Result<(), Box<dyn Error>> {
+    let query = args.query.join(" ");
+    let mut entries = config.journal.open().search(&query)?;
+    if let Some(tag) = &args.tag {
+        let tag = tag.trim_start_matches('#').to_lowercase();
+        entries.retain(|e| journal::tags(&e.content).contains(&tag));
+    }
+    if entries.is_empty() {
+        println!("No entries match.");
+        return Ok(());
+    }
+    if config.journal.backend != Backend::Sqlite {
+        entries.sort_by_key(|e| e.timestamp);
+    }
+    for entry in entries {
+        println!(
+            "{}  {}",
+            entry
+                .timestamp
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M"),
+            entry.content.replace('\n', " ")
+        );
+    }
+    Ok(())
+}
+
+fn doctor(args: &DoctorArgs, config: &Config) -> Result<(), Box<dyn Error>> {
+    let journal = config.journal.open();
+    let loaded = journal.load_checked()?;
+    println!(
+        "Journal: {} entries, {} unreadable.",
+        loaded.entries.len(),
+        loaded.problems.len()
+    );
+    for problem in &loaded.problems {
+        println!("  {}", problem);
+    }
+
+    let mut healthy = true;
+    let problems = |loaded: io::Result<Vec<journal::LoadProblem>>| match loaded {
+        Ok(problems) => problems.iter().map(ToString::to_string).collect(),
+        Err(e) => vec![e.to_string()],
+    };
+    let checks = [
+        (
+            "Activity",
+            problems(config.journal.open_activity().load_checked().map(|l| l.1)),
+        ),
+        ("Flow", problems(FlowLog::new().load_checked().map(|l| l.1))),
+        ("Goals", problems(GoalLog::new().load().map(|_| Vec::new()))),
+    ];
+    for (name, errors) in checks {
+        match errors.as_slice() {
+            [] => println!("{}: ok", name),
+            [error] => {
+                healthy = false;
+                println!("{}: {}", name, error);
+            }
+            errors => {
+                healthy = false;
+                println!("{}: {} unreadable records.", name, errors.len());
+                for error in errors {
+                    println!("  {}", error);
+                }
+            }
+        }
+    }
+
+    if !loaded.problems.is_empty() {
+        if args.fix {
+            let moved = journal.quarantine()?;
+            println!("Quarantined {} unreadable journal records.", moved);
+        } else {
+            healthy = false;
+            println!("Run `captains_log doctor --fix` to move them into quarantine.");
+        }
+    }
+    if healthy {
+        Ok(())
+    } else {
+        Err("problems found".into())
+    }
+}
+
+fn import(args: &ImportArgs, config: &Config) -> Result<(), Box<dyn Error>> {
+    let imported = import::read(args.format.format(), &args.path, &Local)?;
+    for warning in &imported.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+    let journal = config.journal.open();
+    let plan = import::plan(imported.entries, &journal.load_all()?);
+
+    if args.dry_run {
+        for entry in &plan.new {
+            println!(
+                "{}  {}",
+                entry
+                    .timestamp
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M"),
+                entry.content.lines().next().unwrap_or("")
+            );
+        }
+        println!(
+            "Would import {} entries ({} already present).",
+            plan.new.len(),
+            plan.duplicates
+        );
+        return Ok(());
+    }
+
+    for entry in &plan.new {
+        journal.save(entry)?;
+    }
+    println!(
+        "Imported {} entries ({} already present).",
+        plan.new.len(),
+        plan.duplicates
+    );
+    if let Some(status) = journal.status() {
+        println!("Warning: {}", status);
+    }
+    Ok(())
+}
+
+fn export(args: &ExportArgs, config: &Config) -> Result<(), Box<dyn Error>> {
+    let filter = export::Filter {
+        from: args.from.map(|day| stats::day_start(&Local, day)),
+        to: args
+            .to
+            .and_then(|day| day.checked_add_days(Days::new(1)))
+            .map(|day| stats::day_start(&Local, day)),
+        tag: args.tag.clone(),
+    };
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    let journal = config.journal.open();
+    let count = export::export(&*journal, args.format.format(), &filter, &Local, &mut out)?;
+    out.flush()?;
+    if let Some(path) = &args.output {
+        println!("Exported {} entries to {}.", count, path.display());
+    }
+    Ok(())
+}
+
+fn upgrade(config: &Config) -> Result<(), Box<dyn Error>> {
+    let journal = config.journal.open();
+    let upgraded = journal.upgrade()?;
+    println!(
+        "Upgraded {} entries to schema version {}.",
+        upgraded,
+        schema::CURRENT_VERSION
+    );
+    if let Some(status) = journal.status() {
+        println!("Warning: {}", status);
+    }
+    Ok(())
+}
+
+fn encrypt(config: &Config) -> Result<(), Box<dyn Error>> {
+    let passphrase = crypto::read_passphrase("New journal passphrase: ")?;
+    if std::env::var(crypto::PASSPHRASE_VAR).is_err()
+        && crypto::read_passphrase("Repeat passphrase: ")? != passphrase
+    {
+        return Err("passphrases do not match".into());
+    }
+    if passphrase.is_empty() {
+        return Err("the passphrase must not be empty".into());
+    }
+    let count = crypto::encrypt_journal(&config.journal, &passphrase)?;
+    println!(
+        "Encrypted {} entries. The key file is {}; without it and the passphrase the journal cannot be read.",
+        count,
+        config.journal.key_path().display()
+    );
+    let history = config.journal.location().join(".git");
+    if history.exists() {
+        eprintln!(
+            "Warning: earlier commits in {} still hold the entries unencrypted, and so does any remote they were pushed to.",
+            history.display()
+        );
+    }
+    Ok(())
+}
+
+fn decrypt(config: &Config) -> Result<(), Box<dyn Error>> {
+    if !config.journal.is_encrypted() {
+        return Err("the journal is not encrypted".into());
+    }
+    let count = crypto::decrypt_journal(&config.journal)?;
+    println!("Decrypted {} entries.", count);
+    Ok(())
+}
+
+fn keys(config: &Config) -> Result<(), Box<dyn Error>> {
+    let keymap = config.keys.keymap()?;
+    for section in Section::ALL {
+        println!("[keys.{}]", section.name());
+        for binding in keymap.bindings.iter().filter(|b| b.section == section) {
+            let command = binding.action.command();
+            println!(
+                "  {:<12} {:<20} {}",
+                binding.chord.to_string(),
+                command.id,
+                command.name
+            );
+        }
+    }
+    let conflicts = keymap.conflicts();
+    if !conflicts.is_empty() {
+        println!("\nConflicts:");
+        for conflict in conflicts {
+            println!("  {}", conflict);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,244 @@
+//! Actions the TUI can run from the command palette, and fuzzy matching over them.
+
+use crate::export;
+
+/// Something the user can do from the TUI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Search,
+    ClearSearch,
+    ShowJournal,
+    ToggleStats,
+    CycleStatsPeriod,
+    OpenEditor,
+    ExternalEditor,
+    ToggleMultiline,
+    InsertTemplate,
+    TogglePomodoro,
+    ToggleAlerts,
+    CycleLayout,
+    RetrySaving,
+    ZoomIn,
+    ZoomOut,
+    PanLeft,
+    PanRight,
+    Export(export::Format),
+    Palette,
+    Help,
+    Quit,
+    SelectPrevious,
+    SelectNext,
+    OpenEntry,
+    ClearSelection,
+    Submit,
+    /// Saves the entry, or starts a new line in multi-line mode.
+    SubmitOrNewLine,
+    NewLine,
+    GrowInput,
+    ShrinkInput,
+    ScrollUp,
+    ScrollDown,
+    PageUp,
+    PageDown,
+    /// Modal keymaps only: keys run commands instead of typing.
+    NormalMode,
+    InsertMode,
+}
+
+impl Action {
+    /// The entry describing this action.
+    pub fn command(self) -> &'static Command {
+        COMMANDS
+            .iter()
+            .chain(KEY_ACTIONS)
+            .find(|c| c.action == self)
+            .expect("every action is registered")
+    }
+
+    /// Looks an action up by its name in the `[keys]` config.
+    pub fn from_id(id: &str) -> Option<Action> {
+        COMMANDS
+            .iter()
+            .chain(KEY_ACTIONS)
+            .find(|c| c.id == id)
+            .map(|c| c.action)
+    }
+}
+
+/// An action with its config name and description.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Command {
+    pub action: Action,
+    /// Name used in the `[keys]` config.
+    pub id: &'static str,
+    pub name: &'static str,
+}
+
+const fn command(action: Action, id: &'static str, name: &'static str) -> Command {
+    Command { action, id, name }
+}
+
+/// Actions listed in the command palette, in the order shown without a query.
+pub const COMMANDS: &[Command] = &[
+    command(Action::Search, "search", "Search journal"),
+    command(Action::ClearSearch, "clear_search", "Clear search"),
+    command(Action::ShowJournal, "show_journal", "Back to journal"),
+    command(Action::ToggleStats, "toggle_stats", "Toggle statistics"),
+    command(
+        Action::CycleStatsPeriod,
+        "next_stats_period",
+        "Next statistics period",
+    ),
+    command(Action::OpenEditor, "open_editor", "Open full-screen editor"),
+    command(
+        Action::ExternalEditor,
+        "external_editor",
+        "Write entry in $EDITOR",
+    ),
+    command(
+        Action::ToggleMultiline,
+        "toggle_multiline",
+        "Toggle multi-line input",
+    ),
+    command(Action::InsertTemplate, "insert_template", "Insert template"),
+    command(
+        Action::TogglePomodoro,
+        "toggle_pomodoro",
+        "Start or cancel pomodoro",
+    ),
+    command(
+        Action::ToggleAlerts,
+        "toggle_alerts",
+        "Toggle inactivity alerts",
+    ),
+    command(Action::CycleLayout, "cycle_layout", "Next layout"),
+    command(Action::RetrySaving, "retry_saving", "Retry saving entries"),
+    command(Action::ZoomIn, "zoom_in", "Zoom timeline in"),
+    command(Action::ZoomOut, "zoom_out", "Zoom timeline out"),
+    command(Action::PanLeft, "pan_left", "Pan timeline back"),
+    command(Action::PanRight, "pan_right", "Pan timeline forward"),
+    command(
+        Action::Export(export::Format::Html),
+        "export_html",
+        "Export journal as HTML",
+    ),
+    command(
+        Action::Export(export::Format::Json),
+        "export_json",
+        "Export journal as JSON",
+    ),
+    command(
+        Action::Export(export::Format::Csv),
+        "export_csv",
+        "Export journal as CSV",
+    ),
+    command(
+        Action::Export(export::Format::Ical),
+        "export_ical",
+        "Export journal as iCalendar",
+    ),
+    command(Action::Help, "help", "Show key bindings"),
+    command(Action::Quit, "quit", "Quit"),
+];
+
+/// Actions only reachable through a key, such as moving through a list.
+pub const KEY_ACTIONS: &[Command] = &[
+    command(Action::Palette, "palette", "Command palette"),
+    command(
+        Action::SelectPrevious,
+        "select_previous",
+        "Select previous entry",
+    ),
+    command(Action::SelectNext, "select_next", "Select next entry"),
+    command(Action::OpenEntry, "open_entry", "Open selected entry"),
+    command(
+        Action::ClearSelection,
+        "clear_selection",
+        "Clear selection, then search",
+    ),
+    command(Action::Submit, "submit", "Save entry"),
+    command(
+        Action::SubmitOrNewLine,
+        "submit_or_newline",
+        "Save entry (new line in multi-line mode)",
+    ),
+    command(Action::NewLine, "newline", "New line"),
+    command(Action::GrowInput, "grow_input", "Grow input box"),
+    command(Action::ShrinkInput, "shrink_input", "Shrink input box"),
+    command(Action::ScrollUp, "scroll_up", "Scroll up"),
+    command(Action::ScrollDown, "scroll_down", "Scroll down"),
+    command(Action::PageUp, "page_up", "Scroll a page up"),
+    command(Action::PageDown, "page_down", "Scroll a page down"),
+    command(Action::NormalMode, "normal_mode", "Normal mode"),
+    command(Action::InsertMode, "insert_mode", "Insert mode"),
+];
+
+/// What the palette's text field is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteMode {
+    Commands,
+    /// Words to filter the journal panel by.
+    Search,
+}
+
+/// State of the open command palette.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    pub mode: PaletteMode,
+    pub query: String,
+    /// Highlighted row of `matches`.
+    pub selected: usize,
+}
+
+impl Palette {
+    pub fn new(mode: PaletteMode) -> Self {
+        Self {
+            mode,
+            query: String::new(),
+            selected: 0,
+        }
+    }
+
+    /// Commands matching the query, best first.
+    pub fn matches(&self) -> Vec<&'static Command> {
+        matching(&self.query)
+    }
+}
+
+/// How well `query` matches `name` when its characters appear in order,
+/// ignoring case: consecutive characters and word starts score higher. `None`
+/// if it does not match at all.
+pub fn fuzzy_score(query: &str, name: &str) -> Option<i32> {
+    let name: Vec<char> = name.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for q in query.chars().flat_map(char::to_lowercase) {
+        if q.is_whitespace() {
+            continue;
+        }
+        let found = next + name[next..].iter().position(|&c| c == q)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !name[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (found - next).min(5) as i32;
+        previous = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
+
+/// Commands whose name matches `query`, best first; all of them for an empty query.
+pub fn matching(query: &str) -> Vec<&'static Command> {
+    let mut scored: Vec<(i32, &Command)> = COMMANDS
+        .iter()
+        .filter_map(|c| fuzzy_score(query, c.name).map(|score| (score, c)))
+        .collect();
+    // Stable, so ties keep the registry order
+    scored.sort_by_key(|(score, _)| -score);
+    scored.into_iter().map(|(_, c)| c).collect()
+}
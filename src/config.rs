@@ -0,0 +1,51 @@
+use crate::flow::FlowConfig;
+use crate::focus::FocusConfig;
+use crate::goals::GoalsConfig;
+use crate::journal::JournalConfig;
+use crate::keymap::KeysConfig;
+use crate::templates::Template;
+use crate::theme::{ThemeConfig, UserTheme};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// User settings, read from `captains_log.toml` in the working directory.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct Config {
+    pub focus: FocusConfig,
+    pub flow: FlowConfig,
+    pub goals: GoalsConfig,
+    pub journal: JournalConfig,
+    /// Entry templates; a built-in end-of-day template is used when empty.
+    pub templates: Vec<Template>,
+    pub keys: KeysConfig,
+    pub theme: ThemeConfig,
+    /// User themes, picked by name in `[theme]`.
+    pub themes: BTreeMap<String, UserTheme>,
+}
+
+impl Config {
+    pub fn default_path() -> PathBuf {
+        PathBuf::from("captains_log.toml")
+    }
+
+    /// Reads the config at `path`, falling back to defaults when it does not exist.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let invalid = |e: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), e),
+            )
+        };
+        let config: Config = toml::from_str(&content).map_err(|e| invalid(e.to_string()))?;
+        config.keys.keymap().map_err(invalid)?;
+        crate::theme::build(&config.theme, &config.themes, false).map_err(invalid)?;
+        Ok(config)
+    }
+}
@@ -0,0 +1,573 @@
+//! Optional encryption of journal entries at rest.
+//!
+//! Entry text is sealed with XChaCha20-Poly1305 under a key derived from a
+//! passphrase with Argon2id. Timestamps stay readable so every backend can
+//! still sort, index and range-query entries. The salt and KDF parameters live
+//! in a small key file next to the journal; the passphrase itself is never stored.
+
+use crate::git_journal;
+use crate::journal::{
+    Backend, DirLock, JournalConfig, JournalEntry, JournalStore, LoadProblem, Loaded, lock_dir,
+    lock_file, sync_parent, with_suffix,
+};
+use crate::sqlite_journal::SqliteJournal;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use chrono::SecondsFormat;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Marks encrypted entry content.
+const PREFIX: &str = "enc:v1:";
+/// Encrypted into the key file so a wrong passphrase is caught up front.
+const CHECK: &[u8] = b"captains_log";
+/// Environment variable read instead of prompting, for scripts.
+pub const PASSPHRASE_VAR: &str = "CAPTAINS_LOG_PASSPHRASE";
+
+/// Key derivation settings, stored in the key file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct KeyFile {
+    pub version: u32,
+    pub salt: String,
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+    pub check: String,
+}
+
+/// An unlocked journal key.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Cipher {
+    key: [u8; 32],
+}
+
+impl fmt::Debug for Cipher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Cipher(..)")
+    }
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+impl Cipher {
+    fn derive(
+        passphrase: &str,
+        salt: &[u8],
+        m_cost: u32,
+        t_cost: u32,
+        p_cost: u32,
+    ) -> io::Result<Self> {
+        let params = argon2::Params::new(m_cost, t_cost, p_cost, Some(32))
+            .map_err(|e| invalid(e.to_string()))?;
+        let argon =
+            argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+        let mut key = [0u8; 32];
+        argon
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| invalid(e.to_string()))?;
+        Ok(Cipher { key })
+    }
+
+    fn aead(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(&self.key.into())
+    }
+
+    /// Nonce followed by ciphertext. `aad` is authenticated but not encrypted.
+    fn seal(&self, plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let mut out = nonce.to_vec();
+        let sealed = self
+            .aead()
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .expect("XChaCha20-Poly1305 encryption cannot fail for in-memory data");
+        out.extend(sealed);
+        out
+    }
+
+    fn open(&self, sealed: &[u8], aad: &[u8]) -> io::Result<Vec<u8>> {
+        if sealed.len() < 24 {
+            return Err(invalid("encrypted data is truncated"));
+        }
+        let (nonce, ciphertext) = sealed.split_at(24);
+        self.aead()
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| invalid("cannot decrypt: wrong key or damaged data"))
+    }
+
+    /// The entry with its content encrypted. The timestamp is bound to the
+    /// content, so encrypted text cannot be moved to another entry unnoticed.
+    pub fn encrypt(&self, entry: &JournalEntry) -> JournalEntry {
+        let aad = entry.timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+        let sealed = self.seal(entry.content.as_bytes(), aad.as_bytes());
+        JournalEntry {
+            timestamp: entry.timestamp,
+            content: format!("{}{}", PREFIX, BASE64.encode(sealed)),
+        }
+    }
+
+    /// The entry with its content decrypted. Plain entries pass through unchanged.
+    pub fn decrypt(&self, entry: &JournalEntry) -> io::Result<JournalEntry> {
+        let Some(encoded) = entry.content.strip_prefix(PREFIX) else {
+            return Ok(entry.clone());
+        };
+        let sealed = BASE64.decode(encoded).map_err(|e| invalid(e.to_string()))?;
+        let aad = entry.timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+        let content = self.open(&sealed, aad.as_bytes())?;
+        Ok(JournalEntry {
+            timestamp: entry.timestamp,
+            content: String::from_utf8(content).map_err(|e| invalid(e.to_string()))?,
+        })
+    }
+}
+
+/// True if `content` is an encrypted entry body.
+pub fn is_encrypted(content: &str) -> bool {
+    content.starts_with(PREFIX)
+}
+
+/// A fresh random salt and the key it gives for `passphrase`.
+pub fn new_key(passphrase: &str) -> io::Result<(KeyFile, Cipher)> {
+    let params = argon2::Params::default();
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let cipher = Cipher::derive(
+        passphrase,
+        &salt,
+        params.m_cost(),
+        params.t_cost(),
+        params.p_cost(),
+    )?;
+    let key_file = KeyFile {
+        version: 1,
+        salt: BASE64.encode(salt),
+        m_cost: params.m_cost(),
+        t_cost: params.t_cost(),
+        p_cost: params.p_cost(),
+        check: BASE64.encode(cipher.seal(CHECK, b"")),
+    };
+    Ok((key_file, cipher))
+}
+
+fn write_key_file(key_path: &Path, key_file: &KeyFile) -> io::Result<()> {
+    if let Some(parent) = key_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    crate::journal::write_new(key_path, serde_json::to_string_pretty(key_file)?.as_bytes()).map_err(
+        |e| match e.kind() {
+            io::ErrorKind::AlreadyExists => io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", key_path.display()),
+            ),
+            _ => e,
+        },
+    )
+}
+
+/// Creates a key file for a new passphrase and returns the unlocked key.
+pub fn init(key_path: &Path, passphrase: &str) -> io::Result<Cipher> {
+    let (key_file, cipher) = new_key(passphrase)?;
+    write_key_file(key_path, &key_file)?;
+    Ok(cipher)
+}
+
+/// Derives the key from `passphrase` and checks it against the key file.
+pub fn unlock(key_path: &Path, passphrase: &str) -> io::Result<Cipher> {
+    let content = std::fs::read_to_string(key_path)?;
+    let key_file: KeyFile = serde_json::from_str(&content)?;
+    if key_file.version != 1 {
+        return Err(invalid(format!(
+            "{}: unsupported key file version {}",
+            key_path.display(),
+            key_file.version
+        )));
+    }
+    let salt = BASE64
+        .decode(&key_file.salt)
+        .map_err(|e| invalid(e.to_string()))?;
+    let cipher = Cipher::derive(
+        passphrase,
+        &salt,
+        key_file.m_cost,
+        key_file.t_cost,
+        key_file.p_cost,
+    )?;
+    let check = BASE64
+        .decode(&key_file.check)
+        .map_err(|e| invalid(e.to_string()))?;
+    match cipher.open(&check, b"") {
+        Ok(plain) if plain == CHECK => Ok(cipher),
+        _ => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "wrong passphrase",
+        )),
+    }
+}
+
+/// The passphrase from [`PASSPHRASE_VAR`], or else asked for on the terminal without echo.
+pub fn read_passphrase(prompt: &str) -> io::Result<String> {
+    match std::env::var(PASSPHRASE_VAR) {
+        Ok(passphrase) => Ok(passphrase),
+        Err(_) => rpassword::prompt_password(prompt),
+    }
+}
+
+/// Wraps any store, encrypting entry text on save and decrypting on load.
+pub struct EncryptedStore {
+    pub inner: Box<dyn JournalStore>,
+    pub cipher: Cipher,
+    /// Where the inner store lives, for problem reports.
+    pub location: PathBuf,
+}
+
+impl JournalStore for EncryptedStore {
+    fn save(&self, entry: &JournalEntry) -> io::Result<()> {
+        self.inner.save(&self.cipher.encrypt(entry))
+    }
+
+    fn load_all(&self) -> io::Result<Vec<JournalEntry>> {
+        Ok(self.load_checked()?.entries)
+    }
+
+    /// Entries that cannot be decrypted are reported like unreadable files.
+    fn load_checked(&self) -> io::Result<Loaded> {
+        let loaded = self.inner.load_checked()?;
+        let mut result = Loaded {
+            entries: Vec::with_capacity(loaded.entries.len()),
+            problems: loaded.problems,
+        };
+        for entry in loaded.entries {
+            match self.cipher.decrypt(&entry) {
+                Ok(entry) => result.entries.push(entry),
+                Err(e) => result.problems.push(LoadProblem {
+                    path: self.location.clone(),
+                    line: None,
+                    error: format!("entry of {}: {}", entry.timestamp.to_rfc3339(), e),
+                }),
+            }
+        }
+        Ok(result)
+    }
+
+    fn quarantine(&self) -> io::Result<usize> {
+        self.inner.quarantine()
+    }
+
+    fn upgrade(&self) -> io::Result<usize> {
+        self.inner.upgrade()
+    }
+
+    /// Stops at the first entry that cannot be decrypted, rather than leave
+    /// it out of an export unnoticed.
+    fn for_each_entry(&self, f: &mut dyn FnMut(JournalEntry) -> io::Result<()>) -> io::Result<()> {
+        self.inner
+            .for_each_entry(&mut |entry| match self.cipher.decrypt(&entry) {
+                Ok(entry) => f(entry),
+                Err(e) => Err(invalid(format!(
+                    "entry of {}: {}",
+                    entry.timestamp.to_rfc3339(),
+                    e
+                ))),
+            })
+    }
+
+    fn status(&self) -> Option<String> {
+        self.inner.status()
+    }
+}
+
+/// Encrypts every entry of a plain journal under a new passphrase. Returns
+/// how many entries were converted.
+pub fn encrypt_journal(journal: &JournalConfig, passphrase: &str) -> io::Result<usize> {
+    if journal.is_encrypted() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "the journal is already encrypted",
+        ));
+    }
+    let (key_file, cipher) = new_key(passphrase)?;
+    let count = rewrite(journal, |e| Ok(cipher.encrypt(e)), Some(&key_file))?;
+    commit_rewrite(journal, "Encrypt journal")?;
+    Ok(count)
+}
+
+/// Decrypts every entry with the journal's unlocked key and removes the key file.
+pub fn decrypt_journal(journal: &JournalConfig) -> io::Result<usize> {
+    let Some(cipher) = &journal.cipher else {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "the journal is not unlocked",
+        ));
+    };
+    let count = rewrite(journal, |e| cipher.decrypt(e), None)?;
+    commit_rewrite(journal, "Decrypt journal")?;
+    Ok(count)
+}
+
+fn commit_rewrite(journal: &JournalConfig, message: &str) -> io::Result<()> {
+    if journal.git.enabled && journal.backend != Backend::Sqlite {
+        git_journal::commit_all(&journal.location(), message, &journal.git)?;
+    }
+    Ok(())
+}
+
+fn remove(path: &Path) -> io::Result<()> {
+    if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else if path.exists() {
+        std::fs::remove_file(path)
+    } else {
+        Ok(())
+    }
+}
+
+/// Where a rewrite of the journal keeps its new and old copies.
+struct RewritePaths {
+    backend: Backend,
+    location: PathBuf,
+    staging: PathBuf,
+    backup: PathBuf,
+}
+
+impl RewritePaths {
+    fn new(journal: &JournalConfig) -> Self {
+        let location = journal.location();
+        RewritePaths {
+            backend: journal.backend,
+            staging: with_suffix(&location, ".rewrite"),
+            backup: with_suffix(&location, ".old"),
+            location,
+        }
+    }
+
+    fn staging_config(&self) -> JournalConfig {
+        JournalConfig {
+            backend: self.backend,
+            path: Some(self.staging.clone()),
+            ..JournalConfig::default()
+        }
+    }
+
+    fn key_path(&self) -> PathBuf {
+        JournalConfig {
+            backend: self.backend,
+            path: Some(self.location.clone()),
+            ..JournalConfig::default()
+        }
+        .key_path()
+    }
+
+    /// Held for a whole rewrite. It sits beside the journal, since the
+    /// journal itself is renamed while it is held.
+    fn lock(&self) -> io::Result<DirLock> {
+        lock_file(&with_suffix(&self.location, ".lock"))
+    }
+
+    /// Moves the finished staging copy into place. A database keeps its key
+    /// file beside it, so that moves too, or goes away when decrypting.
+    fn swap_in(&self) -> io::Result<()> {
+        std::fs::rename(&self.staging, &self.location)?;
+        if self.backend == Backend::Sqlite {
+            let staging_key = self.staging_config().key_path();
+            if staging_key.exists() {
+                std::fs::rename(staging_key, self.key_path())?;
+            } else {
+                remove(&self.key_path())?;
+            }
+            remove(&with_suffix(&self.staging, ".lock"))?;
+        }
+        sync_parent(&self.location);
+        Ok(())
+    }
+
+    /// Finishes or undoes a rewrite that was interrupted, depending on how
+    /// far it got. Returns whether there was one.
+    fn recover(&self) -> io::Result<bool> {
+        let staging_key = self.staging_config().key_path();
+        match (
+            self.location.exists(),
+            self.staging.exists(),
+            self.backup.exists(),
+        ) {
+            (_, false, false) => return Ok(false),
+            // Stopped before the swap; the journal was never touched
+            (true, true, false) => {
+                remove(&self.staging)?;
+                if self.backend == Backend::Sqlite {
+                    remove(&staging_key)?;
+                    remove(&with_suffix(&self.staging, ".lock"))?;
+                }
+            }
+            // Stopped mid-swap; the staging copy is complete
+            (false, true, true) => {
+                self.swap_in()?;
+                remove(&self.backup)?;
+            }
+            // Stopped after the swap
+            (true, false, true) => {
+                if self.backend == Backend::Sqlite && staging_key.exists() {
+                    std::fs::rename(&staging_key, self.key_path())?;
+                }
+                remove(&self.backup)?;
+            }
+            _ => {
+                return Err(invalid(format!(
+                    "an interrupted encrypt or decrypt left {} and {}; \
+                     restore {} from them by hand",
+                    self.staging.display(),
+                    self.backup.display(),
+                    self.location.display()
+                )));
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Finishes or undoes an encrypt or decrypt that was interrupted, so the
+/// copies it left behind are never mistaken for leftovers. Returns whether
+/// there was one.
+pub fn recover(journal: &JournalConfig) -> io::Result<bool> {
+    let paths = RewritePaths::new(journal);
+    if !paths.staging.exists() && !paths.backup.exists() {
+        return Ok(false);
+    }
+    let _lock = paths.lock()?;
+    paths.recover()
+}
+
+/// Whether `name`, at the top of the journal, is one of the files holding
+/// entries, which a rewrite writes afresh rather than copies.
+fn is_record(backend: Backend, name: &str) -> bool {
+    match backend {
+        Backend::Files => name.ends_with(".json"),
+        Backend::Jsonl => name.ends_with(".jsonl") || name == "index.json",
+        Backend::Sqlite => false,
+    }
+}
+
+/// Copies `from` to `to`, recursing into directories, and flushes every file to disk.
+fn copy_tree(from: &Path, to: &Path) -> io::Result<()> {
+    if from.is_dir() {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_tree(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        std::fs::copy(from, to)?;
+        std::fs::File::open(to)?.sync_all()?;
+        sync_parent(to);
+    }
+    Ok(())
+}
+
+/// Writes every entry through `convert` into a new store beside the old one,
+/// along with everything else in the journal (quarantine, git history) and
+/// `key_file` if given, then swaps it in. Writers are locked out throughout,
+/// and an interrupted run is finished or undone by [`recover`].
+fn rewrite(
+    journal: &JournalConfig,
+    convert: impl Fn(&JournalEntry) -> io::Result<JournalEntry>,
+    key_file: Option<&KeyFile>,
+) -> io::Result<usize> {
+    let paths = RewritePaths::new(journal);
+    let _lock = paths.lock()?;
+    paths.recover()?;
+    let _writers = match journal.backend {
+        Backend::Sqlite => None,
+        Backend::Files | Backend::Jsonl => Some(lock_dir(&paths.location)?),
+    };
+
+    let loaded = journal.open_raw().load_checked()?;
+    if !loaded.problems.is_empty() {
+        return Err(invalid(format!(
+            "{} unreadable records; run `captains_log doctor --fix` first",
+            loaded.problems.len()
+        )));
+    }
+    let mut entries = loaded.entries;
+    entries.sort_by_key(|e| e.timestamp);
+
+    let staged = stage(journal, &paths, &entries, convert, key_file);
+    if let Err(e) = staged {
+        let _ = paths.recover();
+        return Err(e);
+    }
+
+    // Nothing has touched the journal until here
+    std::fs::rename(&paths.location, &paths.backup)?;
+    paths.swap_in()?;
+    remove(&paths.backup)?;
+    Ok(entries.len())
+}
+
+/// Builds the complete new journal at the staging path.
+fn stage(
+    journal: &JournalConfig,
+    paths: &RewritePaths,
+    entries: &[JournalEntry],
+    convert: impl Fn(&JournalEntry) -> io::Result<JournalEntry>,
+    key_file: Option<&KeyFile>,
+) -> io::Result<()> {
+    let staging = paths.staging_config();
+    match journal.backend {
+        // Imported entries keep their source, and the database holds the
+        // activity history too
+        Backend::Sqlite => {
+            let db = SqliteJournal {
+                path: paths.location.clone(),
+            };
+            let target = SqliteJournal {
+                path: paths.staging.clone(),
+            };
+            let sourced = db
+                .load_sourced()?
+                .iter()
+                .map(|(source, entry)| Ok((source.clone(), convert(entry)?)))
+                .collect::<io::Result<Vec<_>>>()?;
+            target.insert_sourced(&sourced)?;
+            target.import_activity(&journal.open_activity().load_all()?)?;
+        }
+        Backend::Files | Backend::Jsonl => {
+            let target = staging.open_raw();
+            for entry in entries {
+                target.save(&convert(entry)?)?;
+            }
+            std::fs::create_dir_all(&paths.staging)?;
+            for item in std::fs::read_dir(&paths.location)? {
+                let item = item?;
+                let name = item.file_name().to_string_lossy().into_owned();
+                let skip = is_record(journal.backend, &name)
+                    || name == ".lock"
+                    || name == ".journal-key"
+                    || name.ends_with(".tmp");
+                if !skip {
+                    copy_tree(&item.path(), &paths.staging.join(&name))?;
+                }
+            }
+        }
+    }
+    if let Some(key_file) = key_file {
+        write_key_file(&staging.key_path(), key_file)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+//! Writing entries in the user's own editor.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// `$VISUAL`, then `$EDITOR`, then `vi`.
+pub fn command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|cmd| !cmd.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// Opens `initial` in the user's editor and returns the saved text, or `None`
+/// if it was left empty.
+pub fn edit(initial: &str) -> io::Result<Option<String>> {
+    edit_with(&command(), initial)
+}
+
+/// Like [`edit`], with an explicit editor command. The command goes through
+/// the shell, so values like `code --wait` work.
+pub fn edit_with(editor: &str, initial: &str) -> io::Result<Option<String>> {
+    let path = draft_path();
+    // Only the user may read the draft; the journal itself may be encrypted
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&path)?;
+    file.write_all(initial.as_bytes())?;
+    drop(file);
+
+    let result = run(editor, &path).and_then(|_| fs::read_to_string(&path));
+    let _ = fs::remove_file(&path);
+    let text = result?;
+    let text = text.trim_end();
+    Ok((!text.trim().is_empty()).then(|| text.to_string()))
+}
+
+fn draft_path() -> PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    std::env::temp_dir().join(format!("captains_log-{}-{}.md", std::process::id(), nanos))
+}
+
+fn run(editor: &str, path: &Path) -> io::Result<()> {
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("sh")
+        .arg(path)
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "`{}` exited with {}",
+            editor, status
+        )))
+    }
+}
@@ -0,0 +1,277 @@
+//! Exports the journal as JSON, CSV, a standalone HTML page or iCalendar.
+//!
+//! Entries are written as they stream out of the store, so exporting a large
+//! journal does not need it to fit in memory.
+
+use crate::journal::{self, JournalEntry, JournalStore};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Serialize;
+use std::fmt::Display;
+use std::io::{self, Write};
+
+/// Output formats of [`export`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One JSON array of `{timestamp, tags, content}` objects.
+    Json,
+    /// `timestamp,tags,content` rows with a header.
+    Csv,
+    /// A single styled HTML page, grouped by day.
+    Html,
+    /// An iCalendar file with one VJOURNAL per entry.
+    Ical,
+}
+
+impl Format {
+    /// File extension for files in this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Csv => "csv",
+            Format::Html => "html",
+            Format::Ical => "ics",
+        }
+    }
+}
+
+/// Which entries to export. Empty fields do not filter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    /// First instant included.
+    pub from: Option<DateTime<Utc>>,
+    /// First instant no longer included.
+    pub to: Option<DateTime<Utc>>,
+    /// Tag without the `#`, matched case-insensitively.
+    pub tag: Option<String>,
+}
+
+impl Filter {
+    pub fn matches(&self, entry: &JournalEntry) -> bool {
+        self.from.is_none_or(|from| entry.timestamp >= from)
+            && self.to.is_none_or(|to| entry.timestamp < to)
+            && self.tag.as_ref().is_none_or(|tag| {
+                journal::tags(&entry.content).contains(&tag.trim_start_matches('#').to_lowercase())
+            })
+    }
+}
+
+#[derive(Serialize)]
+struct Exported<'a> {
+    timestamp: DateTime<Utc>,
+    tags: Vec<String>,
+    content: &'a str,
+}
+
+/// Writes every entry of `store` that passes `filter` to `out`, oldest first.
+/// Local times in CSV and HTML use `tz`. Returns how many entries were written.
+pub fn export<Tz>(
+    store: &dyn JournalStore,
+    format: Format,
+    filter: &Filter,
+    tz: &Tz,
+    out: &mut dyn Write,
+) -> io::Result<usize>
+where
+    Tz: TimeZone,
+    Tz::Offset: Display,
+{
+    let mut count = 0;
+    let mut last_day = None;
+    begin(format, out)?;
+    store.for_each_entry(&mut |entry| {
+        if !filter.matches(&entry) {
+            return Ok(());
+        }
+        match format {
+            Format::Json => {
+                out.write_all(if count == 0 { b"\n  " } else { b",\n  " })?;
+                serde_json::to_writer(
+                    &mut *out,
+                    &Exported {
+                        timestamp: entry.timestamp,
+                        tags: journal::tags(&entry.content),
+                        content: &entry.content,
+                    },
+                )?;
+            }
+            Format::Csv => writeln!(
+                out,
+                "{},{},{}",
+                entry.timestamp.with_timezone(tz).to_rfc3339(),
+                csv_field(&journal::tags(&entry.content).join(" ")),
+                csv_field(&entry.content)
+            )?,
+            Format::Html => {
+                let local = entry.timestamp.with_timezone(tz);
+                let day = local.date_naive();
+                if last_day != Some(day) {
+                    if last_day.is_some() {
+                        out.write_all(b"</section>\n")?;
+                    }
+                    writeln!(out, "<section>\n<h2>{}</h2>", local.format("%A, %-d %B %Y"))?;
+                    last_day = Some(day);
+                }
+                write_html_entry(out, &entry, &local.format("%H:%M").to_string())?;
+            }
+            Format::Ical => write_vjournal(out, &entry)?,
+        }
+        count += 1;
+        Ok(())
+    })?;
+    if format == Format::Html && last_day.is_some() {
+        out.write_all(b"</section>\n")?;
+    }
+    end(format, count, out)?;
+    Ok(count)
+}
+
+const HTML_HEAD: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Captain's Log</title>
+<style>
+body { background: #0b0f0b; color: #c8e6c9; font: 15px/1.5 ui-monospace, Menlo, Consolas, monospace; max-width: 52rem; margin: 2rem auto; padding: 0 1rem; }
+h1 { color: #66bb6a; text-align: center; letter-spacing: .2em; }
+h2 { color: #81c784; border-bottom: 1px solid #2e7d32; margin-top: 2rem; font-size: 1.05rem; }
+article { display: flex; gap: 1rem; margin: .6rem 0; }
+time { color: #6b8f6b; flex: none; }
+.content { white-space: pre-wrap; overflow-wrap: anywhere; }
+.tag { color: #4dd0e1; }
+footer { color: #6b8f6b; text-align: center; margin-top: 3rem; font-size: .85rem; }
+</style>
+</head>
+<body>
+<h1>&gt;&gt;&gt; CAPTAIN'S LOG &lt;&lt;&lt;</h1>
+"#;
+
+fn begin(format: Format, out: &mut dyn Write) -> io::Result<()> {
+    match format {
+        Format::Json => out.write_all(b"["),
+        Format::Csv => out.write_all(b"timestamp,tags,content\n"),
+        Format::Html => out.write_all(HTML_HEAD.as_bytes()),
+        Format::Ical => out.write_all(
+            b"BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//captains_log//Journal Export//EN\r\n",
+        ),
+    }
+}
+
+fn end(format: Format, count: usize, out: &mut dyn Write) -> io::Result<()> {
+    match format {
+        Format::Json if count == 0 => out.write_all(b"]\n"),
+        Format::Json => out.write_all(b"\n]\n"),
+        Format::Csv => Ok(()),
+        Format::Html => write!(
+            out,
+            "<footer>{} entries</footer>\n</body>\n</html>\n",
+            count
+        ),
+        Format::Ical => out.write_all(b"END:VCALENDAR\r\n"),
+    }
+}
+
+/// Quotes a CSV field when it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn html_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn write_html_entry(out: &mut dyn Write, entry: &JournalEntry, time: &str) -> io::Result<()> {
+    let mut content = String::new();
+    for (i, line) in entry.content.split('\n').enumerate() {
+        if i > 0 {
+            content.push('\n');
+        }
+        for (j, word) in line.split(' ').enumerate() {
+            if j > 0 {
+                content.push(' ');
+            }
+            if journal::tags(word).is_empty() {
+                content.push_str(&html_escape(word));
+            } else {
+                content.push_str(&format!("<span class=\"tag\">{}</span>", html_escape(word)));
+            }
+        }
+    }
+    writeln!(
+        out,
+        "<article><time datetime=\"{}\">{}</time><div class=\"content\">{}</div></article>",
+        entry.timestamp.to_rfc3339(),
+        time,
+        content
+    )
+}
+
+/// Escapes a TEXT value (RFC 5545, 3.3.11).
+fn ical_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Writes one content line, folded at 75 octets (RFC 5545, 3.1).
+fn ical_line(out: &mut dyn Write, line: &str) -> io::Result<()> {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.write_all(b"\r\n ")?;
+            width = 1;
+        }
+        write!(out, "{}", c)?;
+        width += c.len_utf8();
+    }
+    out.write_all(b"\r\n")
+}
+
+/// A stable identifier, so re-importing an export updates instead of duplicating.
+fn uid(entry: &JournalEntry) -> String {
+    // FNV-1a: stable across builds, unlike the standard library hasher
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in entry.content.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!(
+        "{}-{:016x}@captains_log",
+        entry.timestamp.format("%Y%m%dT%H%M%S%.9fZ"),
+        hash
+    )
+}
+
+fn write_vjournal(out: &mut dyn Write, entry: &JournalEntry) -> io::Result<()> {
+    let stamp = entry.timestamp.format("%Y%m%dT%H%M%SZ");
+    let summary = entry.content.lines().next().unwrap_or("");
+    ical_line(out, "BEGIN:VJOURNAL")?;
+    ical_line(out, &format!("UID:{}", uid(entry)))?;
+    ical_line(out, &format!("DTSTAMP:{}", stamp))?;
+    ical_line(out, &format!("DTSTART:{}", stamp))?;
+    ical_line(out, &format!("SUMMARY:{}", ical_text(summary)))?;
+    ical_line(out, &format!("DESCRIPTION:{}", ical_text(&entry.content)))?;
+    let tags = journal::tags(&entry.content);
+    if !tags.is_empty() {
+        let tags: Vec<String> = tags.iter().map(|t| ical_text(t)).collect();
+        ical_line(out, &format!("CATEGORIES:{}", tags.join(",")))?;
+    }
+    ical_line(out, "END:VJOURNAL")
+}
@@ -0,0 +1,180 @@
+use crate::activity::read_records;
+use crate::journal::{LoadProblem, append_line};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A stretch of sustained typing that qualified as flow.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlowPeriod {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub keystrokes: u32,
+}
+
+impl FlowPeriod {
+    pub fn duration(&self) -> TimeDelta {
+        self.end - self.start
+    }
+
+    /// Length of the part of this period that falls inside `[from, to)`.
+    pub fn overlap(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> TimeDelta {
+        (self.end.min(to) - self.start.max(from)).max(TimeDelta::zero())
+    }
+}
+
+/// `[flow]` section of the config file.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct FlowConfig {
+    /// Minimum length of a session to count as flow.
+    pub min_minutes: u32,
+    /// Gaps between keystrokes at least this long count as long pauses.
+    pub long_pause_secs: u64,
+    /// Long pauses tolerated per hour of session.
+    pub max_long_pauses_per_hour: f64,
+    /// A gap this long ends the session.
+    pub break_secs: u64,
+    /// Add a journal entry whenever a flow period ends.
+    pub auto_journal: bool,
+}
+
+impl Default for FlowConfig {
+    fn default() -> Self {
+        FlowConfig {
+            min_minutes: 20,
+            long_pause_secs: 60,
+            max_long_pauses_per_hour: 6.0,
+            break_secs: 300,
+            auto_journal: false,
+        }
+    }
+}
+
+struct Session {
+    start: DateTime<Utc>,
+    last_key: DateTime<Utc>,
+    long_pauses: u32,
+    keystrokes: u32,
+}
+
+/// Watches the tick stream and reports flow periods as they end.
+///
+/// A session runs from the first keystroke until a gap of `break_secs`. It is
+/// flow once it has lasted `min_minutes` without more long pauses than
+/// `max_long_pauses_per_hour` allows for its length.
+pub struct FlowDetector {
+    config: FlowConfig,
+    session: Option<Session>,
+}
+
+impl FlowDetector {
+    pub fn new(config: FlowConfig) -> Self {
+        FlowDetector {
+            config,
+            session: None,
+        }
+    }
+
+    fn qualifies(&self, session: &Session) -> bool {
+        let length = session.last_key - session.start;
+        let hours = length.num_seconds() as f64 / 3600.0;
+        length >= TimeDelta::minutes(self.config.min_minutes as i64)
+            && session.long_pauses as f64 <= self.config.max_long_pauses_per_hour * hours
+    }
+
+    /// Feeds the keystrokes of the tick ending at `now`; returns a flow period that just ended.
+    pub fn on_tick(&mut self, now: DateTime<Utc>, keystrokes: u32) -> Option<FlowPeriod> {
+        let long_pause = TimeDelta::seconds(self.config.long_pause_secs as i64);
+        let break_after = TimeDelta::seconds(self.config.break_secs as i64);
+
+        if keystrokes > 0 {
+            match &mut self.session {
+                Some(session) if now - session.last_key < break_after => {
+                    if now - session.last_key >= long_pause {
+                        session.long_pauses += 1;
+                    }
+                    session.last_key = now;
+                    session.keystrokes += keystrokes;
+                    return None;
+                }
+                _ => {
+                    let ended = self.finish();
+                    self.session = Some(Session {
+                        start: now,
+                        last_key: now,
+                        long_pauses: 0,
+                        keystrokes,
+                    });
+                    return ended;
+                }
+            }
+        }
+
+        match &self.session {
+            Some(session) if now - session.last_key >= break_after => self.finish(),
+            _ => None,
+        }
+    }
+
+    /// Ends the current session, returning it if it qualified as flow.
+    pub fn finish(&mut self) -> Option<FlowPeriod> {
+        let session = self.session.take()?;
+        self.qualifies(&session).then_some(FlowPeriod {
+            start: session.start,
+            end: session.last_key,
+            keystrokes: session.keystrokes,
+        })
+    }
+
+    /// The ongoing flow period, if the current session already qualifies.
+    pub fn current(&self) -> Option<FlowPeriod> {
+        let session = self.session.as_ref()?;
+        self.qualifies(session).then_some(FlowPeriod {
+            start: session.start,
+            end: session.last_key,
+            keystrokes: session.keystrokes,
+        })
+    }
+}
+
+/// Persistent flow history, stored as one JSON-lines file per UTC month.
+pub struct FlowLog {
+    pub path: PathBuf,
+}
+
+impl Default for FlowLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FlowLog {
+    pub fn new() -> Self {
+        FlowLog {
+            path: PathBuf::from("flow"),
+        }
+    }
+
+    pub fn append(&self, period: &FlowPeriod) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.path)?;
+        let filename = self
+            .path
+            .join(format!("{}.jsonl", period.start.format("%Y-%m")));
+        append_line(&filename, &serde_json::to_string(period)?)?;
+        Ok(())
+    }
+
+    /// Every readable period, oldest first. Lines that fail to parse are
+    /// skipped; see [`FlowLog::load_checked`].
+    pub fn load_all(&self) -> std::io::Result<Vec<FlowPeriod>> {
+        Ok(self.load_checked()?.0)
+    }
+
+    /// Every readable period, oldest first, and the lines that were skipped.
+    pub fn load_checked(&self) -> std::io::Result<(Vec<FlowPeriod>, Vec<LoadProblem>)> {
+        let (mut periods, problems) = read_records::<FlowPeriod>(&self.path)?;
+        periods.sort_by_key(|p| p.start);
+        Ok((periods, problems))
+    }
+}
@@ -0,0 +1,198 @@
+//! Focus scoring.
+//!
+//! A [`FocusModel`] is fed the number of keystrokes seen in every UI tick and
+//! reports a focus level between 0 and 100. The default [`EmaModel`] tracks
+//! the share of recent time spent actively typing:
+//!
+//! - A tick counts as *active* if a key was pressed within the last
+//!   `active_grace_secs` seconds, so short thinking pauses do not count as idle.
+//! - The level is an exponential moving average of that active/idle signal with
+//!   a half-life of `half_life_secs`: after one half-life of steady typing from
+//!   zero the level is 50%, after two 75%, and so on. Idle time decays it the
+//!   same way.
+//! - Once a pause reaches `long_pause_secs`, a one-off penalty removes
+//!   `long_pause_penalty` (a fraction) of the current level.
+//!
+//! [`ClassicModel`] keeps the original behaviour (+1 per keystroke, -0.5 per
+//! tick after 10 s idle) for anyone who prefers it.
+
+use serde::Deserialize;
+
+pub trait FocusModel {
+    /// Feeds one tick of `keystrokes` that lasted `dt` seconds.
+    fn on_tick(&mut self, keystrokes: u32, dt: f64);
+    /// Current focus level in percent (0-100).
+    fn level(&self) -> f64;
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelKind {
+    #[default]
+    Ema,
+    Classic,
+}
+
+/// `[focus]` section of the config file.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct FocusConfig {
+    pub model: ModelKind,
+    /// Level from which the gauge reads "LOCKED IN".
+    pub locked_in_at: f64,
+    pub ema: EmaParams,
+    pub classic: ClassicParams,
+}
+
+impl Default for FocusConfig {
+    fn default() -> Self {
+        FocusConfig {
+            model: ModelKind::Ema,
+            locked_in_at: 80.0,
+            ema: EmaParams::default(),
+            classic: ClassicParams::default(),
+        }
+    }
+}
+
+impl FocusConfig {
+    pub fn build(&self) -> Box<dyn FocusModel> {
+        match self.model {
+            ModelKind::Ema => Box::new(EmaModel::new(self.ema.clone())),
+            ModelKind::Classic => Box::new(ClassicModel::new(self.classic.clone())),
+        }
+    }
+
+    /// Gauge label for a focus level.
+    pub fn describe(&self, level: f64) -> &'static str {
+        if level >= self.locked_in_at {
+            "LOCKED IN"
+        } else if level >= self.locked_in_at / 2.0 {
+            "FOCUSED"
+        } else if level > 0.5 {
+            "WARMING UP"
+        } else {
+            "IDLE"
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct EmaParams {
+    pub half_life_secs: f64,
+    pub active_grace_secs: f64,
+    pub long_pause_secs: f64,
+    pub long_pause_penalty: f64,
+}
+
+impl Default for EmaParams {
+    fn default() -> Self {
+        EmaParams {
+            half_life_secs: 180.0,
+            active_grace_secs: 5.0,
+            long_pause_secs: 120.0,
+            long_pause_penalty: 0.25,
+        }
+    }
+}
+
+pub struct EmaModel {
+    params: EmaParams,
+    /// Moving average of the active ratio, 0.0-1.0.
+    ratio: f64,
+    idle_secs: f64,
+    penalized: bool,
+}
+
+impl EmaModel {
+    pub fn new(params: EmaParams) -> Self {
+        EmaModel {
+            params,
+            ratio: 0.0,
+            idle_secs: f64::INFINITY,
+            penalized: true,
+        }
+    }
+}
+
+impl FocusModel for EmaModel {
+    fn on_tick(&mut self, keystrokes: u32, dt: f64) {
+        if keystrokes > 0 {
+            self.idle_secs = 0.0;
+            self.penalized = false;
+        } else {
+            self.idle_secs += dt;
+        }
+
+        let active = if self.idle_secs <= self.params.active_grace_secs {
+            1.0
+        } else {
+            0.0
+        };
+        let alpha = 1.0 - 0.5f64.powf(dt / self.params.half_life_secs.max(f64::EPSILON));
+        self.ratio += alpha * (active - self.ratio);
+
+        if !self.penalized && self.idle_secs >= self.params.long_pause_secs {
+            self.ratio *= 1.0 - self.params.long_pause_penalty.clamp(0.0, 1.0);
+            self.penalized = true;
+        }
+    }
+
+    fn level(&self) -> f64 {
+        (self.ratio * 100.0).clamp(0.0, 100.0)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ClassicParams {
+    pub per_keystroke: f64,
+    pub decay_per_tick: f64,
+    pub idle_after_secs: f64,
+}
+
+impl Default for ClassicParams {
+    fn default() -> Self {
+        ClassicParams {
+            per_keystroke: 1.0,
+            decay_per_tick: 0.5,
+            idle_after_secs: 10.0,
+        }
+    }
+}
+
+pub struct ClassicModel {
+    params: ClassicParams,
+    level: f64,
+    idle_secs: f64,
+}
+
+impl ClassicModel {
+    pub fn new(params: ClassicParams) -> Self {
+        ClassicModel {
+            params,
+            level: 100.0,
+            idle_secs: 0.0,
+        }
+    }
+}
+
+impl FocusModel for ClassicModel {
+    fn on_tick(&mut self, keystrokes: u32, dt: f64) {
+        if keystrokes > 0 {
+            self.idle_secs = 0.0;
+            self.level += keystrokes as f64 * self.params.per_keystroke;
+        } else {
+            self.idle_secs += dt;
+            if self.idle_secs > self.params.idle_after_secs {
+                self.level -= self.params.decay_per_tick;
+            }
+        }
+        self.level = self.level.clamp(0.0, 100.0);
+    }
+
+    fn level(&self) -> f64 {
+        self.level
+    }
+}
@@ -0,0 +1,232 @@
+use crate::crypto;
+use crate::journal::{JournalEntry, JournalStore, Loaded};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+/// Files in a journal directory that should never be committed.
+const GITIGNORE: &str = ".lock\n*.tmp\nindex.json\n";
+
+/// `[journal.git]` section of the config file.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct GitConfig {
+    /// Keep the journal directory in a git repository and commit every change.
+    pub enabled: bool,
+    /// Remote name or URL to push to after every commit. Nothing is pushed when unset.
+    pub push: Option<String>,
+}
+
+/// Runs `git` in `dir`, returning stdout or an error carrying git's stderr.
+fn git(dir: &Path, args: &[&str]) -> io::Result<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(io::Error::other(format!(
+            "git {}: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+/// Makes `dir` a git repository if it is not one yet.
+pub fn init(dir: &Path) -> io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    if !dir.join(".git").exists() {
+        git(dir, &["init", "--quiet"])?;
+    }
+    let ignore = dir.join(".gitignore");
+    if !ignore.exists() {
+        std::fs::write(ignore, GITIGNORE)?;
+    }
+    Ok(())
+}
+
+/// Commits everything that changed in `dir`, then pushes if configured.
+/// Returns false when there was nothing to commit.
+pub fn commit_all(dir: &Path, message: &str, config: &GitConfig) -> io::Result<bool> {
+    let committed = commit(dir, message)?;
+    if committed && let Some(remote) = &config.push {
+        push(dir, remote)?;
+    }
+    Ok(committed)
+}
+
+/// Pushes the current branch of `dir` to `remote`.
+fn push(dir: &Path, remote: &str) -> io::Result<()> {
+    git(dir, &["push", "--quiet", remote, "HEAD"])?;
+    Ok(())
+}
+
+/// Commits everything that changed in `dir`. Returns false when there was
+/// nothing to commit.
+fn commit(dir: &Path, message: &str) -> io::Result<bool> {
+    init(dir)?;
+    git(dir, &["add", "--all"])?;
+    if git(dir, &["diff", "--cached", "--quiet"]).is_ok() {
+        return Ok(false);
+    }
+    // Fall back to a local identity so commits work on machines without git set up
+    let mut args = Vec::new();
+    if git(dir, &["config", "user.email"]).is_err() {
+        args.extend([
+            "-c",
+            "user.name=Captain's Log",
+            "-c",
+            "user.email=captains_log@localhost",
+        ]);
+    }
+    args.extend(["commit", "--quiet", "--message", message]);
+    git(dir, &args)?;
+    Ok(true)
+}
+
+/// Commit message for a new entry: its time and, unless encrypted, its first line.
+pub fn entry_message(entry: &JournalEntry) -> String {
+    let time = entry.timestamp.format("%Y-%m-%d %H:%M:%S");
+    let first_line = entry.content.lines().next().unwrap_or("");
+    if crypto::is_encrypted(&entry.content) || first_line.is_empty() {
+        return format!("Add entry {}", time);
+    }
+    let mut preview: String = first_line.chars().take(50).collect();
+    if preview.len() < first_line.len() {
+        preview.push('…');
+    }
+    format!("Add entry {}: {}", time, preview)
+}
+
+/// Pushes from a background thread, so a slow or unreachable remote never
+/// holds up a save. Requests made while a push runs are folded into the next.
+struct Pusher {
+    requests: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+    last_error: Arc<Mutex<Option<String>>>,
+}
+
+impl Pusher {
+    fn start(dir: PathBuf, remote: String) -> Self {
+        let (requests, rx) = mpsc::channel::<()>();
+        let last_error = Arc::new(Mutex::new(None));
+        let error = Arc::clone(&last_error);
+        let thread = std::thread::spawn(move || {
+            while rx.recv().is_ok() {
+                while rx.try_recv().is_ok() {}
+                let result = push(&dir, &remote);
+                *error.lock().unwrap() = result.err().map(|e| e.to_string());
+            }
+        });
+        Pusher {
+            requests: Some(requests),
+            thread: Some(thread),
+            last_error,
+        }
+    }
+
+    fn request(&self) {
+        if let Some(requests) = &self.requests {
+            let _ = requests.send(());
+        }
+    }
+}
+
+impl Drop for Pusher {
+    /// Waits for a push still pending, so commits made just before exit reach the remote.
+    fn drop(&mut self) {
+        self.requests.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Wraps a directory-based store and commits the directory after every
+/// change. Pushing, if configured, happens in the background.
+pub struct GitJournal {
+    pub inner: Box<dyn JournalStore>,
+    pub dir: PathBuf,
+    pub config: GitConfig,
+    last_error: RefCell<Option<String>>,
+    pusher: Option<Pusher>,
+}
+
+impl GitJournal {
+    pub fn new(inner: Box<dyn JournalStore>, dir: PathBuf, config: GitConfig) -> Self {
+        let pusher = config
+            .push
+            .clone()
+            .map(|remote| Pusher::start(dir.clone(), remote));
+        GitJournal {
+            inner,
+            dir,
+            config,
+            last_error: RefCell::new(None),
+            pusher,
+        }
+    }
+
+    /// Commits, remembering a failure instead of returning it: the change
+    /// itself is on disk and the next successful commit will include it.
+    fn commit(&self, message: &str) {
+        let result = commit(&self.dir, message);
+        if let (Ok(true), Some(pusher)) = (&result, &self.pusher) {
+            pusher.request();
+        }
+        *self.last_error.borrow_mut() = result.err().map(|e| e.to_string());
+    }
+}
+
+impl JournalStore for GitJournal {
+    fn save(&self, entry: &JournalEntry) -> io::Result<()> {
+        self.inner.save(entry)?;
+        self.commit(&entry_message(entry));
+        Ok(())
+    }
+
+    fn load_all(&self) -> io::Result<Vec<JournalEntry>> {
+        self.inner.load_all()
+    }
+
+    fn load_checked(&self) -> io::Result<Loaded> {
+        self.inner.load_checked()
+    }
+
+    fn search(&self, query: &str) -> io::Result<Vec<JournalEntry>> {
+        self.inner.search(query)
+    }
+
+    fn for_each_entry(&self, f: &mut dyn FnMut(JournalEntry) -> io::Result<()>) -> io::Result<()> {
+        self.inner.for_each_entry(f)
+    }
+
+    fn quarantine(&self) -> io::Result<usize> {
+        let moved = self.inner.quarantine()?;
+        if moved > 0 {
+            self.commit(&format!("Quarantine {} unreadable records", moved));
+        }
+        Ok(moved)
+    }
+
+    fn upgrade(&self) -> io::Result<usize> {
+        let upgraded = self.inner.upgrade()?;
+        if upgraded > 0 {
+            self.commit(&format!(
+                "Upgrade {} entries to schema version {}",
+                upgraded,
+                crate::schema::CURRENT_VERSION
+            ));
+        }
+        Ok(upgraded)
+    }
+
+    fn status(&self) -> Option<String> {
+        let push_error = (self.pusher.as_ref()).and_then(|p| p.last_error.lock().unwrap().clone());
+        self.last_error.borrow().clone().or(push_error)
+    }
+}
@@ -0,0 +1,194 @@
+use chrono::{DateTime, Days, NaiveDate, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// `[goals]` section of the config file. A target of 0 disables that goal.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct GoalsConfig {
+    pub active_minutes: u32,
+    pub pomodoros: u32,
+    pub journal_entries: u32,
+    pub keystrokes: u32,
+    /// Length of one pomodoro.
+    pub pomodoro_minutes: u32,
+}
+
+impl Default for GoalsConfig {
+    fn default() -> Self {
+        GoalsConfig {
+            active_minutes: 120,
+            pomodoros: 0,
+            journal_entries: 1,
+            keystrokes: 0,
+            pomodoro_minutes: 25,
+        }
+    }
+}
+
+/// What has been achieved towards the goals on one local day.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub active_minutes: u32,
+    pub pomodoros: u32,
+    pub journal_entries: u32,
+    pub keystrokes: u32,
+}
+
+/// One enabled goal and how far along it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GoalStatus {
+    pub name: &'static str,
+    pub current: u32,
+    pub target: u32,
+}
+
+impl GoalStatus {
+    pub fn is_met(&self) -> bool {
+        self.current >= self.target
+    }
+}
+
+impl GoalsConfig {
+    /// Status of every enabled goal.
+    pub fn evaluate(&self, progress: &Progress) -> Vec<GoalStatus> {
+        [
+            ("ACTIVE MIN", progress.active_minutes, self.active_minutes),
+            ("POMODOROS", progress.pomodoros, self.pomodoros),
+            ("ENTRIES", progress.journal_entries, self.journal_entries),
+            ("KEYSTROKES", progress.keystrokes, self.keystrokes),
+        ]
+        .into_iter()
+        .filter(|(_, _, target)| *target > 0)
+        .map(|(name, current, target)| GoalStatus {
+            name,
+            current,
+            target,
+        })
+        .collect()
+    }
+
+    /// True when at least one goal is set and all of them are reached.
+    pub fn is_met(&self, progress: &Progress) -> bool {
+        let goals = self.evaluate(progress);
+        !goals.is_empty() && goals.iter().all(GoalStatus::is_met)
+    }
+
+    /// Average completion of the enabled goals, 0.0-1.0.
+    pub fn ratio(&self, progress: &Progress) -> f64 {
+        let goals = self.evaluate(progress);
+        if goals.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = goals
+            .iter()
+            .map(|g| (g.current as f64 / g.target as f64).min(1.0))
+            .sum();
+        total / goals.len() as f64
+    }
+}
+
+/// A finished (or in-progress) day in the goal history.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayRecord {
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub progress: Progress,
+    pub met: bool,
+}
+
+/// Consecutive met days ending at `today`. An unmet `today` does not break
+/// the streak yet, it just does not extend it.
+pub fn current_streak(records: &[DayRecord], today: NaiveDate) -> u32 {
+    let met = |date: NaiveDate| records.iter().any(|r| r.date == date && r.met);
+    let mut day = if met(today) {
+        today
+    } else {
+        today - Days::new(1)
+    };
+    let mut streak = 0;
+    while met(day) {
+        streak += 1;
+        day = day - Days::new(1);
+    }
+    streak
+}
+
+/// Longest run of consecutive met days in the history.
+pub fn best_streak(records: &[DayRecord]) -> u32 {
+    let mut dates: Vec<NaiveDate> = records.iter().filter(|r| r.met).map(|r| r.date).collect();
+    dates.sort();
+    dates.dedup();
+    let mut best = 0;
+    let mut run = 0;
+    let mut last: Option<NaiveDate> = None;
+    for date in dates {
+        run = match last {
+            Some(prev) if date - prev == TimeDelta::days(1) => run + 1,
+            _ => 1,
+        };
+        best = best.max(run);
+        last = Some(date);
+    }
+    best
+}
+
+/// Persistent per-day goal history, stored as a single JSON file.
+pub struct GoalLog {
+    pub path: PathBuf,
+}
+
+impl Default for GoalLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GoalLog {
+    pub fn new() -> Self {
+        GoalLog {
+            path: PathBuf::from("goals.json"),
+        }
+    }
+
+    pub fn load(&self) -> std::io::Result<Vec<DayRecord>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&self.path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Inserts or replaces the record for `record.date`.
+    pub fn upsert(&self, record: DayRecord) -> std::io::Result<Vec<DayRecord>> {
+        let mut records = self.load()?;
+        records.retain(|r| r.date != record.date);
+        records.push(record);
+        records.sort_by_key(|r| r.date);
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        crate::journal::write_atomic(
+            &self.path,
+            serde_json::to_string_pretty(&records)?.as_bytes(),
+        )?;
+        Ok(records)
+    }
+}
+
+/// A running pomodoro.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pomodoro {
+    pub started: DateTime<Utc>,
+    pub length: TimeDelta,
+}
+
+impl Pomodoro {
+    pub fn remaining(&self, now: DateTime<Utc>) -> TimeDelta {
+        (self.started + self.length - now).max(TimeDelta::zero())
+    }
+
+    pub fn is_done(&self, now: DateTime<Utc>) -> bool {
+        self.remaining(now).is_zero()
+    }
+}
@@ -0,0 +1,474 @@
+//! Importers for other journaling and note-taking tools.
+//!
+//! Parsers are pure functions from file contents to entries, so they can be
+//! tested without a file system. Times written without a zone are taken as
+//! local time in the `tz` passed in.
+
+use crate::journal::{self, JournalEntry};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde::Deserialize;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Source formats understood by [`read`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// A jrnl journal file or `jrnl --export json` output.
+    Jrnl,
+    /// A Day One JSON export (`Journal.json`).
+    DayOne,
+    /// A folder of Obsidian daily notes named `YYYY-MM-DD.md`.
+    Obsidian,
+    /// Markdown or `.txt` logs with dated lines or headings.
+    Text,
+}
+
+/// Entries parsed from a source, plus anything that was skipped.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Imported {
+    pub entries: Vec<JournalEntry>,
+    pub warnings: Vec<String>,
+}
+
+impl Imported {
+    fn extend(&mut self, other: Imported) {
+        self.entries.extend(other.entries);
+        self.warnings.extend(other.warnings);
+    }
+}
+
+/// What an import would add to a journal.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Plan {
+    /// Entries not yet in the journal, oldest first.
+    pub new: Vec<JournalEntry>,
+    /// Entries skipped because the journal (or the import itself) already has them.
+    pub duplicates: usize,
+}
+
+/// Drops entries that are already in `existing` or repeated in `imported`.
+pub fn plan(imported: Vec<JournalEntry>, existing: &[JournalEntry]) -> Plan {
+    let mut plan = Plan::default();
+    for entry in imported {
+        if existing.contains(&entry) || plan.new.contains(&entry) {
+            plan.duplicates += 1;
+        } else {
+            plan.new.push(entry);
+        }
+    }
+    plan.new.sort_by_key(|e| e.timestamp);
+    plan
+}
+
+/// Reads `path` (a file, or a folder for Obsidian and text logs) in `format`.
+pub fn read<Tz: TimeZone>(format: Format, path: &Path, tz: &Tz) -> io::Result<Imported> {
+    match format {
+        Format::Jrnl => Ok(parse_jrnl(&std::fs::read_to_string(path)?, tz)),
+        Format::DayOne => parse_day_one(&std::fs::read_to_string(path)?),
+        Format::Obsidian => {
+            let mut imported = Imported::default();
+            for file in files_with(path, &["md"])? {
+                let Some(date) = file
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
+                else {
+                    continue;
+                };
+                let mut note = parse_obsidian_note(date, &std::fs::read_to_string(&file)?, tz);
+                prefix_warnings(&mut note, &file);
+                imported.extend(note);
+            }
+            Ok(imported)
+        }
+        Format::Text => {
+            let mut imported = Imported::default();
+            for file in files_with(path, &["md", "txt"])? {
+                let modified: DateTime<Utc> = std::fs::metadata(&file)?.modified()?.into();
+                let mut log = parse_text(&std::fs::read_to_string(&file)?, tz, Some(modified));
+                prefix_warnings(&mut log, &file);
+                imported.extend(log);
+            }
+            Ok(imported)
+        }
+    }
+}
+
+fn prefix_warnings(imported: &mut Imported, file: &Path) {
+    for warning in &mut imported.warnings {
+        *warning = format!("{}: {}", file.display(), warning);
+    }
+}
+
+/// `path` itself if it is a file, otherwise every file below it with one of
+/// `extensions`, in path order.
+fn files_with(path: &Path, extensions: &[&str]) -> io::Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = Vec::new();
+    let mut dirs = vec![path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let hidden = path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .is_some_and(|s| s.starts_with('.'));
+            if hidden {
+                continue;
+            }
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path
+                .extension()
+                .and_then(|s| s.to_str())
+                .is_some_and(|e| extensions.contains(&e))
+            {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Appends `#tag`s the content does not already carry, on a line of their own.
+fn with_tags(content: &str, tags: &[String]) -> String {
+    let present = journal::tags(content);
+    let missing: Vec<String> = tags
+        .iter()
+        .map(|t| t.trim_start_matches(['#', '@']).trim().replace(' ', "-"))
+        .filter(|t| !t.is_empty() && !present.contains(&t.to_lowercase()))
+        .map(|t| format!("#{}", t))
+        .collect();
+    if missing.is_empty() {
+        content.to_string()
+    } else if content.is_empty() {
+        missing.join(" ")
+    } else {
+        format!("{}\n{}", content, missing.join(" "))
+    }
+}
+
+fn to_utc<Tz: TimeZone>(local: NaiveDateTime, tz: &Tz) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// A line that starts a dated entry: `2024-01-15 09:30 Title`,
+/// `[2024-01-15 09:30:00 AM] Title` or a heading such as `## 2024-01-15 09:30`.
+/// Returns the time and the rest of the line.
+fn parse_header(line: &str) -> Option<(NaiveDateTime, &str)> {
+    let mut rest = line.trim_end();
+    if rest.starts_with('#') {
+        let stripped = rest.trim_start_matches('#');
+        if !stripped.starts_with(' ') {
+            return None;
+        }
+        rest = stripped.trim_start();
+    }
+    let bracketed = rest.starts_with('[');
+    rest = rest.strip_prefix('[').unwrap_or(rest);
+    let (date, after_date) = NaiveDate::parse_and_remainder(rest, "%Y-%m-%d").ok()?;
+    rest = after_date;
+    let mut time = NaiveTime::MIN;
+    if let Some(after_space) = rest.strip_prefix(' ') {
+        for format in ["%I:%M:%S %p", "%I:%M %p", "%H:%M:%S", "%H:%M"] {
+            if let Ok((parsed, after_time)) = NaiveTime::parse_and_remainder(after_space, format) {
+                time = parsed;
+                rest = after_time;
+                break;
+            }
+        }
+    }
+    if bracketed {
+        rest = rest.strip_prefix(']')?;
+    }
+    // The date must stand on its own, not start a longer word
+    if !(rest.is_empty() || rest.starts_with([' ', ':', '-', '.'])) {
+        return None;
+    }
+    let title = rest.trim_start_matches([':', '-', '.']).trim();
+    Some((date.and_time(time), title))
+}
+
+/// Markdown or plain-text logs. Every dated line (see [`parse_header`]) starts
+/// an entry that runs until the next one. A file without dated lines becomes a
+/// single entry at `fallback` (usually its modification time).
+pub fn parse_text<Tz: TimeZone>(text: &str, tz: &Tz, fallback: Option<DateTime<Utc>>) -> Imported {
+    let mut imported = Imported::default();
+    let mut current: Option<(NaiveDateTime, Vec<&str>)> = None;
+    let mut preamble = Vec::new();
+
+    let finish = |current: Option<(NaiveDateTime, Vec<&str>)>, imported: &mut Imported| {
+        let Some((time, lines)) = current else {
+            return;
+        };
+        let content = lines.join("\n").trim().to_string();
+        if content.is_empty() {
+            return;
+        }
+        match to_utc(time, tz) {
+            Some(timestamp) => imported.entries.push(JournalEntry { timestamp, content }),
+            None => imported
+                .warnings
+                .push(format!("{} does not exist in the local time zone", time)),
+        }
+    };
+
+    for line in text.lines() {
+        if let Some((time, title)) = parse_header(line) {
+            finish(current.take(), &mut imported);
+            current = Some((
+                time,
+                if title.is_empty() {
+                    vec![]
+                } else {
+                    vec![title]
+                },
+            ));
+        } else if let Some((_, lines)) = &mut current {
+            lines.push(line);
+        } else {
+            preamble.push(line);
+        }
+    }
+    finish(current.take(), &mut imported);
+
+    let preamble = preamble.join("\n").trim().to_string();
+    if !preamble.is_empty() {
+        match fallback {
+            Some(timestamp) if imported.entries.is_empty() => imported.entries.push(JournalEntry {
+                timestamp,
+                content: preamble,
+            }),
+            _ => imported
+                .warnings
+                .push("skipped text before the first dated line".to_string()),
+        }
+    }
+    imported
+}
+
+#[derive(Deserialize)]
+struct JrnlExport {
+    entries: Vec<JrnlEntry>,
+}
+
+#[derive(Deserialize)]
+struct JrnlEntry {
+    date: String,
+    time: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    body: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// `@tag` is jrnl's default tag marker; it becomes `#tag`.
+fn jrnl_tags(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut at_word_start = true;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '@' && at_word_start && chars.peek().is_some_and(|n| n.is_alphanumeric()) {
+            out.push('#');
+        } else {
+            out.push(c);
+        }
+        at_word_start = c.is_whitespace() || c == '(';
+    }
+    out
+}
+
+/// A jrnl journal file, or the JSON from `jrnl --export json`.
+pub fn parse_jrnl<Tz: TimeZone>(text: &str, tz: &Tz) -> Imported {
+    let mut imported = Imported::default();
+    if text.trim_start().starts_with('{') {
+        let export: JrnlExport = match serde_json::from_str(text) {
+            Ok(export) => export,
+            Err(e) => {
+                imported.warnings.push(format!("not a jrnl export: {}", e));
+                return imported;
+            }
+        };
+        for entry in export.entries {
+            let time = format!("{} {}", entry.date, entry.time);
+            let Some(timestamp) = NaiveDateTime::parse_from_str(&time, "%Y-%m-%d %H:%M")
+                .ok()
+                .and_then(|t| to_utc(t, tz))
+            else {
+                imported.warnings.push(format!("bad date {}", time));
+                continue;
+            };
+            let content = format!("{}\n{}", entry.title, entry.body)
+                .trim()
+                .to_string();
+            imported.entries.push(JournalEntry {
+                timestamp,
+                content: with_tags(&jrnl_tags(&content), &entry.tags),
+            });
+        }
+        return imported;
+    }
+    let mut imported = parse_text(text, tz, None);
+    for entry in &mut imported.entries {
+        entry.content = jrnl_tags(&entry.content);
+    }
+    imported
+}
+
+#[derive(Deserialize)]
+struct DayOneExport {
+    entries: Vec<DayOneEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DayOneEntry {
+    creation_date: DateTime<Utc>,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Day One escapes Markdown punctuation in its exports (`\.`, `\-`, ...).
+fn day_one_unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.peek().is_some_and(|n| n.is_ascii_punctuation()) {
+            continue;
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// A Day One JSON export. Its times are already in UTC.
+pub fn parse_day_one(text: &str) -> io::Result<Imported> {
+    let export: DayOneExport = serde_json::from_str(text)?;
+    let mut imported = Imported::default();
+    for entry in export.entries {
+        let content = day_one_unescape(entry.text.trim());
+        if content.is_empty() && entry.tags.is_empty() {
+            imported
+                .warnings
+                .push(format!("skipped empty entry of {}", entry.creation_date));
+            continue;
+        }
+        imported.entries.push(JournalEntry {
+            timestamp: entry.creation_date,
+            content: with_tags(&content, &entry.tags),
+        });
+    }
+    Ok(imported)
+}
+
+/// Splits YAML front matter off a note and returns its `tags` and the body.
+fn front_matter(note: &str) -> (Vec<String>, &str) {
+    let Some(rest) = note.strip_prefix("---\n") else {
+        return (Vec::new(), note);
+    };
+    let Some(end) = rest.find("\n---") else {
+        return (Vec::new(), note);
+    };
+    let yaml = &rest[..end];
+    let body = &rest[end + 4..];
+    let body = body.strip_prefix('\n').unwrap_or(body);
+    let mut tags = Vec::new();
+    let mut in_list = false;
+    for line in yaml.lines() {
+        if let Some(value) = line.strip_prefix("tags:") {
+            let value = value.trim().trim_start_matches('[').trim_end_matches(']');
+            tags.extend(
+                value
+                    .split(',')
+                    .map(|t| t.trim().trim_matches(['"', '\'']).to_string())
+                    .filter(|t| !t.is_empty()),
+            );
+            in_list = value.is_empty();
+        } else if in_list && let Some(item) = line.trim_start().strip_prefix("- ") {
+            tags.push(item.trim().trim_matches(['"', '\'']).to_string());
+        } else {
+            in_list = false;
+        }
+    }
+    (tags, body)
+}
+
+/// A list item that starts with a time: `- 09:30 text` or `- [09:30] text`.
+fn timed_item(line: &str) -> Option<(NaiveTime, &str)> {
+    let rest = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))?;
+    let bracketed = rest.starts_with('[');
+    let rest = rest.strip_prefix('[').unwrap_or(rest);
+    let (time, rest) = NaiveTime::parse_and_remainder(rest, "%H:%M").ok()?;
+    let rest = if bracketed {
+        rest.strip_prefix(']')?
+    } else {
+        rest
+    };
+    Some((time, rest.trim_start_matches([' ', '-', ':']).trim_end()))
+}
+
+/// One Obsidian daily note. Timed list items (`- 09:30 ...`) become separate
+/// entries; otherwise the whole note is one entry at the start of the day.
+/// Tags from the front matter are added to every entry.
+pub fn parse_obsidian_note<Tz: TimeZone>(date: NaiveDate, note: &str, tz: &Tz) -> Imported {
+    let (tags, body) = front_matter(note);
+    let mut imported = Imported::default();
+    let push = |time: NaiveTime, content: String, imported: &mut Imported| {
+        let content = content.trim().to_string();
+        if content.is_empty() {
+            return;
+        }
+        match to_utc(date.and_time(time), tz) {
+            Some(timestamp) => imported.entries.push(JournalEntry {
+                timestamp,
+                content: with_tags(&content, &tags),
+            }),
+            None => imported.warnings.push(format!(
+                "{} {} does not exist in the local time zone",
+                date, time
+            )),
+        }
+    };
+
+    let mut items: Vec<(NaiveTime, Vec<&str>)> = Vec::new();
+    let mut other = Vec::new();
+    for line in body.lines() {
+        if let Some((time, text)) = timed_item(line) {
+            items.push((time, vec![text]));
+        } else if let Some((_, lines)) = items.last_mut()
+            && (line.starts_with([' ', '\t']) && !line.trim().is_empty())
+        {
+            lines.push(line.trim());
+        } else {
+            other.push(line);
+        }
+    }
+
+    if items.is_empty() {
+        push(NaiveTime::MIN, body.to_string(), &mut imported);
+    } else {
+        for (time, lines) in items {
+            push(time, lines.join("\n"), &mut imported);
+        }
+        if other.iter().any(|l| {
+            let l = l.trim();
+            !l.is_empty() && !l.starts_with('#')
+        }) {
+            imported
+                .warnings
+                .push(format!("{}: kept only the timed list items", date));
+        }
+    }
+    imported
+}
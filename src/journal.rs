@@ -1,13 +1,364 @@
+use crate::activity::{ActivityLog, ActivityStore};
+use crate::crypto::{Cipher, EncryptedStore};
+use crate::git_journal::{GitConfig, GitJournal};
+use crate::jsonl_journal::JsonlJournal;
+use crate::schema;
+use crate::sqlite_journal::SqliteJournal;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::fmt;
+use std::path::{Path, PathBuf};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct JournalEntry {
     pub timestamp: DateTime<Utc>,
     pub content: String,
 }
 
+/// Storage backend for journal entries.
+pub trait JournalStore {
+    fn save(&self, entry: &JournalEntry) -> std::io::Result<()>;
+    fn load_all(&self) -> std::io::Result<Vec<JournalEntry>>;
+
+    /// Entries whose content contains every word of `query`, ignoring case.
+    fn search(&self, query: &str) -> std::io::Result<Vec<JournalEntry>> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let mut entries = self.load_all()?;
+        entries.retain(|e| {
+            let content = e.content.to_lowercase();
+            words.iter().all(|w| content.contains(w.as_str()))
+        });
+        Ok(entries)
+    }
+
+    /// Like `load_all`, but skips unreadable records and reports them instead of failing.
+    fn load_checked(&self) -> std::io::Result<Loaded> {
+        Ok(Loaded {
+            entries: self.load_all()?,
+            problems: Vec::new(),
+        })
+    }
+
+    /// Moves unreadable records into a `quarantine` directory so they stop
+    /// being reported. Returns how many were moved.
+    fn quarantine(&self) -> std::io::Result<usize> {
+        Ok(0)
+    }
+
+    /// Rewrites records stored in an older schema version in the current one.
+    /// Returns how many were rewritten.
+    fn upgrade(&self) -> std::io::Result<usize> {
+        Ok(0)
+    }
+
+    /// Calls `f` with every readable entry, oldest first, without holding the
+    /// whole journal in memory where the store allows it.
+    fn for_each_entry(
+        &self,
+        f: &mut dyn FnMut(JournalEntry) -> std::io::Result<()>,
+    ) -> std::io::Result<()> {
+        let mut entries = self.load_all()?;
+        entries.sort_by_key(|e| e.timestamp);
+        entries.into_iter().try_for_each(f)
+    }
+
+    /// A problem that did not stop the last change, such as a failed commit or push.
+    fn status(&self) -> Option<String> {
+        None
+    }
+}
+
+/// A record that could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadProblem {
+    pub path: PathBuf,
+    /// 1-based line, for stores with several records per file.
+    pub line: Option<usize>,
+    pub error: String,
+}
+
+impl fmt::Display for LoadProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path.display(), line, self.error),
+            None => write!(f, "{}: {}", self.path.display(), self.error),
+        }
+    }
+}
+
+/// The readable entries of a store and the records that had to be skipped.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Loaded {
+    pub entries: Vec<JournalEntry>,
+    pub problems: Vec<LoadProblem>,
+}
+
+/// Writes `bytes` to a temporary file next to `path` and flushes it to disk.
+fn write_temp(path: &Path, bytes: &[u8]) -> std::io::Result<PathBuf> {
+    use std::io::Write;
+
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.tmp", std::process::id()));
+    let tmp = path.with_file_name(name);
+    let result = std::fs::File::create(&tmp).and_then(|mut file| {
+        file.write_all(bytes)?;
+        file.sync_all()
+    });
+    match result {
+        Ok(()) => Ok(tmp),
+        Err(e) => {
+            let _ = std::fs::remove_file(&tmp);
+            Err(e)
+        }
+    }
+}
+
+/// Persists a rename or link in `path`'s directory; not every platform can open a directory.
+pub(crate) fn sync_parent(path: &Path) {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    if let Ok(dir) = std::fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+}
+
+/// Writes `bytes` to `path` so that readers see either the old file or the
+/// complete new one: the data goes to a temporary file in the same directory,
+/// is flushed to disk, and is then renamed over `path`.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let tmp = write_temp(path, bytes)?;
+    if let Err(e) = std::fs::rename(&tmp, path) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e);
+    }
+    sync_parent(path);
+    Ok(())
+}
+
+/// Like [`write_atomic`], but fails with `AlreadyExists` instead of replacing
+/// an existing file, even if another process creates it concurrently.
+pub fn write_new(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let tmp = write_temp(path, bytes)?;
+    let linked = std::fs::hard_link(&tmp, path);
+    let _ = std::fs::remove_file(&tmp);
+    linked?;
+    sync_parent(path);
+    Ok(())
+}
+
+/// Appends `line` and a newline to `path` and flushes it to disk. A last line
+/// left unterminated by an interrupted write is ended first, so it stays a
+/// bad line of its own instead of corrupting the new one. Returns the new
+/// length of the file.
+pub fn append_line(path: &Path, line: &str) -> std::io::Result<u64> {
+    use std::io::{Read, Seek, SeekFrom, Write};
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)?;
+    let mut record = String::with_capacity(line.len() + 2);
+    if file.metadata()?.len() > 0 {
+        let mut last = [0u8];
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            record.push('\n');
+        }
+    }
+    record.push_str(line);
+    record.push('\n');
+    file.write_all(record.as_bytes())?;
+    file.sync_data()?;
+    Ok(file.metadata()?.len())
+}
+
+/// An exclusive advisory lock on a store directory, released on drop.
+pub struct DirLock {
+    _file: std::fs::File,
+}
+
+/// Blocks until no other process holds the lock on `dir`. Every writer takes
+/// it, so the TUI and CLI commands can run side by side.
+pub fn lock_dir(dir: &Path) -> std::io::Result<DirLock> {
+    std::fs::create_dir_all(dir)?;
+    lock_file(&dir.join(".lock"))
+}
+
+/// Blocks until no other process holds the lock file at `path`, creating it if needed.
+pub fn lock_file(path: &Path) -> std::io::Result<DirLock> {
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+    file.lock()?;
+    Ok(DirLock { _file: file })
+}
+
+/// `path` with `suffix` appended to its file name, e.g. `captains_log.db-wal`.
+pub fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// A file name for a new entry: its time, for sorting, then the process id
+/// and a per-process counter, so saves in the same millisecond never clash.
+pub fn entry_filename(time: DateTime<Utc>) -> String {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static SEQ: AtomicU64 = AtomicU64::new(0);
+    format!(
+        "{}-{}-{}.json",
+        time.format("%Y-%m-%d-%H-%M-%S-%3f"),
+        std::process::id(),
+        SEQ.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Moves `file` into `dir`, keeping its name and never overwriting an earlier
+/// quarantined file.
+pub(crate) fn move_into(file: &Path, dir: &Path) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    let mut target = dir.join(name.as_ref());
+    let mut n = 1;
+    while target.exists() {
+        target = dir.join(format!("{}.{}", name, n));
+        n += 1;
+    }
+    std::fs::rename(file, &target)?;
+    Ok(target)
+}
+
+/// `#hashtags` in `content`, lowercased and without the `#`, in order of appearance.
+/// Markdown headings (`# Title`) are not tags.
+pub fn tags(content: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    for word in content.split_whitespace() {
+        let Some(rest) = word.strip_prefix('#') else {
+            continue;
+        };
+        let tag: String = rest
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+            .collect::<String>()
+            .to_lowercase();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// One JSON file per entry (`journals/`).
+    #[default]
+    Files,
+    /// Append-only JSON-lines file per month (`journal_log/`).
+    Jsonl,
+    /// Embedded SQLite database (`captains_log.db`).
+    Sqlite,
+}
+
+/// `[journal]` section of the config file.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct JournalConfig {
+    pub backend: Backend,
+    /// Overrides the backend's default directory.
+    pub path: Option<PathBuf>,
+    /// Key for an encrypted journal, set once it has been unlocked.
+    #[serde(skip)]
+    pub cipher: Option<Cipher>,
+    pub git: GitConfig,
+}
+
+impl JournalConfig {
+    /// Where the configured store lives on disk.
+    pub fn location(&self) -> PathBuf {
+        match (&self.path, self.backend) {
+            (Some(path), _) => path.clone(),
+            (None, Backend::Files) => Journal::new().path,
+            (None, Backend::Jsonl) => JsonlJournal::new().path,
+            (None, Backend::Sqlite) => SqliteJournal::new().path,
+        }
+    }
+
+    /// The key file that marks this journal as encrypted.
+    pub fn key_path(&self) -> PathBuf {
+        match self.backend {
+            Backend::Sqlite => with_suffix(&self.location(), ".key"),
+            Backend::Files | Backend::Jsonl => self.location().join(".journal-key"),
+        }
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.key_path().exists()
+    }
+
+    /// The configured store, decrypting and encrypting entries if a key is set
+    /// and committing changes if git is enabled.
+    pub fn open(&self) -> Box<dyn JournalStore> {
+        let mut store = self.open_raw();
+        if self.git.enabled && self.backend != Backend::Sqlite {
+            store = Box::new(GitJournal::new(store, self.location(), self.git.clone()));
+        }
+        match &self.cipher {
+            Some(cipher) => Box::new(EncryptedStore {
+                inner: store,
+                cipher: cipher.clone(),
+                location: self.location(),
+            }),
+            None => store,
+        }
+    }
+
+    /// Where activity is kept: the database with the SQLite backend, otherwise
+    /// the `activity/` directory.
+    pub fn open_activity(&self) -> Box<dyn ActivityStore> {
+        match self.backend {
+            Backend::Sqlite => Box::new(SqliteJournal {
+                path: self.location(),
+            }),
+            Backend::Files | Backend::Jsonl => Box::new(ActivityLog::new()),
+        }
+    }
+
+    /// The configured store as it is on disk, without encryption.
+    pub fn open_raw(&self) -> Box<dyn JournalStore> {
+        match self.backend {
+            Backend::Files => {
+                let mut journal = Journal::new();
+                if let Some(path) = &self.path {
+                    journal.path = path.clone();
+                }
+                Box::new(journal)
+            }
+            Backend::Jsonl => {
+                let mut journal = JsonlJournal::new();
+                if let Some(path) = &self.path {
+                    journal.path = path.clone();
+                }
+                Box::new(journal)
+            }
+            Backend::Sqlite => {
+                let mut journal = SqliteJournal::new();
+                if let Some(path) = &self.path {
+                    journal.path = path.clone();
+                }
+                Box::new(journal)
+            }
+        }
+    }
+}
+
+/// The original layout: one JSON file per entry.
 pub struct Journal {
     pub path: PathBuf,
 }
@@ -26,34 +377,190 @@ impl Journal {
     }
 
     pub fn save(&self, entry: &JournalEntry) -> std::io::Result<()> {
-        use std::fs::{File, create_dir_all};
-        use std::io::Write;
-
-        create_dir_all(&self.path)?;
-        let filename = self.path.join(format!(
-            "{}.json",
-            Utc::now().format("%Y-%m-%d-%H-%M-%S-%3f")
-        ));
-        let mut file = File::create(filename)?;
-        let json = serde_json::to_string(entry)?;
-        file.write_all(json.as_bytes())?;
-        Ok(())
+        let _lock = lock_dir(&self.path)?;
+        let json = schema::encode(entry)?;
+        loop {
+            // The counter moves on with every call, so a clash cannot repeat
+            let filename = self.path.join(entry_filename(Utc::now()));
+            match write_new(&filename, json.as_bytes()) {
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                result => return result,
+            }
+        }
     }
 
+    /// Every readable entry. Files that fail to parse are skipped; see [`Journal::load_checked`].
     pub fn load_all(&self) -> std::io::Result<Vec<JournalEntry>> {
-        use std::fs;
-        let mut entries = Vec::new();
+        Ok(self.load_checked()?.entries)
+    }
+
+    fn entry_files(&self) -> std::io::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
         if !self.path.exists() {
-            return Ok(entries);
+            return Ok(files);
         }
-        for entry in fs::read_dir(&self.path)? {
-            let entry = entry?;
-            if entry.path().extension().and_then(|s| s.to_str()) == Some("json") {
-                let content = fs::read_to_string(entry.path())?;
-                let entry: JournalEntry = serde_json::from_str(&content)?;
-                entries.push(entry);
+        for entry in std::fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+                files.push(path);
             }
         }
+        Ok(files)
+    }
+
+    fn read_entry(path: &Path) -> Result<JournalEntry, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        schema::decode(&content)
+            .map(|(entry, _)| entry)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn load_checked(&self) -> std::io::Result<Loaded> {
+        let mut loaded = Loaded::default();
+        for path in self.entry_files()? {
+            match Self::read_entry(&path) {
+                Ok(entry) => loaded.entries.push(entry),
+                Err(error) => loaded.problems.push(LoadProblem {
+                    path,
+                    line: None,
+                    error,
+                }),
+            }
+        }
+        loaded.problems.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(loaded)
+    }
+
+    /// Every readable entry with the name of its file, oldest first, for
+    /// imports that must tell identical entries apart.
+    pub fn load_named(&self) -> std::io::Result<Vec<(String, JournalEntry)>> {
+        let mut entries: Vec<(String, JournalEntry)> = self
+            .entry_files()?
+            .into_iter()
+            .filter_map(|path| {
+                let entry = Self::read_entry(&path).ok()?;
+                Some((path.file_name()?.to_string_lossy().into_owned(), entry))
+            })
+            .collect();
+        entries.sort_by(|a, b| (a.1.timestamp, &a.0).cmp(&(b.1.timestamp, &b.0)));
         Ok(entries)
     }
+
+    /// Streams entries oldest first. Only timestamps are held while ordering;
+    /// each file is read again when its turn comes.
+    pub fn for_each_entry(
+        &self,
+        f: &mut dyn FnMut(JournalEntry) -> std::io::Result<()>,
+    ) -> std::io::Result<()> {
+        let mut order: Vec<(DateTime<Utc>, PathBuf)> = self
+            .entry_files()?
+            .into_iter()
+            .filter_map(|path| Some((Self::read_entry(&path).ok()?.timestamp, path)))
+            .collect();
+        order.sort();
+        for (_, path) in order {
+            if let Ok(entry) = Self::read_entry(&path) {
+                f(entry)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn quarantine_dir(&self) -> PathBuf {
+        self.path.join("quarantine")
+    }
+
+    /// Rewrites files in an older schema version in place.
+    pub fn upgrade(&self) -> std::io::Result<usize> {
+        if !self.path.exists() {
+            return Ok(0);
+        }
+        let _lock = lock_dir(&self.path)?;
+        let mut upgraded = 0;
+        for entry in std::fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            // Unreadable files are left for `doctor`
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Ok((entry, version)) = schema::decode(&content)
+                && version < schema::CURRENT_VERSION
+            {
+                write_atomic(&path, schema::encode(&entry)?.as_bytes())?;
+                upgraded += 1;
+            }
+        }
+        Ok(upgraded)
+    }
+
+    /// Moves every unreadable file into [`Journal::quarantine_dir`].
+    pub fn quarantine(&self) -> std::io::Result<usize> {
+        if !self.path.exists() {
+            return Ok(0);
+        }
+        let _lock = lock_dir(&self.path)?;
+        let problems = self.load_checked()?.problems;
+        for problem in &problems {
+            move_into(&problem.path, &self.quarantine_dir())?;
+        }
+        Ok(problems.len())
+    }
+}
+
+impl JournalStore for Journal {
+    fn save(&self, entry: &JournalEntry) -> std::io::Result<()> {
+        Journal::save(self, entry)
+    }
+
+    fn load_all(&self) -> std::io::Result<Vec<JournalEntry>> {
+        Journal::load_all(self)
+    }
+
+    fn load_checked(&self) -> std::io::Result<Loaded> {
+        Journal::load_checked(self)
+    }
+
+    fn quarantine(&self) -> std::io::Result<usize> {
+        Journal::quarantine(self)
+    }
+
+    fn upgrade(&self) -> std::io::Result<usize> {
+        Journal::upgrade(self)
+    }
+
+    fn for_each_entry(
+        &self,
+        f: &mut dyn FnMut(JournalEntry) -> std::io::Result<()>,
+    ) -> std::io::Result<()> {
+        Journal::for_each_entry(self, f)
+    }
+}
+
+/// Outcome of [`migrate`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Migration {
+    pub migrated: usize,
+    pub skipped: usize,
+}
+
+/// Copies every entry of `from` that `to` does not already hold, oldest first.
+/// The source is left untouched, so the migration can be re-run safely.
+pub fn migrate(from: &dyn JournalStore, to: &dyn JournalStore) -> std::io::Result<Migration> {
+    let existing = to.load_all()?;
+    let mut entries = from.load_all()?;
+    entries.sort_by_key(|e| e.timestamp);
+
+    let mut result = Migration::default();
+    for entry in entries {
+        if existing.contains(&entry) {
+            result.skipped += 1;
+        } else {
+            to.save(&entry)?;
+            result.migrated += 1;
+        }
+    }
+    Ok(result)
 }
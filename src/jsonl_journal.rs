@@ -0,0 +1,357 @@
+use crate::journal::{self, JournalEntry, JournalStore, LoadProblem, Loaded};
+use crate::schema;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// What the index knows about one month file.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonthIndex {
+    pub entries: usize,
+    /// Size of the month file when the index was last written.
+    pub bytes: u64,
+    /// Earliest and latest entry; unset while the month has no readable entries.
+    pub first: Option<DateTime<Utc>>,
+    pub last: Option<DateTime<Utc>>,
+}
+
+/// `index.json`: month (`YYYY-MM`) to its summary.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Index {
+    pub months: BTreeMap<String, MonthIndex>,
+}
+
+/// Append-only journal: one JSON-lines file per month of entry timestamps,
+/// plus an index so ranges can be read without opening every file.
+pub struct JsonlJournal {
+    pub path: PathBuf,
+}
+
+impl Default for JsonlJournal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonlJournal {
+    pub fn new() -> Self {
+        JsonlJournal {
+            path: PathBuf::from("journal_log"),
+        }
+    }
+
+    fn month_file(&self, month: &str) -> PathBuf {
+        self.path.join(format!("{}.jsonl", month))
+    }
+
+    fn index_file(&self) -> PathBuf {
+        self.path.join("index.json")
+    }
+
+    fn month_of(entry: &JournalEntry) -> String {
+        entry.timestamp.format("%Y-%m").to_string()
+    }
+
+    pub fn save(&self, entry: &JournalEntry) -> io::Result<()> {
+        let _lock = journal::lock_dir(&self.path)?;
+        let mut index = self.index()?;
+        let month = Self::month_of(entry);
+        let filename = self.month_file(&month);
+        let bytes = journal::append_line(&filename, &schema::encode(entry)?)?;
+
+        let summary = index.months.entry(month).or_insert(MonthIndex {
+            entries: 0,
+            bytes: 0,
+            first: None,
+            last: None,
+        });
+        summary.entries += 1;
+        summary.bytes = bytes;
+        summary.first = Some(
+            summary
+                .first
+                .map_or(entry.timestamp, |t| t.min(entry.timestamp)),
+        );
+        summary.last = Some(
+            summary
+                .last
+                .map_or(entry.timestamp, |t| t.max(entry.timestamp)),
+        );
+        self.write_index(&index)
+    }
+
+    /// The readable entries of a month file; bad lines are reported, not fatal.
+    fn read_month(&self, month: &str) -> io::Result<Loaded> {
+        let path = self.month_file(month);
+        let mut loaded = Loaded::default();
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                loaded.problems.push(LoadProblem {
+                    path,
+                    line: None,
+                    error: e.to_string(),
+                });
+                return Ok(loaded);
+            }
+            Err(e) => return Err(e),
+        };
+        for (i, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match schema::decode(line) {
+                Ok((entry, _)) => loaded.entries.push(entry),
+                Err(e) => loaded.problems.push(LoadProblem {
+                    path: path.clone(),
+                    line: Some(i + 1),
+                    error: e.to_string(),
+                }),
+            }
+        }
+        Ok(loaded)
+    }
+
+    fn months_on_disk(&self) -> io::Result<Vec<String>> {
+        let mut months = Vec::new();
+        if !self.path.exists() {
+            return Ok(months);
+        }
+        for entry in fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("jsonl")
+                && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
+            {
+                months.push(stem.to_string());
+            }
+        }
+        months.sort();
+        Ok(months)
+    }
+
+    /// The index, rebuilt from the month files if it is missing or stale.
+    pub fn index(&self) -> io::Result<Index> {
+        let index: Option<Index> = fs::read_to_string(self.index_file())
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok());
+        if let Some(index) = index
+            && self.is_current(&index)?
+        {
+            return Ok(index);
+        }
+        self.rebuild_index()
+    }
+
+    fn is_current(&self, index: &Index) -> io::Result<bool> {
+        let months = self.months_on_disk()?;
+        if months.len() != index.months.len() {
+            return Ok(false);
+        }
+        for month in months {
+            let Some(summary) = index.months.get(&month) else {
+                return Ok(false);
+            };
+            if fs::metadata(self.month_file(&month))?.len() != summary.bytes {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Re-reads every month file and writes a fresh index.
+    pub fn rebuild_index(&self) -> io::Result<Index> {
+        let mut index = Index::default();
+        for month in self.months_on_disk()? {
+            // Recorded even without readable entries, so the index matches the files
+            let entries = self.read_month(&month)?.entries;
+            index.months.insert(
+                month.clone(),
+                MonthIndex {
+                    entries: entries.len(),
+                    bytes: fs::metadata(self.month_file(&month))?.len(),
+                    first: entries.iter().map(|e| e.timestamp).min(),
+                    last: entries.iter().map(|e| e.timestamp).max(),
+                },
+            );
+        }
+        if self.path.exists() {
+            self.write_index(&index)?;
+        }
+        Ok(index)
+    }
+
+    fn write_index(&self, index: &Index) -> io::Result<()> {
+        journal::write_atomic(
+            &self.index_file(),
+            serde_json::to_string_pretty(index)?.as_bytes(),
+        )
+    }
+
+    /// Every readable entry. Lines that fail to parse are skipped; see [`JsonlJournal::load_checked`].
+    pub fn load_all(&self) -> io::Result<Vec<JournalEntry>> {
+        Ok(self.load_checked()?.entries)
+    }
+
+    pub fn load_checked(&self) -> io::Result<Loaded> {
+        let mut loaded = Loaded::default();
+        for month in self.months_on_disk()? {
+            let month = self.read_month(&month)?;
+            loaded.entries.extend(month.entries);
+            loaded.problems.extend(month.problems);
+        }
+        Ok(loaded)
+    }
+
+    /// Streams entries oldest first, holding one month at a time.
+    pub fn for_each_entry(
+        &self,
+        f: &mut dyn FnMut(JournalEntry) -> io::Result<()>,
+    ) -> io::Result<()> {
+        for month in self.months_on_disk()? {
+            let mut entries = self.read_month(&month)?.entries;
+            entries.sort_by_key(|e| e.timestamp);
+            entries.into_iter().try_for_each(&mut *f)?;
+        }
+        Ok(())
+    }
+
+    pub fn quarantine_dir(&self) -> PathBuf {
+        self.path.join("quarantine")
+    }
+
+    /// Rewrites month files containing lines in an older schema version.
+    /// Lines that cannot be parsed are kept as they are.
+    pub fn upgrade(&self) -> io::Result<usize> {
+        if !self.path.exists() {
+            return Ok(0);
+        }
+        let _lock = journal::lock_dir(&self.path)?;
+        let mut upgraded = 0;
+        for month in self.months_on_disk()? {
+            let file = self.month_file(&month);
+            let Ok(content) = fs::read_to_string(&file) else {
+                continue;
+            };
+            let mut out = String::with_capacity(content.len());
+            let mut changed = 0;
+            for line in content.lines() {
+                match schema::decode(line) {
+                    Ok((entry, version)) if version < schema::CURRENT_VERSION => {
+                        out.push_str(&schema::encode(&entry)?);
+                        changed += 1;
+                    }
+                    _ => out.push_str(line),
+                }
+                out.push('\n');
+            }
+            if changed > 0 {
+                journal::write_atomic(&file, out.as_bytes())?;
+                upgraded += changed;
+            }
+        }
+        if upgraded > 0 {
+            self.rebuild_index()?;
+        }
+        Ok(upgraded)
+    }
+
+    /// Moves unreadable lines to the same month file under
+    /// [`JsonlJournal::quarantine_dir`], rewrites the month without them and
+    /// rebuilds the index.
+    pub fn quarantine(&self) -> io::Result<usize> {
+        if !self.path.exists() {
+            return Ok(0);
+        }
+        let _lock = journal::lock_dir(&self.path)?;
+        let mut moved = 0;
+        for month in self.months_on_disk()? {
+            let file = self.month_file(&month);
+            let loaded = self.read_month(&month)?;
+            if loaded.problems.is_empty() {
+                continue;
+            }
+            moved += loaded.problems.len();
+            if loaded.problems.iter().any(|p| p.line.is_none()) {
+                journal::move_into(&file, &self.quarantine_dir())?;
+                continue;
+            }
+            let bad: Vec<usize> = loaded.problems.iter().filter_map(|p| p.line).collect();
+            let content = fs::read_to_string(&file)?;
+            let (mut keep, mut reject) = (String::new(), String::new());
+            for (i, line) in content.lines().enumerate() {
+                let out = if bad.contains(&(i + 1)) {
+                    &mut reject
+                } else {
+                    &mut keep
+                };
+                out.push_str(line);
+                out.push('\n');
+            }
+            fs::create_dir_all(self.quarantine_dir())?;
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.quarantine_dir().join(format!("{}.jsonl", month)))?
+                .write_all(reject.as_bytes())?;
+            journal::write_atomic(&file, keep.as_bytes())?;
+        }
+        if moved > 0 {
+            self.rebuild_index()?;
+        }
+        Ok(moved)
+    }
+
+    /// Entries with timestamps in `[from, to)`, reading only the months that can contain them.
+    pub fn load_range(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> io::Result<Vec<JournalEntry>> {
+        let index = self.index()?;
+        let mut entries = Vec::new();
+        for (month, summary) in &index.months {
+            let (Some(first), Some(last)) = (summary.first, summary.last) else {
+                continue;
+            };
+            if last < from || first >= to {
+                continue;
+            }
+            entries.extend(
+                self.read_month(month)?
+                    .entries
+                    .into_iter()
+                    .filter(|e| e.timestamp >= from && e.timestamp < to),
+            );
+        }
+        Ok(entries)
+    }
+}
+
+impl JournalStore for JsonlJournal {
+    fn save(&self, entry: &JournalEntry) -> io::Result<()> {
+        JsonlJournal::save(self, entry)
+    }
+
+    fn load_all(&self) -> io::Result<Vec<JournalEntry>> {
+        JsonlJournal::load_all(self)
+    }
+
+    fn load_checked(&self) -> io::Result<Loaded> {
+        JsonlJournal::load_checked(self)
+    }
+
+    fn quarantine(&self) -> io::Result<usize> {
+        JsonlJournal::quarantine(self)
+    }
+
+    fn upgrade(&self) -> io::Result<usize> {
+        JsonlJournal::upgrade(self)
+    }
+
+    fn for_each_entry(&self, f: &mut dyn FnMut(JournalEntry) -> io::Result<()>) -> io::Result<()> {
+        JsonlJournal::for_each_entry(self, f)
+    }
+}
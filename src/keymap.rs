@@ -0,0 +1,559 @@
+//! Key bindings: which key runs which action on each screen and mode.
+//!
+//! Bindings live in sections that are looked up in order, most specific
+//! first, so a key bound in `[keys.normal]` wins over the same key in
+//! `[keys.global]` while browsing the journal.
+
+use crate::commands::Action;
+use crate::state::Screen;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Built-in sets of bindings to start from.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyStyle {
+    /// Function keys and arrows; typing always goes into the input.
+    #[default]
+    Default,
+    /// Normal and insert modes: `i` to type, `Esc` to run commands with plain keys.
+    Vim,
+    /// `Alt+X` for commands, `Ctrl+N`/`Ctrl+P` to move and `Ctrl+G` to go back.
+    Emacs,
+}
+
+/// `[keys]` settings: a style plus per-section overrides of `"key" = "action"`.
+/// The action `"none"` removes a binding.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct KeysConfig {
+    pub style: KeyStyle,
+    pub global: BTreeMap<String, String>,
+    pub journal: BTreeMap<String, String>,
+    pub normal: BTreeMap<String, String>,
+    pub insert: BTreeMap<String, String>,
+    pub editor: BTreeMap<String, String>,
+    pub stats: BTreeMap<String, String>,
+    pub detail: BTreeMap<String, String>,
+}
+
+impl KeysConfig {
+    /// The style's bindings with the overrides applied.
+    pub fn keymap(&self) -> Result<Keymap, String> {
+        let mut keymap = Keymap::preset(self.style);
+        let sections = [
+            (Section::Global, &self.global),
+            (Section::Journal, &self.journal),
+            (Section::Normal, &self.normal),
+            (Section::Insert, &self.insert),
+            (Section::Editor, &self.editor),
+            (Section::Stats, &self.stats),
+            (Section::Detail, &self.detail),
+        ];
+        for (section, table) in sections {
+            let mut seen = Vec::new();
+            for (key, id) in table {
+                let at = || format!("[keys.{}] \"{}\"", section.name(), key);
+                let chord: KeyChord = key.parse().map_err(|e| format!("{}: {}", at(), e))?;
+                if seen.contains(&chord) {
+                    return Err(format!("{}: {} is bound more than once", at(), chord));
+                }
+                seen.push(chord);
+                let action = match id.as_str() {
+                    "none" => None,
+                    id => Some(
+                        Action::from_id(id)
+                            .ok_or_else(|| format!("{}: unknown action `{}`", at(), id))?,
+                    ),
+                };
+                keymap.bind(section, chord, action, true);
+            }
+        }
+        Ok(keymap)
+    }
+}
+
+/// A group of bindings that applies in some situations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    /// Every screen.
+    Global,
+    /// The journal screen, whether typing or not.
+    Journal,
+    /// The journal while not typing: normal mode, or an empty input.
+    Normal,
+    /// The journal while typing an entry.
+    Insert,
+    Editor,
+    Stats,
+    Detail,
+}
+
+impl Section {
+    pub const ALL: [Section; 7] = [
+        Section::Global,
+        Section::Journal,
+        Section::Normal,
+        Section::Insert,
+        Section::Editor,
+        Section::Stats,
+        Section::Detail,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Section::Global => "global",
+            Section::Journal => "journal",
+            Section::Normal => "normal",
+            Section::Insert => "insert",
+            Section::Editor => "editor",
+            Section::Stats => "stats",
+            Section::Detail => "detail",
+        }
+    }
+}
+
+/// A key with its modifiers, like `Ctrl+P`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        let code = match code {
+            // The character already says whether Shift was held
+            KeyCode::Char(c) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                if modifiers.contains(KeyModifiers::CONTROL) {
+                    KeyCode::Char(c.to_ascii_lowercase())
+                } else {
+                    KeyCode::Char(c)
+                }
+            }
+            KeyCode::BackTab => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            code => code,
+        };
+        Self { code, modifiers }
+    }
+
+    /// A plain character, which would otherwise be typed into the input.
+    pub fn is_text(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_))
+            && !self
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    }
+}
+
+impl From<KeyEvent> for KeyChord {
+    fn from(key: KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers)
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    /// Parses keys like `ctrl+p`, `alt+up`, `f2`, `pgdn`, `?` or `shift+tab`.
+    fn from_str(text: &str) -> Result<Self, String> {
+        let (prefix, key) = match text.strip_suffix("++") {
+            Some(prefix) => (prefix, "+"),
+            None if text == "+" => ("", "+"),
+            None => text.rsplit_once('+').unwrap_or(("", text)),
+        };
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier in prefix.split('+').filter(|m| !m.is_empty()) {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                other => return Err(format!("unknown modifier `{}`", other)),
+            };
+        }
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" => KeyCode::PageDown,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=24) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key `{}`", key)),
+                },
+            },
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            // Ctrl+letter arrives in lower case either way
+            KeyCode::Char(c) if self.modifiers.contains(KeyModifiers::CONTROL) => {
+                write!(f, "{}", c.to_ascii_uppercase())
+            }
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::Up => f.write_str("↑"),
+            KeyCode::Down => f.write_str("↓"),
+            KeyCode::Left => f.write_str("←"),
+            KeyCode::Right => f.write_str("→"),
+            KeyCode::PageUp => f.write_str("PgUp"),
+            KeyCode::PageDown => f.write_str("PgDn"),
+            KeyCode::BackTab => f.write_str("Shift+Tab"),
+            KeyCode::Delete => f.write_str("Del"),
+            KeyCode::Insert => f.write_str("Ins"),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    pub section: Section,
+    pub chord: KeyChord,
+    pub action: Action,
+    /// Set in the config file rather than by the style.
+    pub custom: bool,
+}
+
+/// The active key bindings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    /// Whether the journal has a normal mode where plain keys run commands.
+    pub modal: bool,
+    pub bindings: Vec<Binding>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::preset(KeyStyle::Default)
+    }
+}
+
+impl Keymap {
+    /// The bindings of a built-in style.
+    pub fn preset(style: KeyStyle) -> Self {
+        use Action::*;
+        use Section::*;
+        let mut keymap = Keymap {
+            modal: style == KeyStyle::Vim,
+            bindings: Vec::new(),
+        };
+        let mut bind = |section, keys: &[(&str, Action)]| {
+            for &(key, action) in keys {
+                let chord = key.parse().expect("built-in keys parse");
+                keymap.bind(section, chord, Some(action), false);
+            }
+        };
+        bind(
+            Global,
+            &[
+                ("ctrl+p", Palette),
+                ("f1", Help),
+                ("f2", ToggleStats),
+                ("f4", TogglePomodoro),
+                ("f5", RetrySaving),
+                ("f8", InsertTemplate),
+                ("f9", CycleLayout),
+                ("f10", Quit),
+            ],
+        );
+        bind(
+            Journal,
+            &[
+                ("f3", ToggleMultiline),
+                ("f6", OpenEditor),
+                ("f7", ExternalEditor),
+                ("alt+left", PanLeft),
+                ("alt+right", PanRight),
+                ("alt+up", ZoomIn),
+                ("alt+down", ZoomOut),
+                ("ctrl+up", GrowInput),
+                ("ctrl+down", ShrinkInput),
+            ],
+        );
+        bind(
+            Normal,
+            &[
+                ("up", SelectPrevious),
+                ("down", SelectNext),
+                ("enter", OpenEntry),
+                ("esc", ClearSelection),
+            ],
+        );
+        bind(
+            Insert,
+            &[
+                ("enter", SubmitOrNewLine),
+                ("ctrl+s", Submit),
+                ("ctrl+enter", Submit),
+                ("alt+enter", NewLine),
+            ],
+        );
+        bind(
+            Editor,
+            &[
+                ("ctrl+s", Submit),
+                ("ctrl+enter", Submit),
+                ("esc", ShowJournal),
+                ("f6", ShowJournal),
+                ("f7", ExternalEditor),
+            ],
+        );
+        // Bare characters only where nothing is typed; the journal input
+        // must be able to start with them
+        bind(
+            Stats,
+            &[
+                ("tab", CycleStatsPeriod),
+                ("esc", ShowJournal),
+                (":", Palette),
+                ("?", Help),
+            ],
+        );
+        bind(
+            Detail,
+            &[
+                (":", Palette),
+                ("?", Help),
+                ("up", ScrollUp),
+                ("down", ScrollDown),
+                ("pgup", PageUp),
+                ("pgdn", PageDown),
+                ("esc", ShowJournal),
+            ],
+        );
+        match style {
+            KeyStyle::Default => {}
+            KeyStyle::Vim => {
+                bind(
+                    Normal,
+                    &[
+                        ("i", InsertMode),
+                        ("a", InsertMode),
+                        ("k", SelectPrevious),
+                        ("j", SelectNext),
+                        ("l", OpenEntry),
+                        ("/", Search),
+                        ("e", OpenEditor),
+                        ("shift+e", ExternalEditor),
+                        ("t", InsertTemplate),
+                        (":", Palette),
+                        ("?", Help),
+                    ],
+                );
+                bind(Insert, &[("esc", NormalMode)]);
+                bind(Stats, &[("q", ShowJournal)]);
+                bind(
+                    Detail,
+                    &[
+                        ("k", ScrollUp),
+                        ("j", ScrollDown),
+                        ("ctrl+u", PageUp),
+                        ("ctrl+d", PageDown),
+                        ("q", ShowJournal),
+                    ],
+                );
+            }
+            KeyStyle::Emacs => {
+                bind(Global, &[("alt+x", Palette)]);
+                bind(
+                    Normal,
+                    &[
+                        ("ctrl+p", SelectPrevious),
+                        ("ctrl+n", SelectNext),
+                        ("ctrl+s", Search),
+                        ("ctrl+g", ClearSelection),
+                    ],
+                );
+                bind(Editor, &[("ctrl+g", ShowJournal)]);
+                bind(Stats, &[("ctrl+g", ShowJournal)]);
+                bind(
+                    Detail,
+                    &[
+                        ("ctrl+p", ScrollUp),
+                        ("ctrl+n", ScrollDown),
+                        ("alt+v", PageUp),
+                        ("ctrl+v", PageDown),
+                        ("ctrl+g", ShowJournal),
+                    ],
+                );
+                keymap.bind(Global, "ctrl+p".parse().unwrap(), None, false);
+            }
+        }
+        keymap
+    }
+
+    /// Binds `chord` in `section` to `action`, replacing what it did there.
+    /// `None` unbinds it.
+    pub fn bind(
+        &mut self,
+        section: Section,
+        chord: KeyChord,
+        action: Option<Action>,
+        custom: bool,
+    ) {
+        self.bindings
+            .retain(|b| !(b.section == section && b.chord == chord));
+        if let Some(action) = action {
+            self.bindings.push(Binding {
+                section,
+                chord,
+                action,
+                custom,
+            });
+        }
+    }
+
+    fn get(&self, section: Section, chord: KeyChord) -> Option<&Binding> {
+        self.bindings
+            .iter()
+            .find(|b| b.section == section && b.chord == chord)
+    }
+
+    /// Sections consulted on `screen`, most specific first. `normal` is
+    /// whether the journal is in normal mode or, without modes, has an empty input.
+    pub fn layers(&self, screen: Screen, normal: bool) -> &'static [Section] {
+        use Section::*;
+        match screen {
+            Screen::Journal if normal && self.modal => &[Normal, Journal, Global],
+            Screen::Journal if normal => &[Normal, Insert, Journal, Global],
+            Screen::Journal => &[Insert, Journal, Global],
+            Screen::Editor => &[Editor, Global],
+            Screen::Stats => &[Stats, Global],
+            Screen::Detail => &[Detail, Global],
+        }
+    }
+
+    /// Whether keys without a binding are typed into the input.
+    pub fn types_text(&self, screen: Screen, normal: bool) -> bool {
+        match screen {
+            Screen::Journal => !(normal && self.modal),
+            Screen::Editor => true,
+            Screen::Stats | Screen::Detail => false,
+        }
+    }
+
+    /// What `chord` does with these sections active.
+    pub fn lookup(&self, layers: &[Section], chord: KeyChord) -> Option<Action> {
+        layers
+            .iter()
+            .find_map(|&section| self.get(section, chord))
+            .map(|b| b.action)
+    }
+
+    /// A key that runs `action` with these sections active, preferring the
+    /// broadest section so hints do not change as the input fills.
+    pub fn key_for(&self, layers: &[Section], action: Action) -> Option<KeyChord> {
+        layers.iter().rev().find_map(|&section| {
+            self.bindings
+                .iter()
+                .find(|b| b.section == section && b.action == action)
+                .filter(|b| self.lookup(layers, b.chord) == Some(action))
+                .map(|b| b.chord)
+        })
+    }
+
+    /// Every key that works with these sections active, with what it does.
+    pub fn help(&self, layers: &[Section]) -> Vec<(KeyChord, Action)> {
+        let mut keys: Vec<(KeyChord, Action)> = Vec::new();
+        for &section in layers {
+            for b in self.bindings.iter().filter(|b| b.section == section) {
+                if !keys.iter().any(|(chord, _)| *chord == b.chord) {
+                    keys.push((b.chord, b.action));
+                }
+            }
+        }
+        keys
+    }
+
+    /// Custom bindings that hide another binding, or a key that would
+    /// otherwise be typed, somewhere they are active.
+    pub fn conflicts(&self) -> Vec<String> {
+        let contexts = [
+            Screen::Journal,
+            Screen::Editor,
+            Screen::Stats,
+            Screen::Detail,
+        ]
+        .into_iter()
+        .flat_map(|screen| [(screen, true), (screen, false)]);
+        let mut found = Vec::new();
+        for (screen, normal) in contexts {
+            let layers = self.layers(screen, normal);
+            for (i, &section) in layers.iter().enumerate() {
+                for b in self.bindings.iter().filter(|b| b.section == section) {
+                    let hidden_by = layers[..i]
+                        .iter()
+                        .find_map(|&s| self.get(s, b.chord))
+                        .filter(|first| first.action != b.action && (first.custom || b.custom));
+                    let conflict = match hidden_by {
+                        Some(first) => format!(
+                            "{} runs {} from [keys.{}], so {} in [keys.{}] never runs there",
+                            b.chord,
+                            first.action.command().id,
+                            first.section.name(),
+                            b.action.command().id,
+                            section.name()
+                        ),
+                        None if b.custom
+                            && b.chord.is_text()
+                            && self.types_text(screen, normal) =>
+                        {
+                            format!(
+                                "{} in [keys.{}] can no longer be typed into an entry",
+                                b.chord,
+                                section.name()
+                            )
+                        }
+                        None => continue,
+                    };
+                    if !found.contains(&conflict) {
+                        found.push(conflict);
+                    }
+                }
+            }
+        }
+        found
+    }
+}
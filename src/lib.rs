@@ -1,6 +1,28 @@
+pub mod activity;
+pub mod cli;
+pub mod commands;
+pub mod config;
+pub mod crypto;
+pub mod editor;
+pub mod export;
+pub mod flow;
+pub mod focus;
+pub mod git_journal;
+pub mod goals;
+pub mod import;
 pub mod journal;
+pub mod jsonl_journal;
+pub mod keymap;
+pub mod markdown;
 pub mod monitor;
+pub mod schema;
+pub mod sqlite_journal;
 pub mod state;
+pub mod stats;
+pub mod templates;
+pub mod theme;
+pub mod timeline;
 pub mod ui;
+pub mod watch;
 
 pub use state::App;
@@ -1,30 +1,76 @@
-use captains_log::{App, monitor, ui};
+use captains_log::cli::{self, Cli};
+use captains_log::commands::Action;
+use captains_log::config::Config;
+use captains_log::crypto;
+use captains_log::editor;
+use captains_log::state::TICK_MILLIS;
+use captains_log::{App, monitor, ui, watch};
+use clap::Parser;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
     execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+    terminal::{
+        EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+        supports_keyboard_enhancement,
+    },
 };
 use ratatui::{Terminal, backend::CrosstermBackend};
 use std::error::Error;
+use std::io::Stdout;
 use std::sync::mpsc;
 use std::time::{Duration, Instant};
 
 fn main() -> Result<(), Box<dyn Error>> {
+    // Load config before entering the alternate screen so errors stay visible
+    let args = Cli::parse();
+    let mut config = Config::load(&Config::default_path())?;
+    if crypto::recover(&config.journal)? {
+        eprintln!("Recovered from an interrupted encrypt or decrypt of the journal.");
+    }
+    if config.journal.is_encrypted() {
+        let passphrase = crypto::read_passphrase("Journal passphrase: ")?;
+        config.journal.cipher = Some(crypto::unlock(&config.journal.key_path(), &passphrase)?);
+    }
+    if let Some(command) = args.command {
+        return cli::run(command, &config);
+    }
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = std::io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    // Lets terminals that can tell Ctrl+Enter apart from Enter report it
+    let enhanced = supports_keyboard_enhancement().unwrap_or(false);
+    if enhanced {
+        execute!(
+            stdout,
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        )?;
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     // Create app state
-    let mut app = App::new();
+    let mut app = App::with_config(config);
 
     // Start input monitor
     let (tx, rx) = mpsc::channel();
     monitor::start_monitor(tx);
 
-    let tick_rate = Duration::from_millis(250);
+    // Pick up entries written by other instances or CLI commands
+    let (journal_tx, journal_rx) = mpsc::channel();
+    let _watcher = match watch::watch_journal(&app.config.journal.location(), journal_tx) {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            app.error = Some(format!("NOT WATCHING JOURNAL: {}", e));
+            None
+        }
+    };
+
+    let tick_rate = Duration::from_millis(TICK_MILLIS);
     let mut last_tick = Instant::now();
 
     loop {
@@ -34,31 +80,17 @@ fn main() -> Result<(), Box<dyn Error>> {
             .checked_sub(last_tick.elapsed())
             .unwrap_or_else(|| Duration::from_secs(0));
 
-        if crossterm::event::poll(timeout)?
-            && let Event::Key(key) = event::read()? {
-                // Handle global shortcuts
-                match key.code {
-                    KeyCode::F(10) => app.should_quit = true,
-                    KeyCode::Enter => {
-                        // Add log entry
-                        let content = app.textarea.lines().join("\n");
-                        if !content.trim().is_empty() {
-                            app.add_log(content);
-                            // Reset textarea
-                            app.textarea = tui_textarea::TextArea::default();
-                            app.textarea.set_placeholder_text("Add log entry...");
-                            app.textarea.set_block(
-                                ratatui::widgets::Block::default()
-                                    .borders(ratatui::widgets::Borders::ALL)
-                                    .title("Input"),
-                            );
-                        }
-                    }
-                    _ => {
-                        app.textarea.input(key);
+        if crossterm::event::poll(timeout)? {
+            match event::read()? {
+                Event::Key(key) => {
+                    if let Some(action) = app.handle_key(key) {
+                        perform(action, &mut app, &mut terminal, enhanced)?;
                     }
                 }
+                Event::Mouse(mouse) => app.handle_mouse(mouse, terminal.size()?),
+                _ => {}
             }
+        }
 
         // Check monitor events
         let mut activity_count = 0;
@@ -67,14 +99,15 @@ fn main() -> Result<(), Box<dyn Error>> {
             activity_count += 1;
         }
 
+        if journal_rx.try_iter().count() > 0 {
+            app.reload_journal();
+        }
+
         // Update activity stream
         if last_tick.elapsed() >= tick_rate {
             app.on_tick();
             // Add activity count to stream
-            if app.activity_stream.len() >= 100 {
-                app.activity_stream.pop_front();
-            }
-            app.activity_stream.push_back(activity_count);
+            app.record_tick(activity_count);
 
             last_tick = Instant::now();
         }
@@ -84,14 +117,80 @@ fn main() -> Result<(), Box<dyn Error>> {
         }
     }
 
+    app.finish_flow();
+    app.flush_activity();
+    app.save_goals();
+
     // Restore terminal
+    suspend(&mut terminal, enhanced)?;
+
+    // Last chance for entries that never reached the disk; print them rather than lose them
+    if !app.unsaved.is_empty() {
+        app.retry_unsaved();
+    }
+    if !app.unsaved.is_empty() {
+        eprintln!("{}", app.error.as_deref().unwrap_or("JOURNAL NOT SAVED"));
+        for entry in &app.unsaved {
+            eprintln!("[{}] {}", entry.timestamp.to_rfc3339(), entry.content);
+        }
+        return Err("journal entries could not be saved".into());
+    }
+
+    Ok(())
+}
+
+/// Runs `action`, handing the terminal to `$VISUAL`/`$EDITOR` if it needs to.
+fn perform(
+    action: Action,
+    app: &mut App,
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    enhanced: bool,
+) -> std::io::Result<()> {
+    if action != Action::ExternalEditor {
+        app.run(action);
+        return Ok(());
+    }
+    let draft = app.textarea.lines().join("\n");
+    suspend(terminal, enhanced)?;
+    let edited = editor::edit(&draft);
+    resume(terminal, enhanced)?;
+    app.save_edited(edited);
+    Ok(())
+}
+
+/// Gives the terminal back to the shell, for exit or an external editor.
+fn suspend(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    enhanced: bool,
+) -> std::io::Result<()> {
+    if enhanced {
+        execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags)?;
+    }
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
         DisableMouseCapture
     )?;
-    terminal.show_cursor()?;
+    terminal.show_cursor()
+}
 
-    Ok(())
+/// Takes the terminal over again after `suspend`.
+fn resume(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    enhanced: bool,
+) -> std::io::Result<()> {
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
+    if enhanced {
+        execute!(
+            terminal.backend_mut(),
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        )?;
+    }
+    terminal.clear()
 }
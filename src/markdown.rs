@@ -0,0 +1,497 @@
+//! Renders the Markdown used in journal entries as styled, wrapped lines.
+//!
+//! Covers what entries use day to day: headings, lists and task lists, quotes,
+//! rules, fenced code with light syntax highlighting, and inline emphasis,
+//! code, links and tags. Line breaks inside a paragraph are kept, as they were
+//! typed.
+
+use crate::theme::Theme;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Highlighted in code blocks, whatever the language.
+const KEYWORDS: &str = "\
+    as async await break case class const continue def default do elif else enum \
+    except export extends false False fn for from func function if impl import in \
+    interface let loop match mod mut new nil None null pub return self Self static \
+    struct switch trait true True try type use var where while with yield";
+
+/// Drawn in front of every line of a code block.
+const GUTTER: &str = "▎ ";
+
+/// Renders `text` into lines no wider than `width` columns.
+pub fn render(text: &str, width: u16, theme: &Theme) -> Vec<Line<'static>> {
+    let width = (width as usize).max(8);
+    let mut out = Vec::new();
+    // Closing marker and language of the open code fence
+    let mut fence: Option<(&str, String)> = None;
+    for raw in text.lines() {
+        let trimmed = raw.trim_start();
+        if let Some((marker, lang)) = &fence {
+            if trimmed.starts_with(*marker) {
+                fence = None;
+            } else {
+                let code = raw.replace('\t', "    ");
+                wrap_chars(
+                    highlight(&code, lang, theme),
+                    width,
+                    vec![Span::styled(GUTTER, theme.muted)],
+                    &mut out,
+                );
+            }
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = Some((marker, trimmed[3..].trim().to_lowercase()));
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            // Collapse runs of blank lines into one
+            if out.last().is_some_and(|l: &Line| l.width() > 0) {
+                out.push(Line::default());
+            }
+        } else if let Some((level, title)) = heading(trimmed) {
+            let style = match level {
+                1 => theme.heading.add_modifier(Modifier::UNDERLINED),
+                2 => theme.heading,
+                _ => theme.subheading,
+            };
+            wrap(
+                inline(title, style, theme),
+                width,
+                Vec::new(),
+                Vec::new(),
+                &mut out,
+            );
+        } else if is_rule(trimmed) {
+            out.push(Line::styled("─".repeat(width), theme.muted));
+        } else if trimmed.starts_with('>') {
+            let depth = trimmed
+                .chars()
+                .take_while(|&c| c == '>' || c == ' ')
+                .filter(|&c| c == '>')
+                .count();
+            let body = trimmed.trim_start_matches(['>', ' ']);
+            let bar = Span::styled("│ ".repeat(depth), theme.muted);
+            wrap(
+                inline(body, theme.quote, theme),
+                width,
+                vec![bar.clone()],
+                vec![bar],
+                &mut out,
+            );
+        } else if let Some((marker, body)) = list_item(trimmed) {
+            let level = raw.len() - trimmed.len();
+            let indent = "  ".repeat(level / 2);
+            let (marker, body, style) = match body.get(..4) {
+                Some("[ ] ") => ("☐ ".to_string(), &body[4..], Style::default()),
+                Some("[x] " | "[X] ") => ("☑ ".to_string(), &body[4..], theme.muted),
+                _ => (
+                    marker.unwrap_or_else(|| bullet(level / 2).to_string()),
+                    body,
+                    Style::default(),
+                ),
+            };
+            let first = vec![
+                Span::raw(indent.clone()),
+                Span::styled(marker.clone(), theme.bullet),
+            ];
+            let rest = vec![Span::raw(format!(
+                "{}{}",
+                indent,
+                " ".repeat(marker.width())
+            ))];
+            wrap(inline(body, style, theme), width, first, rest, &mut out);
+        } else {
+            wrap(
+                inline(trimmed, Style::default(), theme),
+                width,
+                Vec::new(),
+                Vec::new(),
+                &mut out,
+            );
+        }
+    }
+    while out.last().is_some_and(|l| l.width() == 0) {
+        out.pop();
+    }
+    out
+}
+
+/// At most `max_lines` non-blank lines of [`render`]; the last one ends in `…`
+/// when something was left out.
+pub fn condensed(text: &str, width: u16, max_lines: usize, theme: &Theme) -> Vec<Line<'static>> {
+    let mut lines: Vec<Line<'static>> = render(text, width.saturating_sub(1), theme)
+        .into_iter()
+        .filter(|l| l.width() > 0)
+        .collect();
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        if let Some(last) = lines.last_mut() {
+            last.spans.push(Span::styled("…", theme.muted));
+        }
+    }
+    lines
+}
+
+/// Level and text of an ATX heading (`## Title`).
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[level..];
+    ((1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' ')))
+        .then(|| (level, rest.trim()))
+}
+
+/// `---`, `***` or `___`, optionally spaced.
+fn is_rule(line: &str) -> bool {
+    let marks: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    marks.len() >= 3 && matches!(marks[0], '-' | '*' | '_') && marks.iter().all(|&c| c == marks[0])
+}
+
+/// The marker of an ordered item (`None` for bullets) and the item text.
+fn list_item(line: &str) -> Option<(Option<String>, &str)> {
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(body) = line.strip_prefix(bullet) {
+            return Some((None, body));
+        }
+    }
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    let rest = &line[digits..];
+    if (1..=9).contains(&digits) && (rest.starts_with(". ") || rest.starts_with(") ")) {
+        return Some((Some(format!("{} ", &line[..digits + 1])), &rest[2..]));
+    }
+    None
+}
+
+fn bullet(level: usize) -> &'static str {
+    match level {
+        0 => "• ",
+        1 => "◦ ",
+        _ => "▪ ",
+    }
+}
+
+/// Splits inline Markdown into styled spans on top of `base`.
+fn inline(text: &str, base: Style, theme: &Theme) -> Vec<Span<'static>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut spans = Vec::new();
+    let mut buf = String::new();
+    let (mut bold, mut italic, mut strike) = (false, false, false);
+    let style = |bold: bool, italic: bool, strike: bool| {
+        let mut style = base;
+        if bold {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        if italic {
+            style = style.add_modifier(Modifier::ITALIC);
+        }
+        if strike {
+            style = style.add_modifier(Modifier::CROSSED_OUT);
+        }
+        style
+    };
+    let link = |s: Style| s.patch(theme.link);
+    let at_word_start = |i: usize| i == 0 || chars[i - 1].is_whitespace();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let current = style(bold, italic, strike);
+        let flush = |buf: &mut String, spans: &mut Vec<Span<'static>>| {
+            if !buf.is_empty() {
+                spans.push(Span::styled(std::mem::take(buf), current));
+            }
+        };
+        match c {
+            '\\' if chars.get(i + 1).is_some_and(|n| n.is_ascii_punctuation()) => {
+                buf.push(chars[i + 1]);
+                i += 2;
+            }
+            '`' => match find(&chars, i + 1, &['`']) {
+                Some(end) => {
+                    flush(&mut buf, &mut spans);
+                    let code: String = chars[i + 1..end].iter().collect();
+                    spans.push(Span::styled(code, current.patch(theme.code)));
+                    i = end + 1;
+                }
+                None => {
+                    buf.push(c);
+                    i += 1;
+                }
+            },
+            '*' | '_' | '~' => {
+                let double = chars.get(i + 1) == Some(&c);
+                let n = if double { 2 } else { 1 };
+                let prev = i.checked_sub(1).map(|j| chars[j]);
+                let next = chars.get(i + n).copied();
+                let open = match (c, double) {
+                    ('~', true) => strike,
+                    ('~', false) => {
+                        buf.push(c);
+                        i += 1;
+                        continue;
+                    }
+                    (_, true) => bold,
+                    (_, false) => italic,
+                };
+                // `_` only counts at word edges, so snake_case stays as typed
+                let toggles = if open {
+                    prev.is_some_and(|p| !p.is_whitespace())
+                        && (c != '_' || !next.is_some_and(|n| n.is_alphanumeric()))
+                } else {
+                    next.is_some_and(|n| !n.is_whitespace())
+                        && (c != '_' || !prev.is_some_and(|p| p.is_alphanumeric()))
+                        && find(&chars, i + n, &[c, c][..n]).is_some()
+                };
+                if toggles {
+                    flush(&mut buf, &mut spans);
+                    match (c, double) {
+                        ('~', _) => strike = !strike,
+                        (_, true) => bold = !bold,
+                        _ => italic = !italic,
+                    }
+                } else {
+                    buf.extend(&chars[i..i + n]);
+                }
+                i += n;
+            }
+            '[' => match link_at(&chars, i) {
+                Some((label, url, end)) => {
+                    flush(&mut buf, &mut spans);
+                    spans.push(Span::styled(label.clone(), link(current)));
+                    if url != label {
+                        spans.push(Span::styled(format!(" ({})", url), theme.muted));
+                    }
+                    i = end;
+                }
+                None => {
+                    buf.push(c);
+                    i += 1;
+                }
+            },
+            '<' if starts_with(&chars, i + 1, "http") => match find(&chars, i + 1, &['>']) {
+                Some(end) => {
+                    flush(&mut buf, &mut spans);
+                    let url: String = chars[i + 1..end].iter().collect();
+                    spans.push(Span::styled(url, link(current)));
+                    i = end + 1;
+                }
+                None => {
+                    buf.push(c);
+                    i += 1;
+                }
+            },
+            'h' if at_word_start(i)
+                && (starts_with(&chars, i, "http://") || starts_with(&chars, i, "https://")) =>
+            {
+                flush(&mut buf, &mut spans);
+                let end = (i..chars.len())
+                    .find(|&j| chars[j].is_whitespace())
+                    .unwrap_or(chars.len());
+                let url: String = chars[i..end].iter().collect();
+                spans.push(Span::styled(url, link(current)));
+                i = end;
+            }
+            '#' if at_word_start(i) && chars.get(i + 1).is_some_and(|n| n.is_alphanumeric()) => {
+                flush(&mut buf, &mut spans);
+                let end = (i + 1..chars.len())
+                    .find(|&j| !(chars[j].is_alphanumeric() || chars[j] == '-' || chars[j] == '_'))
+                    .unwrap_or(chars.len());
+                let tag: String = chars[i..end].iter().collect();
+                spans.push(Span::styled(tag, current.patch(theme.tag)));
+                i = end;
+            }
+            _ => {
+                buf.push(c);
+                i += 1;
+            }
+        }
+    }
+    if !buf.is_empty() {
+        spans.push(Span::styled(buf, style(bold, italic, strike)));
+    }
+    spans
+}
+
+/// Index of the next occurrence of `pattern` at or after `from`.
+fn find(chars: &[char], from: usize, pattern: &[char]) -> Option<usize> {
+    (from..chars.len()).find(|&j| chars[j..].starts_with(pattern))
+}
+
+fn starts_with(chars: &[char], at: usize, prefix: &str) -> bool {
+    chars.get(at..).is_some_and(|rest| {
+        let mut rest = rest.iter();
+        prefix.chars().all(|p| rest.next() == Some(&p))
+    })
+}
+
+/// `[label](url)` at `start`: label, url and the index after the link.
+fn link_at(chars: &[char], start: usize) -> Option<(String, String, usize)> {
+    let close = find(chars, start + 1, &[']', '('])?;
+    let end = find(chars, close + 2, &[')'])?;
+    let label: String = chars[start + 1..close].iter().collect();
+    let url: String = chars[close + 2..end].iter().collect();
+    (!url.contains(char::is_whitespace)).then_some((label, url, end + 1))
+}
+
+/// Splits one line of code into keywords, strings, numbers and comments.
+fn highlight(line: &str, lang: &str, theme: &Theme) -> Vec<Span<'static>> {
+    let comment = match lang {
+        "python" | "py" | "sh" | "bash" | "zsh" | "shell" | "toml" | "yaml" | "yml" | "ruby"
+        | "rb" => "#",
+        "sql" | "lua" | "haskell" | "hs" => "--",
+        _ => "//",
+    };
+    // In Rust a single quote usually starts a lifetime
+    let quotes: &[char] = if matches!(lang, "rust" | "rs") {
+        &['"']
+    } else {
+        &['"', '\'']
+    };
+    let plain = theme.code_text;
+    let chars: Vec<char> = line.chars().collect();
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let (end, style) = if starts_with(&chars, i, comment) {
+            (chars.len(), theme.comment)
+        } else if quotes.contains(&c) {
+            let mut j = i + 1;
+            while j < chars.len() && chars[j] != c {
+                j += if chars[j] == '\\' { 2 } else { 1 };
+            }
+            ((j + 1).min(chars.len()), theme.string)
+        } else if c.is_ascii_digit() {
+            let end = (i..chars.len())
+                .find(|&j| !(chars[j].is_alphanumeric() || chars[j] == '.' || chars[j] == '_'))
+                .unwrap_or(chars.len());
+            (end, theme.code)
+        } else if c.is_alphanumeric() || c == '_' {
+            let end = (i..chars.len())
+                .find(|&j| !(chars[j].is_alphanumeric() || chars[j] == '_'))
+                .unwrap_or(chars.len());
+            let word: String = chars[i..end].iter().collect();
+            let style = if KEYWORDS.split_whitespace().any(|k| k == word) {
+                theme.keyword
+            } else {
+                plain
+            };
+            (end, style)
+        } else {
+            (i + 1, plain)
+        };
+        spans.push(Span::styled(
+            chars[i..end].iter().collect::<String>(),
+            style,
+        ));
+        i = end;
+    }
+    spans
+}
+
+/// Word-wraps `spans` to `width`, starting the first line with `first` and
+/// the others with `rest`.
+fn wrap(
+    spans: Vec<Span<'static>>,
+    width: usize,
+    first: Vec<Span<'static>>,
+    rest: Vec<Span<'static>>,
+    out: &mut Vec<Line<'static>>,
+) {
+    let prefix_width = |p: &[Span]| p.iter().map(Span::width).sum::<usize>();
+    let start_rest = prefix_width(&rest);
+    let mut start = prefix_width(&first);
+    let mut used = start;
+    let mut line = first;
+    let mut space: Option<Span<'static>> = None;
+
+    for span in spans {
+        for (is_space, piece) in runs(&span.content) {
+            if is_space {
+                if used > start {
+                    space = Some(Span::styled(piece, span.style));
+                }
+                continue;
+            }
+            let pending = space.as_ref().map_or(0, Span::width);
+            if used > start && used + pending + piece.width() > width {
+                out.push(Line::from(std::mem::replace(&mut line, rest.clone())));
+                used = start_rest;
+                start = start_rest;
+                space = None;
+            }
+            if let Some(space) = space.take() {
+                used += space.width();
+                line.push(space);
+            }
+            // Words longer than a line are broken wherever they run out of room
+            let mut piece = piece;
+            while used + piece.width() > width {
+                let (head, tail) = split_at_width(&piece, width.saturating_sub(used).max(1));
+                line.push(Span::styled(head, span.style));
+                out.push(Line::from(std::mem::replace(&mut line, rest.clone())));
+                used = start_rest;
+                start = start_rest;
+                piece = tail;
+            }
+            used += piece.width();
+            line.push(Span::styled(piece, span.style));
+        }
+    }
+    out.push(Line::from(line));
+}
+
+/// Wraps `spans` at exactly `width` columns, keeping every space; for code.
+fn wrap_chars(
+    spans: Vec<Span<'static>>,
+    width: usize,
+    gutter: Vec<Span<'static>>,
+    out: &mut Vec<Line<'static>>,
+) {
+    let start = gutter.iter().map(Span::width).sum::<usize>();
+    let mut line = gutter.clone();
+    let mut used = start;
+    for span in spans {
+        let mut piece = span.content.into_owned();
+        while used + piece.width() > width {
+            if used < width {
+                let (head, tail) = split_at_width(&piece, width - used);
+                line.push(Span::styled(head, span.style));
+                piece = tail;
+            }
+            out.push(Line::from(std::mem::replace(&mut line, gutter.clone())));
+            used = start;
+        }
+        used += piece.width();
+        line.push(Span::styled(piece, span.style));
+    }
+    out.push(Line::from(line));
+}
+
+/// Alternating runs of whitespace and other characters.
+fn runs(text: &str) -> Vec<(bool, String)> {
+    let mut runs: Vec<(bool, String)> = Vec::new();
+    for c in text.chars() {
+        match runs.last_mut() {
+            Some((space, run)) if *space == c.is_whitespace() => run.push(c),
+            _ => runs.push((c.is_whitespace(), c.to_string())),
+        }
+    }
+    runs
+}
+
+/// Splits `text` after as many characters as fit in `width` columns (at least one).
+fn split_at_width(text: &str, width: usize) -> (String, String) {
+    let mut used = 0;
+    for (i, c) in text.char_indices() {
+        let w = c.width().unwrap_or(0);
+        if used + w > width && i > 0 {
+            return (text[..i].to_string(), text[i..].to_string());
+        }
+        used += w;
+    }
+    (text.to_string(), String::new())
+}
@@ -0,0 +1,75 @@
+//! Versioned on-disk format of journal entries, used by the file and
+//! JSON-lines stores. (The SQLite store versions its tables separately.)
+//!
+//! Every record carries a `version`. Records without one predate versioning
+//! and are version 1. Older records are upgraded in memory when they are read,
+//! and `captains_log upgrade` rewrites them on disk.
+
+use crate::journal::JournalEntry;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::io;
+
+/// Version written by this build.
+pub const CURRENT_VERSION: u64 = 2;
+
+/// `MIGRATIONS[n]` turns a version `n + 1` record into version `n + 2`.
+/// Only ever append to this list.
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[v1_to_v2];
+
+/// Version 2 only adds the `version` field itself.
+fn v1_to_v2(_record: &mut Map<String, Value>) {}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// The version of a raw record.
+pub fn version_of(record: &Map<String, Value>) -> io::Result<u64> {
+    let Some(value) = record.get("version") else {
+        return Ok(1);
+    };
+    match value.as_u64() {
+        Some(version) if version >= 1 => Ok(version),
+        _ => Err(invalid(format!("bad schema version {}", value))),
+    }
+}
+
+/// Brings a raw record up to [`CURRENT_VERSION`].
+pub fn upgrade(mut record: Map<String, Value>) -> io::Result<Map<String, Value>> {
+    let version = version_of(&record)?;
+    if version > CURRENT_VERSION {
+        return Err(invalid(format!(
+            "schema version {} is newer than this build supports ({}); update captains_log",
+            version, CURRENT_VERSION
+        )));
+    }
+    for migration in &MIGRATIONS[(version - 1) as usize..] {
+        migration(&mut record);
+    }
+    record.insert("version".to_string(), Value::from(CURRENT_VERSION));
+    Ok(record)
+}
+
+/// Parses a record of any supported version. Also returns the version it was stored in.
+pub fn decode(text: &str) -> io::Result<(JournalEntry, u64)> {
+    let record: Map<String, Value> = serde_json::from_str(text)?;
+    let version = version_of(&record)?;
+    let entry = serde_json::from_value(Value::Object(upgrade(record)?))?;
+    Ok((entry, version))
+}
+
+#[derive(Serialize)]
+struct Stored<'a> {
+    version: u64,
+    #[serde(flatten)]
+    entry: &'a JournalEntry,
+}
+
+/// The record for `entry` in the current version, on one line.
+pub fn encode(entry: &JournalEntry) -> io::Result<String> {
+    Ok(serde_json::to_string(&Stored {
+        version: CURRENT_VERSION,
+        entry,
+    })?)
+}
@@ -0,0 +1,345 @@
+use crate::activity::{ActivitySample, ActivityStore};
+use crate::crypto;
+use crate::journal::{self, JournalEntry, JournalStore, LoadProblem};
+use crate::stats;
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{Connection, params};
+use std::io;
+use std::path::PathBuf;
+
+/// Schema migrations, applied in order. `PRAGMA user_version` records how
+/// many have run, so only append to this list.
+const MIGRATIONS: &[&str] = &[
+    // 1: entries, tags, per-minute activity and full-text search
+    "CREATE TABLE entries (
+        id INTEGER PRIMARY KEY,
+        timestamp TEXT NOT NULL,
+        content TEXT NOT NULL,
+        -- The file an imported entry came from, so a re-run import skips it
+        source TEXT UNIQUE
+    );
+    CREATE INDEX entries_timestamp ON entries (timestamp);
+    CREATE TABLE tags (
+        entry_id INTEGER NOT NULL REFERENCES entries (id) ON DELETE CASCADE,
+        tag TEXT NOT NULL,
+        PRIMARY KEY (entry_id, tag)
+    );
+    CREATE INDEX tags_tag ON tags (tag);
+    CREATE TABLE activity (
+        minute TEXT PRIMARY KEY,
+        keystrokes INTEGER NOT NULL
+    );
+    CREATE VIRTUAL TABLE entries_fts USING fts5 (
+        content, content = 'entries', content_rowid = 'id'
+    );
+    -- Encrypted entries (`enc:` in front, see crypto.rs) have no words to index
+    CREATE TRIGGER entries_ai AFTER INSERT ON entries
+    WHEN substr(new.content, 1, 4) <> 'enc:' BEGIN
+        INSERT INTO entries_fts (rowid, content) VALUES (new.id, new.content);
+    END;
+    CREATE TRIGGER entries_ad AFTER DELETE ON entries
+    WHEN substr(old.content, 1, 4) <> 'enc:' BEGIN
+        INSERT INTO entries_fts (entries_fts, rowid, content) VALUES ('delete', old.id, old.content);
+    END;
+    CREATE TRIGGER entries_au AFTER UPDATE OF content ON entries BEGIN
+        INSERT INTO entries_fts (entries_fts, rowid, content)
+            SELECT 'delete', old.id, old.content WHERE substr(old.content, 1, 4) <> 'enc:';
+        INSERT INTO entries_fts (rowid, content)
+            SELECT new.id, new.content WHERE substr(new.content, 1, 4) <> 'enc:';
+    END;",
+];
+
+fn to_io(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+/// Timestamps are stored as RFC 3339 with full precision, the same text
+/// serde writes to the JSON files, so round trips are lossless.
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+fn parse_time(text: &str) -> rusqlite::Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, e.into())
+        })
+}
+
+/// Embedded SQLite database holding entries, their tags and per-minute activity.
+pub struct SqliteJournal {
+    pub path: PathBuf,
+}
+
+impl Default for SqliteJournal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SqliteJournal {
+    pub fn new() -> Self {
+        SqliteJournal {
+            path: PathBuf::from("captains_log.db"),
+        }
+    }
+
+    /// Opens the database, creating it and applying pending migrations as needed.
+    pub fn connect(&self) -> io::Result<Connection> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut conn = Connection::open(&self.path).map_err(to_io)?;
+        conn.pragma_update(None, "foreign_keys", true)
+            .map_err(to_io)?;
+        migrate_schema(&mut conn).map_err(to_io)?;
+        Ok(conn)
+    }
+
+    /// Held by every writer, and by `crypto` while it swaps the database out.
+    fn lock(&self) -> io::Result<journal::DirLock> {
+        journal::lock_file(&journal::with_suffix(&self.path, ".lock"))
+    }
+
+    pub fn schema_version(&self) -> io::Result<usize> {
+        let conn = self.connect()?;
+        schema_version(&conn).map_err(to_io)
+    }
+
+    /// Inserts an entry and its tags.
+    pub fn insert(&self, entry: &JournalEntry) -> io::Result<()> {
+        let _lock = self.lock()?;
+        let mut conn = self.connect()?;
+        let tx = conn.transaction().map_err(to_io)?;
+        insert_entry(&tx, entry, None).map_err(to_io)?;
+        tx.commit().map_err(to_io)
+    }
+
+    /// Imports entries in one transaction, each named by the file it came
+    /// from. Entries whose file was imported before are skipped, so the
+    /// import can be re-run; identical entries from different files are kept.
+    pub fn import(&self, entries: &[(String, JournalEntry)]) -> io::Result<journal::Migration> {
+        let _lock = self.lock()?;
+        let mut conn = self.connect()?;
+        let tx = conn.transaction().map_err(to_io)?;
+        let mut result = journal::Migration::default();
+        for (source, entry) in entries {
+            if insert_entry(&tx, entry, Some(source)).map_err(to_io)? {
+                result.migrated += 1;
+            } else {
+                result.skipped += 1;
+            }
+        }
+        tx.commit().map_err(to_io)?;
+        Ok(result)
+    }
+
+    /// Every entry with the file it was imported from, if any, oldest first.
+    pub fn load_sourced(&self) -> io::Result<Vec<(Option<String>, JournalEntry)>> {
+        let conn = self.connect()?;
+        let mut stmt = conn
+            .prepare("SELECT timestamp, content, source FROM entries ORDER BY timestamp, id")
+            .map_err(to_io)?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(2)?, row_to_entry(row)?)))
+            .map_err(to_io)?;
+        rows.collect::<rusqlite::Result<_>>().map_err(to_io)
+    }
+
+    /// Inserts entries with the sources `load_sourced` gave, in one
+    /// transaction, so a copy of the database skips the same files on import.
+    pub fn insert_sourced(&self, entries: &[(Option<String>, JournalEntry)]) -> io::Result<()> {
+        let _lock = self.lock()?;
+        let mut conn = self.connect()?;
+        let tx = conn.transaction().map_err(to_io)?;
+        for (source, entry) in entries {
+            insert_entry(&tx, entry, source.as_deref()).map_err(to_io)?;
+        }
+        tx.commit().map_err(to_io)
+    }
+
+    /// Entries carrying `tag` (without the `#`), oldest first.
+    pub fn by_tag(&self, tag: &str) -> io::Result<Vec<JournalEntry>> {
+        let conn = self.connect()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT e.timestamp, e.content FROM entries e
+                 JOIN tags t ON t.entry_id = e.id
+                 WHERE t.tag = ?1 ORDER BY e.timestamp",
+            )
+            .map_err(to_io)?;
+        let rows = stmt
+            .query_map([tag.to_lowercase()], row_to_entry)
+            .map_err(to_io)?;
+        rows.collect::<rusqlite::Result<_>>().map_err(to_io)
+    }
+
+    /// Stores per-minute samples, replacing any already recorded for the same
+    /// minute. Samples of one minute, which a restart leaves behind, are added
+    /// up first. Returns how many minutes were stored.
+    pub fn import_activity(&self, samples: &[ActivitySample]) -> io::Result<usize> {
+        let mut sorted = samples.to_vec();
+        sorted.sort_by_key(|s| s.minute);
+        let merged = stats::merge_minutes(&sorted);
+        let _lock = self.lock()?;
+        let mut conn = self.connect()?;
+        let tx = conn.transaction().map_err(to_io)?;
+        for sample in &merged {
+            tx.execute(
+                "INSERT OR REPLACE INTO activity (minute, keystrokes) VALUES (?1, ?2)",
+                params![format_time(&sample.minute), sample.keystrokes],
+            )
+            .map_err(to_io)?;
+        }
+        tx.commit().map_err(to_io)?;
+        Ok(merged.len())
+    }
+
+    pub fn load_activity(&self) -> io::Result<Vec<ActivitySample>> {
+        let conn = self.connect()?;
+        let mut stmt = conn
+            .prepare("SELECT minute, keystrokes FROM activity ORDER BY minute")
+            .map_err(to_io)?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(ActivitySample {
+                    minute: parse_time(&row.get::<_, String>(0)?)?,
+                    keystrokes: row.get(1)?,
+                })
+            })
+            .map_err(to_io)?;
+        rows.collect::<rusqlite::Result<_>>().map_err(to_io)
+    }
+}
+
+/// Activity lives in the database too when it holds the journal.
+impl ActivityStore for SqliteJournal {
+    fn append(&self, sample: &ActivitySample) -> io::Result<()> {
+        let _lock = self.lock()?;
+        let conn = self.connect()?;
+        conn.execute(
+            "INSERT INTO activity (minute, keystrokes) VALUES (?1, ?2)
+             ON CONFLICT (minute) DO UPDATE SET keystrokes = keystrokes + excluded.keystrokes",
+            params![format_time(&sample.minute), sample.keystrokes],
+        )
+        .map_err(to_io)?;
+        Ok(())
+    }
+
+    fn load_checked(&self) -> io::Result<(Vec<ActivitySample>, Vec<LoadProblem>)> {
+        Ok((self.load_activity()?, Vec::new()))
+    }
+}
+
+fn schema_version(conn: &Connection) -> rusqlite::Result<usize> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+fn migrate_schema(conn: &mut Connection) -> rusqlite::Result<()> {
+    let version = schema_version(conn)?;
+    if version >= MIGRATIONS.len() {
+        return Ok(());
+    }
+    let tx = conn.transaction()?;
+    for migration in &MIGRATIONS[version..] {
+        tx.execute_batch(migration)?;
+    }
+    tx.pragma_update(None, "user_version", MIGRATIONS.len())?;
+    tx.commit()
+}
+
+/// Inserts an entry and its tags. With a `source`, returns false instead if
+/// that source was imported already.
+fn insert_entry(
+    conn: &Connection,
+    entry: &JournalEntry,
+    source: Option<&str>,
+) -> rusqlite::Result<bool> {
+    let timestamp = format_time(&entry.timestamp);
+    if let Some(source) = source {
+        let known: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM entries WHERE source = ?1)",
+            [source],
+            |row| row.get(0),
+        )?;
+        if known {
+            return Ok(false);
+        }
+        // Claim a copy imported before sources were recorded
+        let claimed = conn.execute(
+            "UPDATE entries SET source = ?1 WHERE id = (
+                SELECT id FROM entries
+                WHERE source IS NULL AND timestamp = ?2 AND content = ?3 LIMIT 1
+            )",
+            params![source, timestamp, entry.content],
+        )?;
+        if claimed > 0 {
+            return Ok(false);
+        }
+    }
+    conn.execute(
+        "INSERT INTO entries (timestamp, content, source) VALUES (?1, ?2, ?3)",
+        params![timestamp, entry.content, source],
+    )?;
+    if crypto::is_encrypted(&entry.content) {
+        return Ok(true);
+    }
+    let id = conn.last_insert_rowid();
+    for tag in journal::tags(&entry.content) {
+        conn.execute(
+            "INSERT OR IGNORE INTO tags (entry_id, tag) VALUES (?1, ?2)",
+            params![id, tag],
+        )?;
+    }
+    Ok(true)
+}
+
+fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<JournalEntry> {
+    Ok(JournalEntry {
+        timestamp: parse_time(&row.get::<_, String>(0)?)?,
+        content: row.get(1)?,
+    })
+}
+
+impl JournalStore for SqliteJournal {
+    fn save(&self, entry: &JournalEntry) -> io::Result<()> {
+        self.insert(entry)
+    }
+
+    fn load_all(&self) -> io::Result<Vec<JournalEntry>> {
+        let conn = self.connect()?;
+        let mut stmt = conn
+            .prepare("SELECT timestamp, content FROM entries ORDER BY timestamp, id")
+            .map_err(to_io)?;
+        let rows = stmt.query_map([], row_to_entry).map_err(to_io)?;
+        rows.collect::<rusqlite::Result<_>>().map_err(to_io)
+    }
+
+    /// Streams rows straight from the database, oldest first.
+    fn for_each_entry(&self, f: &mut dyn FnMut(JournalEntry) -> io::Result<()>) -> io::Result<()> {
+        let conn = self.connect()?;
+        let mut stmt = conn
+            .prepare("SELECT timestamp, content FROM entries ORDER BY timestamp, id")
+            .map_err(to_io)?;
+        let rows = stmt.query_map([], row_to_entry).map_err(to_io)?;
+        for row in rows {
+            f(row.map_err(to_io)?)?;
+        }
+        Ok(())
+    }
+
+    /// Full-text search using FTS5 query syntax, best matches first.
+    fn search(&self, query: &str) -> io::Result<Vec<JournalEntry>> {
+        let conn = self.connect()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT e.timestamp, e.content FROM entries_fts f
+                 JOIN entries e ON e.id = f.rowid
+                 WHERE entries_fts MATCH ?1 ORDER BY f.rank",
+            )
+            .map_err(to_io)?;
+        let rows = stmt.query_map([query], row_to_entry).map_err(to_io)?;
+        rows.collect::<rusqlite::Result<_>>().map_err(to_io)
+    }
+}
@@ -1,19 +1,193 @@
-use crate::journal::{Journal, JournalEntry};
-use chrono::{DateTime, Local, Utc};
+use crate::activity::{ActivitySample, ActivityStore, minute_of};
+use crate::commands::{Action, Palette, PaletteMode};
+use crate::config::Config;
+use crate::export;
+use crate::flow::{FlowDetector, FlowLog, FlowPeriod};
+use crate::focus::FocusModel;
+use crate::goals::{DayRecord, GoalLog, Pomodoro, Progress};
+use crate::journal::{JournalEntry, JournalStore};
+use crate::keymap::{KeyChord, Keymap, Section};
+use crate::stats::{self, Period, Records, Report};
+use crate::templates::{self, Template};
+use crate::theme::{self, Theme};
+use crate::timeline::{self, History, Zoom};
+use crate::ui::{self, Target};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeDelta, Utc};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use std::collections::VecDeque;
 use tui_textarea::TextArea;
 
+/// Length of one UI tick in milliseconds.
+pub const TICK_MILLIS: u64 = 250;
+/// Ticks kept in `activity_stream` (15 minutes).
+pub const TICK_HISTORY: usize = 3600;
+/// How many days of per-minute history the timeline can pan through.
+pub const HISTORY_DAYS: i64 = 7;
+/// Seconds between automatic retries of journal entries that failed to save.
+pub const RETRY_SECS: i64 = 30;
+/// Most text rows the input box can be resized to.
+pub const MAX_INPUT_ROWS: u16 = 20;
+
+/// Every readable entry, oldest first, and a warning if some could not be read.
+fn load_logs(journal: &dyn JournalStore) -> (Vec<JournalEntry>, Option<String>) {
+    let (mut logs, warning) = match journal.load_checked() {
+        Ok(loaded) if loaded.problems.is_empty() => (loaded.entries, None),
+        Ok(loaded) => {
+            let warning = format!(
+                "{} ENTRIES UNREADABLE - RUN `captains_log doctor`",
+                loaded.problems.len()
+            );
+            (loaded.entries, Some(warning))
+        }
+        Err(e) => (Vec::new(), Some(format!("NOT LOADED: {}", e))),
+    };
+    logs.sort_by_key(|e| e.timestamp);
+    (logs, warning)
+}
+
+/// An empty journal input.
+fn new_input(theme: &Theme) -> TextArea<'static> {
+    let mut textarea = TextArea::default();
+    textarea.set_placeholder_text("Add log entry...");
+    textarea.set_placeholder_style(theme.muted);
+    textarea
+}
+
+/// Top-level screens of the TUI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screen {
+    Journal,
+    Stats,
+    /// The entry being written, full screen.
+    Editor,
+    /// The selected entry, rendered as Markdown.
+    Detail,
+}
+
+/// How the screen is divided up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutPreset {
+    /// Everything in one column, with one-line header and footer.
+    Compact,
+    Standard,
+    /// Journal on the left, a full-height activity chart on the right.
+    Wide,
+    /// Only the activity chart and a status line.
+    Minimal,
+}
+
+impl LayoutPreset {
+    pub const ALL: [LayoutPreset; 4] = [
+        LayoutPreset::Compact,
+        LayoutPreset::Standard,
+        LayoutPreset::Wide,
+        LayoutPreset::Minimal,
+    ];
+
+    /// The preset that fits a terminal of `width` by `height` cells.
+    pub fn for_size(width: u16, height: u16) -> Self {
+        if width < 40 || height < 12 {
+            LayoutPreset::Minimal
+        } else if width < 80 || height < 24 {
+            LayoutPreset::Compact
+        } else if width >= 160 {
+            LayoutPreset::Wide
+        } else {
+            LayoutPreset::Standard
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LayoutPreset::Compact => "COMPACT",
+            LayoutPreset::Standard => "STANDARD",
+            LayoutPreset::Wide => "WIDE",
+            LayoutPreset::Minimal => "MINIMAL",
+        }
+    }
+}
+
+/// Whether plain keys type into the journal input. Only modal keymaps have
+/// a normal mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputMode {
+    Normal,
+    Insert,
+}
+
 pub struct App<'a> {
+    pub screen: Screen,
     pub textarea: TextArea<'a>,
+    /// Enter adds a line instead of saving the entry.
+    pub multiline: bool,
+    /// Text rows of the input box; it grows with the entry up to half the panel.
+    pub input_rows: u16,
     pub logs: Vec<JournalEntry>,
+    /// Entry highlighted in the journal panel, as an index into `logs`.
+    pub selected: Option<usize>,
+    pub detail_scroll: u16,
     pub activity_stream: VecDeque<u32>, // Keystrokes per second/tick
     pub focus_level: f64,
+    pub focus_model: Box<dyn FocusModel>,
+    pub config: Config,
     pub last_activity: DateTime<Local>,
     pub alert_active: bool,
     pub should_quit: bool,
-    pub journal: Journal,
+    pub journal: Box<dyn JournalStore>,
     pub lpm: u32,
     pub wpm: u32,
+    pub activity_log: Box<dyn ActivityStore>,
+    pub current_minute: DateTime<Utc>,
+    pub minute_keystrokes: u32,
+    pub stats_period: Period,
+    pub stats: Option<Report>,
+    /// End of the newest tick in `activity_stream`.
+    pub last_tick_at: DateTime<Utc>,
+    /// Persisted per-minute activity for the timeline, sorted by minute.
+    pub history: Vec<ActivitySample>,
+    pub timeline_zoom: Zoom,
+    /// How far before the newest tick the right edge of the timeline sits.
+    pub timeline_offset: TimeDelta,
+    pub flow_detector: FlowDetector,
+    pub flow_log: FlowLog,
+    /// Finished flow periods within the timeline history, sorted by start.
+    pub flows: Vec<FlowPeriod>,
+    /// Local day the goal progress belongs to.
+    pub today: NaiveDate,
+    pub progress: Progress,
+    pub goal_log: GoalLog,
+    pub goal_history: Vec<DayRecord>,
+    pub pomodoro: Option<Pomodoro>,
+    /// Shown in the journal panel when entries could not be loaded.
+    pub journal_warning: Option<String>,
+    /// Most recent storage failure, shown in the footer until a save succeeds.
+    pub error: Option<String>,
+    /// What saving the journal last put in `error`, so a later save clears
+    /// only its own message.
+    save_error: Option<String>,
+    /// Entries that failed to save, oldest first, waiting to be retried.
+    pub unsaved: Vec<JournalEntry>,
+    pub last_retry: DateTime<Utc>,
+    pub templates: Vec<Template>,
+    /// Highlighted row of the open template picker.
+    pub picker: Option<usize>,
+    /// Scheduled templates due up to this time have been opened.
+    pub last_prompt_check: NaiveDateTime,
+    pub session_start: DateTime<Utc>,
+    pub session_keystrokes: u32,
+    pub palette: Option<Palette>,
+    /// The key bindings overlay is open.
+    pub help: bool,
+    /// Words the journal panel is filtered by.
+    pub search: Option<String>,
+    pub alerts_enabled: bool,
+    /// Outcome of the last command, shown in the footer until the next key.
+    pub notice: Option<String>,
+    pub keymap: Keymap,
+    pub input_mode: InputMode,
+    pub theme: Theme,
+    /// Layout picked with `cycle_layout`; `None` follows the terminal size.
+    pub layout: Option<LayoutPreset>,
 }
 
 impl<'a> Default for App<'a> {
@@ -24,42 +198,164 @@ impl<'a> Default for App<'a> {
 
 impl<'a> App<'a> {
     pub fn new() -> Self {
-        let mut textarea = TextArea::default();
-        textarea.set_placeholder_text("Add log entry...");
-        textarea.set_block(
-            ratatui::widgets::Block::default()
-                .borders(ratatui::widgets::Borders::ALL)
-                .title("Input"),
-        );
+        Self::with_config(Config::load(&Config::default_path()).unwrap_or_default())
+    }
 
-        let journal = Journal::new();
-        let mut logs = journal.load_all().unwrap_or_default();
-        logs.sort_by_key(|e| e.timestamp);
+    pub fn with_config(config: Config) -> Self {
+        // Config::load has checked the theme too
+        let theme =
+            theme::build(&config.theme, &config.themes, theme::no_color()).unwrap_or_default();
+        let textarea = new_input(&theme);
+
+        let journal = config.journal.open();
+        let (logs, journal_warning) = load_logs(journal.as_ref());
+
+        let activity_log = config.journal.open_activity();
+        let since = Utc::now() - TimeDelta::days(HISTORY_DAYS);
+        let (mut history, mut unreadable) = activity_log.load_checked().unwrap_or_default();
+        history.retain(|s| s.minute >= since);
+        history.sort_by_key(|s| s.minute);
+        // A restart within a minute logs it twice
+        let history = stats::merge_minutes(&history);
+
+        let focus_model = config.focus.build();
+
+        let flow_log = FlowLog::new();
+        let (mut flows, flow_problems) = flow_log.load_checked().unwrap_or_default();
+        flows.retain(|f| f.end >= since);
+        unreadable.extend(flow_problems);
+        let flow_detector = FlowDetector::new(config.flow.clone());
 
-        Self {
+        // Pick up today's progress from earlier runs
+        let today = Local::now().date_naive();
+        let is_today = |t: DateTime<Utc>| t.with_timezone(&Local).date_naive() == today;
+        let goal_log = GoalLog::new();
+        let goal_history = goal_log.load().unwrap_or_default();
+        let todays = history.iter().filter(|s| is_today(s.minute));
+        let progress = Progress {
+            active_minutes: todays.clone().count() as u32,
+            keystrokes: todays.map(|s| s.keystrokes).sum(),
+            journal_entries: logs.iter().filter(|e| is_today(e.timestamp)).count() as u32,
+            pomodoros: goal_history
+                .iter()
+                .find(|r| r.date == today)
+                .map_or(0, |r| r.progress.pomodoros),
+        };
+
+        // Config::load has checked the bindings; report conflicts until the first key
+        let keymap = config.keys.keymap().unwrap_or_default();
+        let notice = match keymap.conflicts().as_slice() {
+            [] => None,
+            [conflict] => Some(format!("KEYS: {}", conflict)),
+            [conflict, rest @ ..] => Some(format!(
+                "KEYS: {} (+{} MORE, SEE `captains_log keys`)",
+                conflict,
+                rest.len()
+            )),
+        };
+        let notice = notice.or_else(|| {
+            (!unreadable.is_empty()).then(|| {
+                format!(
+                    "{} ACTIVITY RECORDS UNREADABLE - RUN `captains_log doctor`",
+                    unreadable.len()
+                )
+            })
+        });
+        let input_mode = if keymap.modal {
+            InputMode::Normal
+        } else {
+            InputMode::Insert
+        };
+
+        let templates = if config.templates.is_empty() {
+            templates::defaults()
+        } else {
+            config.templates.clone()
+        };
+
+        let mut app = Self {
+            screen: Screen::Journal,
             textarea,
+            multiline: false,
+            input_rows: 1,
             logs,
-            activity_stream: VecDeque::with_capacity(300), // Increased capacity for 60s+ history
-            focus_level: 100.0,
+            selected: None,
+            detail_scroll: 0,
+            activity_stream: VecDeque::with_capacity(TICK_HISTORY),
+            focus_level: focus_model.level(),
+            focus_model,
+            config,
             last_activity: Local::now(),
             alert_active: false,
             should_quit: false,
             journal,
             lpm: 0,
             wpm: 0,
-        }
+            activity_log,
+            current_minute: minute_of(Utc::now()),
+            minute_keystrokes: 0,
+            stats_period: Period::Week,
+            stats: None,
+            last_tick_at: Utc::now(),
+            history,
+            timeline_zoom: Zoom::OneMinute,
+            timeline_offset: TimeDelta::zero(),
+            flow_detector,
+            flow_log,
+            flows,
+            today,
+            progress,
+            goal_log,
+            goal_history,
+            pomodoro: None,
+            journal_warning,
+            error: None,
+            save_error: None,
+            unsaved: Vec::new(),
+            last_retry: Utc::now(),
+            templates,
+            picker: None,
+            last_prompt_check: Local::now().naive_local(),
+            session_start: Utc::now(),
+            session_keystrokes: 0,
+            palette: None,
+            help: false,
+            search: None,
+            alerts_enabled: true,
+            notice,
+            keymap,
+            input_mode,
+            theme,
+            layout: None,
+        };
+        app.update_input_block();
+        app
     }
 
     pub fn on_tick(&mut self) {
-        // Update logic here (e.g. decay focus level if no activity)
         let now = Local::now();
-        let diff = now.signed_duration_since(self.last_activity).num_seconds();
+        self.roll_minute(now.with_timezone(&Utc));
+        self.roll_day(now.date_naive());
 
-        if diff > 10 {
-            self.focus_level = (self.focus_level - 0.5).max(0.0);
+        if self
+            .pomodoro
+            .is_some_and(|p| p.is_done(now.with_timezone(&Utc)))
+        {
+            self.pomodoro = None;
+            self.progress.pomodoros += 1;
+            self.save_goals();
+        }
+        if !self.unsaved.is_empty()
+            && Utc::now() - self.last_retry >= TimeDelta::seconds(RETRY_SECS)
+        {
+            self.retry_unsaved();
+        } else if self.unsaved.is_empty() {
+            self.show_journal_status();
         }
+        self.check_prompts(now.naive_local());
+        let diff = now.signed_duration_since(self.last_activity).num_seconds();
 
-        self.alert_active = diff > 30;
+        self.alert_active = self.alerts_enabled && diff > 30;
 
         // Calculate LPM and WPM
         // activity_stream holds counts per tick (approx 250ms)
@@ -78,15 +374,829 @@ impl<'a> App<'a> {
             timestamp: Utc::now(),
             content,
         };
-        if let Err(e) = self.journal.save(&entry) {
-            eprintln!("Failed to save journal: {}", e);
+        self.logs.push(entry.clone());
+        self.progress.journal_entries += 1;
+        // Keep entries in order: nothing new is written while older ones are pending
+        self.unsaved.push(entry);
+        self.retry_unsaved();
+    }
+
+    /// Saves what is in the input box as a new entry and clears it.
+    pub fn submit_entry(&mut self) {
+        let content = self.textarea.lines().join("\n");
+        if content.trim().is_empty() {
+            return;
+        }
+        self.add_log(content);
+        self.clear_input();
+    }
+
+    /// Saves text written in an external editor; `None` means it was left empty.
+    pub fn save_edited(&mut self, edited: std::io::Result<Option<String>>) {
+        match edited {
+            Ok(Some(content)) => {
+                self.add_log(content);
+                self.clear_input();
+            }
+            Ok(None) => {}
+            Err(e) => self.error = Some(format!("EDITOR FAILED: {}", e)),
+        }
+    }
+
+    fn clear_input(&mut self) {
+        self.textarea = new_input(&self.theme);
+        if self.screen == Screen::Editor {
+            self.screen = Screen::Journal;
+        }
+        self.update_input_block();
+    }
+
+    /// Switches Enter between saving the entry and starting a new line.
+    pub fn toggle_multiline(&mut self) {
+        self.multiline = !self.multiline;
+        self.update_input_block();
+    }
+
+    /// Opens the entry in a full-screen editor, or returns to the journal.
+    pub fn toggle_editor(&mut self) {
+        self.screen = match self.screen {
+            Screen::Editor => Screen::Journal,
+            _ => Screen::Editor,
+        };
+        self.update_input_block();
+    }
+
+    /// Grows or shrinks the input box by `rows`.
+    pub fn resize_input(&mut self, rows: i16) {
+        self.input_rows = self
+            .input_rows
+            .saturating_add_signed(rows)
+            .clamp(1, MAX_INPUT_ROWS);
+    }
+
+    /// Whether Enter starts a new line rather than saving.
+    pub fn enter_adds_line(&self) -> bool {
+        self.multiline || self.screen == Screen::Editor
+    }
+
+    /// Keys for `actions` in the active sections, like `CTRL+S: SAVE | ESC: BACK`.
+    /// Actions without a key are left out.
+    pub fn key_hints(&self, actions: &[(Action, &str)]) -> String {
+        let layers = self.key_layers();
+        actions
+            .iter()
+            .filter_map(|&(action, label)| {
+                let chord = self.keymap.key_for(layers, action)?;
+                Some(format!("{}: {}", chord.to_string().to_uppercase(), label))
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    fn update_input_block(&mut self) {
+        let title = match (self.screen, self.multiline) {
+            (Screen::Editor, _) => format!(
+                "EDITOR - ENTER: NEW LINE | {}",
+                self.key_hints(&[(Action::Submit, "SAVE"), (Action::ShowJournal, "BACK")])
+            ),
+            _ if self.keymap.modal && self.input_mode == InputMode::Normal => format!(
+                "Input -- NORMAL -- {}",
+                self.key_hints(&[(Action::InsertMode, "INSERT")])
+            ),
+            (_, multiline) => {
+                let mut title = String::from("Input");
+                if self.keymap.modal {
+                    title.push_str(" -- INSERT --");
+                }
+                if multiline {
+                    title.push_str(" (multi-line) - Enter: new line, ");
+                    title.push_str(&self.key_hints(&[(Action::Submit, "save")]));
+                }
+                title
+            }
+        };
+        self.textarea.set_block(
+            ratatui::widgets::Block::default()
+                .borders(ratatui::widgets::Borders::ALL)
+                .title(title),
+        );
+    }
+
+    /// What `{placeholder}`s in templates are replaced with.
+    pub fn template_values(&self) -> Vec<(&'static str, String)> {
+        let now = Local::now();
+        let session = (Utc::now() - self.session_start).num_minutes().max(0) as u32;
+        vec![
+            ("date", now.format("%Y-%m-%d").to_string()),
+            ("time", now.format("%H:%M").to_string()),
+            ("weekday", now.format("%A").to_string()),
+            ("wpm", self.wpm.to_string()),
+            ("lpm", self.lpm.to_string()),
+            ("focus", format!("{:.0}", self.focus_level)),
+            ("active_minutes", self.progress.active_minutes.to_string()),
+            ("keystrokes", self.progress.keystrokes.to_string()),
+            ("entries", self.progress.journal_entries.to_string()),
+            ("pomodoros", self.progress.pomodoros.to_string()),
+            ("session", stats::format_minutes(session)),
+            ("session_keystrokes", self.session_keystrokes.to_string()),
+        ]
+    }
+
+    pub fn open_picker(&mut self) {
+        if !self.templates.is_empty() {
+            self.picker = Some(0);
+        }
+    }
+
+    pub fn move_picker(&mut self, rows: isize) {
+        if let Some(i) = self.picker {
+            let last = self.templates.len().saturating_sub(1);
+            self.picker = Some(i.saturating_add_signed(rows).min(last));
+        }
+    }
+
+    /// Inserts the picked template at the cursor.
+    pub fn apply_picked(&mut self) {
+        if let Some(i) = self.picker.take() {
+            self.apply_template(i);
+        }
+    }
+
+    /// Inserts template `index`, filled in, at the cursor. Templates with more
+    /// than one line open in the full-screen editor.
+    pub fn apply_template(&mut self, index: usize) {
+        let Some(template) = self.templates.get(index) else {
+            return;
+        };
+        let text = templates::expand(&template.body, &self.template_values());
+        if text.contains('\n') && self.screen != Screen::Editor {
+            self.screen = Screen::Editor;
+        }
+        self.input_mode = InputMode::Insert;
+        self.update_input_block();
+        self.textarea.insert_str(text);
+    }
+
+    /// Opens templates scheduled since the last check: straight into the
+    /// editor when nothing is being written, otherwise in the picker.
+    pub fn check_prompts(&mut self, now: NaiveDateTime) {
+        let since = std::mem::replace(&mut self.last_prompt_check, now);
+        let Some(template) = templates::due(&self.templates, since, now) else {
+            return;
+        };
+        let index = self
+            .templates
+            .iter()
+            .position(|t| t == template)
+            .unwrap_or_default();
+        if self.screen == Screen::Journal && self.textarea.is_empty() {
+            self.apply_template(index);
+        } else {
+            self.picker = Some(index);
         }
-        self.logs.push(entry);
+    }
+
+    pub fn open_palette(&mut self, mode: PaletteMode) {
+        self.help = false;
+        self.palette = Some(Palette::new(mode));
+    }
+
+    pub fn palette_input(&mut self, c: char) {
+        if let Some(palette) = &mut self.palette {
+            palette.query.push(c);
+            palette.selected = 0;
+        }
+    }
+
+    pub fn palette_backspace(&mut self) {
+        if let Some(palette) = &mut self.palette {
+            palette.query.pop();
+            palette.selected = 0;
+        }
+    }
+
+    pub fn move_palette(&mut self, rows: isize) {
+        if let Some(palette) = &mut self.palette {
+            let last = palette.matches().len().saturating_sub(1);
+            palette.selected = palette.selected.saturating_add_signed(rows).min(last);
+        }
+    }
+
+    /// Closes the palette, returning the highlighted action. A search is
+    /// applied here and returns nothing.
+    pub fn choose_command(&mut self) -> Option<Action> {
+        let palette = self.palette.take()?;
+        match palette.mode {
+            PaletteMode::Commands => palette
+                .matches()
+                .get(palette.selected)
+                .map(|command| command.action),
+            PaletteMode::Search => {
+                self.set_search(&palette.query);
+                None
+            }
+        }
+    }
+
+    /// Runs `action`. `ExternalEditor` needs the terminal and is left to the caller.
+    pub fn run(&mut self, action: Action) {
+        match action {
+            Action::Search => self.open_palette(PaletteMode::Search),
+            Action::ClearSearch => self.set_search(""),
+            Action::ShowJournal => {
+                self.screen = Screen::Journal;
+                self.update_input_block();
+            }
+            Action::ToggleStats => self.toggle_stats(),
+            Action::CycleStatsPeriod => {
+                if self.screen != Screen::Stats {
+                    self.toggle_stats();
+                }
+                self.cycle_stats_period();
+            }
+            Action::OpenEditor => {
+                if self.screen != Screen::Editor {
+                    self.toggle_editor();
+                }
+            }
+            Action::ExternalEditor => {}
+            Action::ToggleMultiline => self.toggle_multiline(),
+            Action::InsertTemplate => self.open_picker(),
+            Action::TogglePomodoro => self.toggle_pomodoro(),
+            Action::ToggleAlerts => {
+                self.alerts_enabled = !self.alerts_enabled;
+                self.alert_active = false;
+                let state = if self.alerts_enabled { "ON" } else { "OFF" };
+                self.notice = Some(format!("INACTIVITY ALERTS {}", state));
+            }
+            Action::CycleLayout => self.cycle_layout(),
+            Action::RetrySaving => self.retry_unsaved(),
+            Action::ZoomIn => self.zoom_in(),
+            Action::ZoomOut => self.zoom_out(),
+            Action::PanLeft => self.pan_left(),
+            Action::PanRight => self.pan_right(),
+            Action::Export(format) => {
+                let path = std::path::PathBuf::from(format!(
+                    "captains_log-{}.{}",
+                    Local::now().format("%Y-%m-%d"),
+                    format.extension()
+                ));
+                self.export_to(format, &path);
+            }
+            Action::Help => {
+                self.palette = None;
+                self.help = !self.help;
+            }
+            Action::Quit => self.should_quit = true,
+            Action::Palette => self.open_palette(PaletteMode::Commands),
+            Action::SelectPrevious => self.select_previous(),
+            Action::SelectNext => self.select_next(),
+            Action::OpenEntry => self.open_detail(),
+            Action::ClearSelection => self.clear_selection(),
+            Action::Submit => self.submit_entry(),
+            Action::SubmitOrNewLine if self.enter_adds_line() => self.textarea.insert_newline(),
+            Action::SubmitOrNewLine => self.submit_entry(),
+            Action::NewLine => self.textarea.insert_newline(),
+            Action::GrowInput => self.resize_input(1),
+            Action::ShrinkInput => self.resize_input(-1),
+            Action::ScrollUp => self.scroll_detail(-1),
+            Action::ScrollDown => self.scroll_detail(1),
+            Action::PageUp => self.scroll_detail(-10),
+            Action::PageDown => self.scroll_detail(10),
+            Action::NormalMode | Action::InsertMode => {
+                self.input_mode = match action {
+                    Action::NormalMode => InputMode::Normal,
+                    _ => InputMode::Insert,
+                };
+                self.update_input_block();
+            }
+        }
+    }
+
+    /// Runs `action`, or hands it back if the caller has to run it.
+    fn dispatch(&mut self, action: Action) -> Option<Action> {
+        if action == Action::ExternalEditor {
+            return Some(action);
+        }
+        self.run(action);
+        None
+    }
+
+    /// Whether the journal's normal-mode keys apply: in normal mode, or with
+    /// an empty input when the keymap has no modes.
+    fn normal(&self) -> bool {
+        if self.keymap.modal {
+            self.input_mode == InputMode::Normal
+        } else {
+            self.browsing()
+        }
+    }
+
+    /// Key binding sections that apply right now, most specific first.
+    pub fn key_layers(&self) -> &'static [Section] {
+        self.keymap.layers(self.screen, self.normal())
+    }
+
+    /// Handles a key press. Popups use fixed keys; everything else goes
+    /// through the keymap and, if unbound, to the input. Returns the action
+    /// when it needs the terminal (`ExternalEditor`).
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        self.notice = None;
+        let typed = !key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        if self.palette.is_some() {
+            match key.code {
+                KeyCode::Esc => self.palette = None,
+                KeyCode::Enter => {
+                    return self
+                        .choose_command()
+                        .and_then(|action| self.dispatch(action));
+                }
+                KeyCode::Up => self.move_palette(-1),
+                KeyCode::Down => self.move_palette(1),
+                KeyCode::Backspace => self.palette_backspace(),
+                KeyCode::Char(c) if typed => self.palette_input(c),
+                _ => {}
+            }
+            return None;
+        }
+        if self.help {
+            self.help = false;
+            return None;
+        }
+        if self.picker.is_some() {
+            match key.code {
+                KeyCode::Up => self.move_picker(-1),
+                KeyCode::Down => self.move_picker(1),
+                KeyCode::Enter => self.apply_picked(),
+                KeyCode::Esc => self.picker = None,
+                _ => {}
+            }
+            return None;
+        }
+
+        let normal = self.normal();
+        let layers = self.keymap.layers(self.screen, normal);
+        match self.keymap.lookup(layers, KeyChord::from(key)) {
+            Some(action) => return self.dispatch(action),
+            None if self.keymap.types_text(self.screen, normal) => {
+                self.textarea.input(key);
+            }
+            None => {}
+        }
+        None
+    }
+
+    /// Exports the whole journal to `path`, reporting the outcome in the footer.
+    pub fn export_to(&mut self, format: export::Format, path: &std::path::Path) {
+        let result = std::fs::File::create(path).and_then(|file| {
+            let mut out = std::io::BufWriter::new(file);
+            let count = export::export(
+                self.journal.as_ref(),
+                format,
+                &export::Filter::default(),
+                &Local,
+                &mut out,
+            )?;
+            std::io::Write::flush(&mut out)?;
+            Ok(count)
+        });
+        self.notice = Some(match result {
+            Ok(count) => format!("EXPORTED {} ENTRIES TO {}", count, path.display()),
+            Err(e) => format!("EXPORT FAILED: {}", e),
+        });
+    }
+
+    /// Tries to save every pending entry, stopping at the first failure.
+    pub fn retry_unsaved(&mut self) {
+        self.last_retry = Utc::now();
+        while let Some(entry) = self.unsaved.first() {
+            if let Err(e) = self.journal.save(entry) {
+                self.show_save_error(Some(format!(
+                    "JOURNAL NOT SAVED ({} PENDING): {}",
+                    self.unsaved.len(),
+                    e
+                )));
+                return;
+            }
+            self.unsaved.remove(0);
+        }
+        self.show_journal_status();
+    }
+
+    /// Shows a problem the journal ran into after saving, such as a failed
+    /// background push, or clears the last one once it is resolved.
+    fn show_journal_status(&mut self) {
+        let status = self
+            .journal
+            .status()
+            .map(|e| format!("JOURNAL SAVED, BUT {}", e));
+        self.show_save_error(status);
+    }
+
+    /// Shows a save failure over any other error; a successful save only
+    /// clears the message an earlier save left.
+    fn show_save_error(&mut self, message: Option<String>) {
+        if message.is_some() || self.error == self.save_error {
+            self.error = message.clone();
+        }
+        self.save_error = message;
+    }
+
+    /// Re-reads the journal after another process changed it. Entries still
+    /// waiting to be saved are kept.
+    pub fn reload_journal(&mut self) {
+        let (mut logs, warning) = load_logs(self.journal.as_ref());
+        if warning.is_some() && logs.is_empty() && !self.logs.is_empty() {
+            // A failed read, e.g. of a busy database, should not blank the panel
+            return;
+        }
+        logs.extend(self.unsaved.iter().cloned());
+        logs.sort_by_key(|e| e.timestamp);
+        let today = self.today;
+        self.progress.journal_entries = logs
+            .iter()
+            .filter(|e| e.timestamp.with_timezone(&Local).date_naive() == today)
+            .count() as u32;
+        // Follow the selected entry to its new place; among identical
+        // entries, the one with as many copies before it
+        self.selected = self.selected.and_then(|i| {
+            let selected = self.logs.get(i)?;
+            let copies = self.logs[..i].iter().filter(|e| *e == selected).count();
+            logs.iter()
+                .enumerate()
+                .filter(|(_, e)| *e == selected)
+                .nth(copies)
+                .map(|(i, _)| i)
+        });
+        if self.selected.is_none() && self.screen == Screen::Detail {
+            self.screen = Screen::Journal;
+        }
+        self.logs = logs;
+        self.journal_warning = warning;
+    }
+
+    /// Whether arrow keys move through the journal rather than the input.
+    pub fn browsing(&self) -> bool {
+        self.screen == Screen::Journal && self.textarea.is_empty()
+    }
+
+    /// Indices into `logs` of the entries shown in the journal panel.
+    pub fn visible_logs(&self) -> Vec<usize> {
+        let words: Vec<String> = self
+            .search
+            .iter()
+            .flat_map(|q| q.split_whitespace())
+            .map(str::to_lowercase)
+            .collect();
+        (0..self.logs.len())
+            .filter(|&i| {
+                let content = self.logs[i].content.to_lowercase();
+                words.iter().all(|w| content.contains(w.as_str()))
+            })
+            .collect()
+    }
+
+    /// Selects the entry before the current one, or the newest if none is.
+    pub fn select_previous(&mut self) {
+        let visible = self.visible_logs();
+        self.selected = match self
+            .selected
+            .and_then(|i| visible.iter().position(|&v| v == i))
+        {
+            Some(pos) => Some(visible[pos.saturating_sub(1)]),
+            None => visible.last().copied(),
+        };
+    }
+
+    /// Selects the entry after the current one; past the newest, none is.
+    pub fn select_next(&mut self) {
+        let visible = self.visible_logs();
+        self.selected = self
+            .selected
+            .and_then(|i| visible.iter().position(|&v| v == i))
+            .and_then(|pos| visible.get(pos + 1).copied());
+    }
+
+    /// Drops the selection, or the search once nothing is selected.
+    pub fn clear_selection(&mut self) {
+        if self.selected.take().is_none() {
+            self.search = None;
+        }
+    }
+
+    /// Filters the journal panel to entries containing every word of `query`.
+    pub fn set_search(&mut self, query: &str) {
+        self.search = Some(query.trim().to_string()).filter(|q| !q.is_empty());
+        self.selected = self.selected.filter(|i| self.visible_logs().contains(i));
+    }
+
+    pub fn selected_entry(&self) -> Option<&JournalEntry> {
+        self.selected.and_then(|i| self.logs.get(i))
+    }
+
+    /// Shows the selected entry on its own.
+    pub fn open_detail(&mut self) {
+        if self.selected_entry().is_some() {
+            self.screen = Screen::Detail;
+            self.detail_scroll = 0;
+        }
+    }
+
+    pub fn close_detail(&mut self) {
+        self.screen = Screen::Journal;
+    }
+
+    pub fn scroll_detail(&mut self, lines: i16) {
+        self.detail_scroll = self.detail_scroll.saturating_add_signed(lines);
+    }
+
+    fn report_error(&mut self, what: &str, e: std::io::Error) {
+        self.error = Some(format!("FAILED TO SAVE {}: {}", what, e));
     }
 
     pub fn register_activity(&mut self) {
         self.last_activity = Local::now();
-        self.focus_level = (self.focus_level + 1.0).min(100.0);
         self.alert_active = false;
+        self.minute_keystrokes += 1;
+        self.progress.keystrokes += 1;
+        self.session_keystrokes += 1;
+    }
+
+    /// Closes out the previous day's goals once the local date changes.
+    pub fn roll_day(&mut self, today: NaiveDate) {
+        if today != self.today {
+            self.save_goals();
+            self.today = today;
+            self.progress = Progress::default();
+        }
+    }
+
+    fn today_record(&self) -> DayRecord {
+        DayRecord {
+            date: self.today,
+            progress: self.progress,
+            met: self.config.goals.is_met(&self.progress),
+        }
+    }
+
+    /// Goal history with today's live progress in place of its saved record.
+    pub fn goal_records(&self) -> Vec<DayRecord> {
+        let mut records = self.goal_history.clone();
+        records.retain(|r| r.date != self.today);
+        records.push(self.today_record());
+        records
+    }
+
+    /// Persists today's goal progress to the goal history.
+    pub fn save_goals(&mut self) {
+        match self.goal_log.upsert(self.today_record()) {
+            Ok(records) => self.goal_history = records,
+            Err(e) => self.report_error("GOALS", e),
+        }
+    }
+
+    /// Starts a pomodoro, or cancels the running one.
+    pub fn toggle_pomodoro(&mut self) {
+        self.pomodoro = match self.pomodoro {
+            Some(_) => None,
+            None => Some(Pomodoro {
+                started: Utc::now(),
+                length: TimeDelta::minutes(self.config.goals.pomodoro_minutes as i64),
+            }),
+        };
+    }
+
+    /// Persists the current minute's keystrokes once the clock has moved past it.
+    pub fn roll_minute(&mut self, now: DateTime<Utc>) {
+        let minute = minute_of(now);
+        if minute != self.current_minute {
+            self.flush_activity();
+            self.current_minute = minute;
+        }
+    }
+
+    /// Writes the in-progress minute to the activity log, if it had any keystrokes.
+    pub fn flush_activity(&mut self) {
+        if self.minute_keystrokes == 0 {
+            return;
+        }
+        let sample = ActivitySample {
+            minute: self.current_minute,
+            keystrokes: self.minute_keystrokes,
+        };
+        if let Err(e) = self.activity_log.append(&sample) {
+            self.report_error("ACTIVITY", e);
+        }
+        match self.history.last_mut() {
+            Some(last) if last.minute == sample.minute => last.keystrokes += sample.keystrokes,
+            _ => {
+                self.history.push(sample);
+                self.progress.active_minutes += 1;
+            }
+        }
+        self.minute_keystrokes = 0;
+    }
+
+    /// Appends one tick's keystroke count to the activity stream.
+    pub fn record_tick(&mut self, count: u32) {
+        if self.activity_stream.len() >= TICK_HISTORY {
+            self.activity_stream.pop_front();
+        }
+        self.activity_stream.push_back(count);
+        self.last_tick_at = Utc::now();
+
+        self.focus_model.on_tick(count, TICK_MILLIS as f64 / 1000.0);
+        self.focus_level = self.focus_model.level();
+
+        if let Some(period) = self.flow_detector.on_tick(self.last_tick_at, count) {
+            self.end_flow(period);
+        }
+    }
+
+    /// Records a finished flow period and, if configured, journals it.
+    fn end_flow(&mut self, period: FlowPeriod) {
+        if let Err(e) = self.flow_log.append(&period) {
+            self.report_error("FLOW PERIOD", e);
+        }
+        self.flows.push(period);
+
+        if self.config.flow.auto_journal {
+            let start: DateTime<Local> = period.start.into();
+            let end: DateTime<Local> = period.end.into();
+            self.add_log(format!(
+                "Flow session {} - {} ({} min, {} keystrokes)",
+                start.format("%H:%M"),
+                end.format("%H:%M"),
+                period.duration().num_minutes(),
+                period.keystrokes
+            ));
+        }
+    }
+
+    /// Closes any ongoing flow period; called on exit.
+    pub fn finish_flow(&mut self) {
+        if let Some(period) = self.flow_detector.finish() {
+            self.end_flow(period);
+        }
+    }
+
+    /// Finished flow periods plus the ongoing one, if any.
+    pub fn all_flows(&self) -> Vec<FlowPeriod> {
+        let mut flows = self.flows.clone();
+        flows.extend(self.flow_detector.current());
+        flows
+    }
+
+    /// Everything the activity timeline can draw from.
+    pub fn history_view(&self) -> History<'_> {
+        History {
+            ticks: &self.activity_stream,
+            tick_len: TimeDelta::milliseconds(TICK_MILLIS as i64),
+            ticks_end: self.last_tick_at,
+            minutes: &self.history,
+        }
+    }
+
+    /// Right edge of the visible timeline.
+    pub fn timeline_end(&self) -> DateTime<Utc> {
+        self.last_tick_at - self.timeline_offset
+    }
+
+    /// Steps through the layout presets, then back to following the terminal size.
+    pub fn cycle_layout(&mut self) {
+        let next = match self.layout {
+            None => Some(LayoutPreset::ALL[0]),
+            Some(current) => LayoutPreset::ALL
+                .iter()
+                .position(|&p| p == current)
+                .and_then(|i| LayoutPreset::ALL.get(i + 1))
+                .copied(),
+        };
+        self.layout = next;
+        self.notice = Some(match next {
+            Some(preset) => format!("LAYOUT: {}", preset.label()),
+            None => "LAYOUT: AUTOMATIC".to_string(),
+        });
+    }
+
+    /// Handles a mouse event on a `screen`-sized terminal. Clicks select and
+    /// open entries, inspect the chart or dismiss the alert; the wheel moves
+    /// through entries or scrolls the open one. Popups ignore the mouse.
+    pub fn handle_mouse(&mut self, mouse: MouseEvent, screen: ratatui::layout::Rect) {
+        if self.palette.is_some() || self.picker.is_some() {
+            return;
+        }
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.notice = None;
+                if self.help {
+                    self.help = false;
+                    return;
+                }
+                match ui::target_at(self, screen, mouse.column, mouse.row) {
+                    Target::Alert => {
+                        self.last_activity = Local::now();
+                        self.alert_active = false;
+                    }
+                    Target::Entry(i) if self.selected == Some(i) => self.open_detail(),
+                    Target::Entry(i) => self.selected = Some(i),
+                    Target::Chart { column, width } => self.inspect_chart(column, width),
+                    Target::Other => {}
+                }
+            }
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+                let up = mouse.kind == MouseEventKind::ScrollUp;
+                match self.screen {
+                    Screen::Journal if up => self.select_previous(),
+                    Screen::Journal => self.select_next(),
+                    Screen::Detail => self.scroll_detail(if up { -1 } else { 1 }),
+                    Screen::Editor => {
+                        self.textarea.input(mouse);
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Shows the keystrokes behind column `column` of a `width`-column chart,
+    /// over the same window the chart averages it on.
+    pub fn inspect_chart(&mut self, column: usize, width: usize) {
+        let zoom = self.timeline_zoom;
+        let (from, to) = timeline::column_span(self.timeline_end(), zoom, width, column);
+        let from = from.min(to - TimeDelta::seconds(1));
+        let count = self.history_view().count_between(from, to);
+        let seconds = (to - from).num_milliseconds() as f64 / 1000.0;
+        let from: DateTime<Local> = from.into();
+        let to: DateTime<Local> = to.into();
+        self.notice = Some(format!(
+            "{}–{}: {} KEYSTROKES, {} LPM",
+            from.format("%H:%M:%S"),
+            to.format("%H:%M:%S"),
+            count.round(),
+            (count / seconds * 60.0).round()
+        ));
+    }
+
+    pub fn zoom_in(&mut self) {
+        self.timeline_zoom = self.timeline_zoom.zoom_in();
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.timeline_zoom = self.timeline_zoom.zoom_out();
+    }
+
+    /// Moves the timeline back in time, up to `HISTORY_DAYS`.
+    pub fn pan_left(&mut self) {
+        let limit = TimeDelta::days(HISTORY_DAYS) - self.timeline_zoom.span();
+        self.timeline_offset = (self.timeline_offset + self.timeline_zoom.pan_step()).min(limit);
+    }
+
+    /// Moves the timeline forward in time, stopping at the present.
+    pub fn pan_right(&mut self) {
+        self.timeline_offset =
+            (self.timeline_offset - self.timeline_zoom.pan_step()).max(TimeDelta::zero());
+    }
+
+    /// Recomputes the stats report for `stats_period` from persisted activity and logs.
+    pub fn refresh_stats(&mut self) {
+        let mut samples = self.activity_log.load_all().unwrap_or_default();
+        if self.minute_keystrokes > 0 {
+            samples.push(ActivitySample {
+                minute: self.current_minute,
+                keystrokes: self.minute_keystrokes,
+            });
+        }
+        let mut flows = self.flow_log.load_all().unwrap_or_default();
+        flows.extend(self.flow_detector.current());
+        self.stats = Some(stats::build_report(
+            self.stats_period,
+            Local::now().date_naive(),
+            &Local,
+            Records {
+                samples: &samples,
+                entries: &self.logs,
+                flows: &flows,
+            },
+        ));
+    }
+
+    pub fn toggle_stats(&mut self) {
+        self.screen = match self.screen {
+            Screen::Stats => Screen::Journal,
+            _ => {
+                self.refresh_stats();
+                Screen::Stats
+            }
+        };
+        self.update_input_block();
+    }
+
+    pub fn cycle_stats_period(&mut self) {
+        self.stats_period = self.stats_period.next();
+        self.refresh_stats();
     }
 }
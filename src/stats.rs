@@ -0,0 +1,317 @@
+use crate::activity::ActivitySample;
+use crate::flow::FlowPeriod;
+use crate::journal::JournalEntry;
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, TimeDelta, TimeZone, Timelike, Utc};
+use serde::Serialize;
+use std::fmt::Write;
+
+/// Reporting window for the stats screen and `captains_log report`.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    /// First day and the day after the last day of the period containing `today`.
+    pub fn range(self, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            Period::Day => (today, today + Days::new(1)),
+            Period::Week => {
+                let start = today - Days::new(today.weekday().num_days_from_monday() as u64);
+                (start, start + Days::new(7))
+            }
+            Period::Month => {
+                let start = today.with_day(1).unwrap_or(today);
+                (start, start + Months::new(1))
+            }
+        }
+    }
+
+    /// The period immediately before the one containing `today`.
+    pub fn previous_range(self, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let (start, _) = self.range(today);
+        self.range(start - Days::new(1))
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Period::Day => Period::Week,
+            Period::Week => Period::Month,
+            Period::Month => Period::Day,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Period::Day => "DAY",
+            Period::Week => "WEEK",
+            Period::Month => "MONTH",
+        }
+    }
+}
+
+/// Aggregated activity and journal numbers over a span of time.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Summary {
+    pub active_minutes: u32,
+    pub keystrokes: u32,
+    pub avg_wpm: u32,
+    pub peak_wpm: u32,
+    /// Longest run of consecutive active minutes.
+    pub longest_streak_minutes: u32,
+    /// Time spent in detected flow periods.
+    pub flow_minutes: u32,
+    pub entries: u32,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DayStats {
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub summary: Summary,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Report {
+    pub period: Period,
+    pub start: NaiveDate,
+    /// Last day included in the report.
+    pub end: NaiveDate,
+    pub summary: Summary,
+    /// Same numbers for the preceding period, for comparisons.
+    pub previous: Summary,
+    pub days: Vec<DayStats>,
+    /// Keystrokes per local hour of day.
+    pub hourly: [u32; 24],
+}
+
+/// Recorded data a report is computed from.
+#[derive(Clone, Copy)]
+pub struct Records<'a> {
+    pub samples: &'a [ActivitySample],
+    pub entries: &'a [JournalEntry],
+    pub flows: &'a [FlowPeriod],
+}
+
+/// First instant of `date` in `tz`, as UTC.
+pub fn day_start<Tz: TimeZone>(tz: &Tz, date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|| midnight.and_utc())
+}
+
+/// Builds the report for the period containing `today`, bucketing by days in `tz`.
+pub fn build_report<Tz: TimeZone>(
+    period: Period,
+    today: NaiveDate,
+    tz: &Tz,
+    records: Records,
+) -> Report {
+    let (start, end) = period.range(today);
+    let (prev_start, prev_end) = period.previous_range(today);
+    let local_date = |s: &ActivitySample| s.minute.with_timezone(tz).date_naive();
+
+    let in_range = |from: NaiveDate, to: NaiveDate| {
+        let samples: Vec<ActivitySample> = records
+            .samples
+            .iter()
+            .filter(|s| (from..to).contains(&local_date(s)))
+            .copied()
+            .collect();
+        let entries = records
+            .entries
+            .iter()
+            .filter(|e| (from..to).contains(&e.timestamp.with_timezone(tz).date_naive()))
+            .count() as u32;
+        let (from, to) = (day_start(tz, from), day_start(tz, to));
+        let flow: TimeDelta = records.flows.iter().map(|f| f.overlap(from, to)).sum();
+
+        let mut summary = summarize(&samples, entries);
+        summary.flow_minutes = flow.num_minutes() as u32;
+        (samples, summary)
+    };
+
+    let (current, summary) = in_range(start, end);
+    let (_, previous) = in_range(prev_start, prev_end);
+
+    let mut hourly = [0; 24];
+    for s in &current {
+        let hour = s.minute.with_timezone(tz).hour() as usize;
+        hourly[hour] += s.keystrokes;
+    }
+
+    let days = start
+        .iter_days()
+        .take_while(|d| *d < end)
+        .map(|date| DayStats {
+            date,
+            summary: in_range(date, date + Days::new(1)).1,
+        })
+        .collect();
+
+    Report {
+        period,
+        start,
+        end: end - Days::new(1),
+        summary,
+        previous,
+        days,
+        hourly,
+    }
+}
+
+/// Adds up samples of the same minute, which a restart within a minute
+/// leaves behind. `samples` must be sorted by minute.
+pub fn merge_minutes(samples: &[ActivitySample]) -> Vec<ActivitySample> {
+    let mut merged: Vec<ActivitySample> = Vec::with_capacity(samples.len());
+    for s in samples {
+        match merged.last_mut() {
+            Some(last) if last.minute == s.minute => last.keystrokes += s.keystrokes,
+            _ => merged.push(*s),
+        }
+    }
+    merged
+}
+
+/// Computes a summary over samples sorted by minute. `flow_minutes` is left
+/// at zero; `build_report` fills it in from the flow log.
+pub fn summarize(samples: &[ActivitySample], entries: u32) -> Summary {
+    let merged = merge_minutes(samples);
+    let active: Vec<&ActivitySample> = merged.iter().filter(|s| s.keystrokes > 0).collect();
+    let active_minutes = active.len() as u32;
+    let keystrokes: u32 = active.iter().map(|s| s.keystrokes).sum();
+    let peak_wpm = active.iter().map(|s| s.wpm()).max().unwrap_or(0);
+    let avg_wpm = keystrokes.checked_div(active_minutes).unwrap_or(0) / 5;
+
+    let mut longest_streak_minutes = 0;
+    let mut streak = 0;
+    let mut last = None;
+    for s in &active {
+        streak = match last {
+            Some(prev) if s.minute - prev == TimeDelta::minutes(1) => streak + 1,
+            _ => 1,
+        };
+        longest_streak_minutes = longest_streak_minutes.max(streak);
+        last = Some(s.minute);
+    }
+
+    Summary {
+        active_minutes,
+        keystrokes,
+        avg_wpm,
+        peak_wpm,
+        longest_streak_minutes,
+        flow_minutes: 0,
+        entries,
+    }
+}
+
+/// Relative change from `previous` to `current` in percent, if there is a baseline.
+pub fn change_percent(current: u32, previous: u32) -> Option<i64> {
+    if previous == 0 {
+        return None;
+    }
+    Some(((current as f64 - previous as f64) / previous as f64 * 100.0).round() as i64)
+}
+
+/// Formats a minute count as `1h 05m`.
+pub fn format_minutes(minutes: u32) -> String {
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+/// Formats the change from `previous` as `+12%`, or `n/a` without a baseline.
+pub fn format_change(current: u32, previous: u32) -> String {
+    match change_percent(current, previous) {
+        Some(pct) => format!("{:+}%", pct),
+        None => "n/a".to_string(),
+    }
+}
+
+impl Report {
+    /// Plain-text rendering used by `captains_log report`.
+    pub fn to_text(&self) -> String {
+        let s = &self.summary;
+        let p = &self.previous;
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "CAPTAIN'S LOG REPORT - {} {} .. {}",
+            self.period.label(),
+            self.start,
+            self.end
+        );
+        let _ = writeln!(out);
+        let _ = writeln!(out, "{:<18}{:>10}{:>12}", "", "CURRENT", "VS PREV");
+        let rows = [
+            (
+                "Active time",
+                format_minutes(s.active_minutes),
+                format_change(s.active_minutes, p.active_minutes),
+            ),
+            (
+                "Keystrokes",
+                s.keystrokes.to_string(),
+                format_change(s.keystrokes, p.keystrokes),
+            ),
+            (
+                "Average WPM",
+                s.avg_wpm.to_string(),
+                format_change(s.avg_wpm, p.avg_wpm),
+            ),
+            (
+                "Peak WPM",
+                s.peak_wpm.to_string(),
+                format_change(s.peak_wpm, p.peak_wpm),
+            ),
+            (
+                "Longest streak",
+                format_minutes(s.longest_streak_minutes),
+                format_change(s.longest_streak_minutes, p.longest_streak_minutes),
+            ),
+            (
+                "Flow time",
+                format_minutes(s.flow_minutes),
+                format_change(s.flow_minutes, p.flow_minutes),
+            ),
+            (
+                "Journal entries",
+                s.entries.to_string(),
+                format_change(s.entries, p.entries),
+            ),
+        ];
+        for (name, value, change) in rows {
+            let _ = writeln!(out, "{:<18}{:>10}{:>12}", name, value, change);
+        }
+
+        let _ = writeln!(out);
+        let _ = writeln!(out, "DAILY BREAKDOWN");
+        for day in &self.days {
+            let d = &day.summary;
+            let _ = writeln!(
+                out,
+                "  {}  active {}  avg {:>3} wpm  peak {:>3} wpm  streak {}  flow {}  entries {}",
+                day.date.format("%a %Y-%m-%d"),
+                format_minutes(d.active_minutes),
+                d.avg_wpm,
+                d.peak_wpm,
+                format_minutes(d.longest_streak_minutes),
+                format_minutes(d.flow_minutes),
+                d.entries
+            );
+        }
+
+        let _ = writeln!(out);
+        let _ = writeln!(out, "TIME OF DAY (keystrokes)");
+        let max = self.hourly.iter().copied().max().unwrap_or(0).max(1);
+        for (hour, count) in self.hourly.iter().enumerate() {
+            let bar = "#".repeat((*count as usize * 40).div_ceil(max as usize));
+            let _ = writeln!(out, "  {:02}:00 {:<40} {}", hour, bar, count);
+        }
+        out
+    }
+}
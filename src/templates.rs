@@ -0,0 +1,91 @@
+//! Entry templates with placeholders, and the prompts scheduled from them.
+
+use chrono::{NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Deserializer};
+
+/// One `[[templates]]` entry of the config file.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pub name: String,
+    /// Text with `{placeholder}`s; `{{` and `}}` stand for literal braces.
+    pub body: String,
+    /// Local time of day (`HH:MM`) to open this template at.
+    #[serde(default, deserialize_with = "time_of_day")]
+    pub at: Option<NaiveTime>,
+}
+
+/// Used when the config file defines no templates.
+pub fn defaults() -> Vec<Template> {
+    vec![Template {
+        name: "End of day".to_string(),
+        body: "## {weekday}, {date}\n\
+               {active_minutes} min active, {keystrokes} keystrokes, {wpm} WPM, focus {focus}%.\n\
+               \n\
+               **What did I ship?**\n- \n\
+               **What blocked me?**\n- \n\
+               **Tomorrow?**\n- "
+            .to_string(),
+        at: None,
+    }]
+}
+
+fn time_of_day<'de, D: Deserializer<'de>>(d: D) -> Result<Option<NaiveTime>, D::Error> {
+    let Some(text) = Option::<String>::deserialize(d)? else {
+        return Ok(None);
+    };
+    NaiveTime::parse_from_str(&text, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(&text, "%H:%M:%S"))
+        .map(Some)
+        .map_err(|_| serde::de::Error::custom(format!("`{}` is not a time like 17:30", text)))
+}
+
+/// Replaces every `{name}` in `body` that `values` has a value for. Unknown
+/// placeholders are left as typed.
+pub fn expand(body: &str, values: &[(&str, String)]) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            out.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+        let value = rest
+            .find('}')
+            .filter(|_| rest.starts_with('{'))
+            .and_then(|end| {
+                let name = &rest[1..end];
+                values
+                    .iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| (value, end))
+            });
+        match value {
+            Some((value, end)) => {
+                out.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push_str(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The first template whose time of day falls after `since` and no later than `now`.
+pub fn due(templates: &[Template], since: NaiveDateTime, now: NaiveDateTime) -> Option<&Template> {
+    templates.iter().find(|t| {
+        t.at.is_some_and(|at| {
+            let today = now.date().and_time(at);
+            let yesterday = today - chrono::TimeDelta::days(1);
+            [today, yesterday]
+                .iter()
+                .any(|&time| since < time && time <= now)
+        })
+    })
+}
@@ -0,0 +1,460 @@
+//! Colors and text styles of the TUI, from a built-in preset or the config.
+//!
+//! Styles are written as words: colors (`green`, `#268bd2`, `244`), `on`
+//! before a background color, and modifiers such as `bold` or `reversed`.
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Letters per minute where the waveform turns medium, fast and very fast.
+pub const DEFAULT_THRESHOLDS: [u32; 3] = [180, 360, 540];
+
+/// `[theme]` settings.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// A built-in theme or one defined under `[themes.NAME]`.
+    pub name: String,
+    pub thresholds: [u32; 3],
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            name: "cyberpunk".to_string(),
+            thresholds: DEFAULT_THRESHOLDS,
+        }
+    }
+}
+
+/// A `[themes.NAME]` table: styles by role on top of a base theme.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserTheme {
+    /// Built-in theme to start from; `cyberpunk` if not given.
+    pub base: Option<String>,
+    #[serde(flatten)]
+    pub styles: BTreeMap<String, String>,
+}
+
+/// Names of the built-in themes.
+pub const PRESETS: [&str; 4] = ["cyberpunk", "solarized", "high-contrast", "monochrome"];
+
+const CYBERPUNK: &[(&str, &str)] = &[
+    ("header", "green bold"),
+    ("border", "cyan bold"),
+    ("text", "white bold"),
+    ("label", "gray"),
+    ("muted", "darkgray"),
+    ("accent", "magenta bold"),
+    ("selection", "on darkgray"),
+    ("highlight", "black bold on magenta"),
+    ("warning", "yellow bold"),
+    ("error", "red"),
+    ("notice", "green bold"),
+    ("alert", "white bold on red"),
+    ("focus_gauge", "blue bold on darkgray"),
+    ("goals_gauge", "green bold on darkgray"),
+    ("change", "cyan"),
+    ("goal_met", "green"),
+    ("bars", "green"),
+    ("bar_values", "black on green"),
+    ("entry_bars", "magenta"),
+    ("entry_values", "black on magenta"),
+    ("hours", "yellow"),
+    ("flow", "magenta"),
+    ("slow", "blue bold"),
+    ("medium", "green bold"),
+    ("fast", "yellow bold"),
+    ("very_fast", "red bold"),
+    ("heading", "green bold"),
+    ("subheading", "cyan bold"),
+    ("code", "yellow"),
+    ("code_text", "white"),
+    ("keyword", "magenta bold"),
+    ("string", "green"),
+    ("comment", "darkgray italic"),
+    ("link", "blue underlined"),
+    ("tag", "cyan"),
+    ("quote", "gray italic"),
+    ("bullet", "green"),
+];
+
+const SOLARIZED: &[(&str, &str)] = &[
+    ("header", "#268bd2 bold"),
+    ("border", "#2aa198 bold"),
+    ("text", "#93a1a1 bold"),
+    ("label", "#839496"),
+    ("muted", "#586e75"),
+    ("accent", "#6c71c4 bold"),
+    ("selection", "on #073642"),
+    ("highlight", "#fdf6e3 bold on #6c71c4"),
+    ("warning", "#b58900 bold"),
+    ("error", "#dc322f"),
+    ("notice", "#859900 bold"),
+    ("alert", "#fdf6e3 bold on #dc322f"),
+    ("focus_gauge", "#268bd2 bold on #073642"),
+    ("goals_gauge", "#859900 bold on #073642"),
+    ("change", "#2aa198"),
+    ("goal_met", "#859900"),
+    ("bars", "#859900"),
+    ("bar_values", "#002b36 on #859900"),
+    ("entry_bars", "#d33682"),
+    ("entry_values", "#002b36 on #d33682"),
+    ("hours", "#b58900"),
+    ("flow", "#d33682"),
+    ("slow", "#268bd2 bold"),
+    ("medium", "#859900 bold"),
+    ("fast", "#b58900 bold"),
+    ("very_fast", "#dc322f bold"),
+    ("heading", "#cb4b16 bold"),
+    ("subheading", "#2aa198 bold"),
+    ("code", "#b58900"),
+    ("code_text", "#93a1a1"),
+    ("keyword", "#859900 bold"),
+    ("string", "#2aa198"),
+    ("comment", "#586e75 italic"),
+    ("link", "#268bd2 underlined"),
+    ("tag", "#6c71c4"),
+    ("quote", "#839496 italic"),
+    ("bullet", "#cb4b16"),
+];
+
+const HIGH_CONTRAST: &[(&str, &str)] = &[
+    ("header", "white bold"),
+    ("border", "white bold"),
+    ("text", "white bold"),
+    ("label", "white"),
+    ("muted", "gray"),
+    ("accent", "lightyellow bold"),
+    ("selection", "black on white"),
+    ("highlight", "black bold on lightyellow"),
+    ("warning", "lightyellow bold"),
+    ("error", "lightred bold"),
+    ("notice", "lightgreen bold"),
+    ("alert", "white bold on red"),
+    ("focus_gauge", "lightcyan bold on black"),
+    ("goals_gauge", "lightgreen bold on black"),
+    ("change", "lightcyan"),
+    ("goal_met", "lightgreen"),
+    ("bars", "lightgreen"),
+    ("bar_values", "black on lightgreen"),
+    ("entry_bars", "lightmagenta"),
+    ("entry_values", "black on lightmagenta"),
+    ("hours", "lightyellow"),
+    ("flow", "lightmagenta bold"),
+    ("slow", "lightblue bold"),
+    ("medium", "lightgreen bold"),
+    ("fast", "lightyellow bold"),
+    ("very_fast", "lightred bold"),
+    ("heading", "lightyellow bold"),
+    ("subheading", "lightcyan bold"),
+    ("code", "lightyellow"),
+    ("code_text", "white"),
+    ("keyword", "lightmagenta bold"),
+    ("string", "lightgreen"),
+    ("comment", "gray italic"),
+    ("link", "lightcyan underlined"),
+    ("tag", "lightcyan bold"),
+    ("quote", "white italic"),
+    ("bullet", "lightyellow"),
+];
+
+/// No colors at all; used whenever `NO_COLOR` is set.
+const MONOCHROME: &[(&str, &str)] = &[
+    ("header", "bold"),
+    ("border", "bold"),
+    ("text", "bold"),
+    ("muted", "dim"),
+    ("accent", "bold"),
+    ("selection", "reversed"),
+    ("highlight", "reversed bold"),
+    ("warning", "bold"),
+    ("error", "bold"),
+    ("notice", "bold"),
+    ("alert", "reversed bold"),
+    ("focus_gauge", "bold"),
+    ("goals_gauge", "bold"),
+    ("goal_met", "bold"),
+    ("bar_values", "reversed"),
+    ("entry_values", "reversed"),
+    ("flow", "bold"),
+    ("slow", "dim"),
+    ("fast", "bold"),
+    ("very_fast", "bold"),
+    ("heading", "bold"),
+    ("subheading", "bold"),
+    ("code", "italic"),
+    ("keyword", "bold"),
+    ("comment", "dim italic"),
+    ("link", "underlined"),
+    ("tag", "bold"),
+    ("quote", "italic"),
+];
+
+/// Style of every part of the interface, by role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub header: Style,
+    /// Borders of the main panels.
+    pub border: Style,
+    /// Figures such as WPM and report values.
+    pub text: Style,
+    /// Footer and report row names.
+    pub label: Style,
+    /// Secondary text: axis, hints, placeholders.
+    pub muted: Style,
+    /// Popups, search and other things in focus.
+    pub accent: Style,
+    /// The selected journal entry.
+    pub selection: Style,
+    /// The selected row of a popup.
+    pub highlight: Style,
+    pub warning: Style,
+    pub error: Style,
+    pub notice: Style,
+    pub alert: Style,
+    pub focus_gauge: Style,
+    pub goals_gauge: Style,
+    /// Change against the previous period in reports.
+    pub change: Style,
+    pub goal_met: Style,
+    pub bars: Style,
+    pub bar_values: Style,
+    pub entry_bars: Style,
+    pub entry_values: Style,
+    pub hours: Style,
+    pub flow: Style,
+    /// Waveform bars by typing speed.
+    pub slow: Style,
+    pub medium: Style,
+    pub fast: Style,
+    pub very_fast: Style,
+    /// Markdown in journal entries.
+    pub heading: Style,
+    pub subheading: Style,
+    pub code: Style,
+    pub code_text: Style,
+    pub keyword: Style,
+    pub string: Style,
+    pub comment: Style,
+    pub link: Style,
+    pub tag: Style,
+    pub quote: Style,
+    pub bullet: Style,
+    /// See [`DEFAULT_THRESHOLDS`].
+    pub thresholds: [u32; 3],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::preset("cyberpunk").expect("cyberpunk is built in")
+    }
+}
+
+impl Theme {
+    /// A built-in theme by name.
+    pub fn preset(name: &str) -> Option<Self> {
+        let styles = match name {
+            "cyberpunk" => CYBERPUNK,
+            "solarized" => SOLARIZED,
+            "high-contrast" => HIGH_CONTRAST,
+            "monochrome" => MONOCHROME,
+            _ => return None,
+        };
+        let mut theme = Theme::plain();
+        for (role, spec) in styles {
+            theme.set(role, spec).expect("built-in styles are valid");
+        }
+        Some(theme)
+    }
+
+    /// Every role unstyled.
+    fn plain() -> Self {
+        let s = Style::default();
+        Theme {
+            header: s,
+            border: s,
+            text: s,
+            label: s,
+            muted: s,
+            accent: s,
+            selection: s,
+            highlight: s,
+            warning: s,
+            error: s,
+            notice: s,
+            alert: s,
+            focus_gauge: s,
+            goals_gauge: s,
+            change: s,
+            goal_met: s,
+            bars: s,
+            bar_values: s,
+            entry_bars: s,
+            entry_values: s,
+            hours: s,
+            flow: s,
+            slow: s,
+            medium: s,
+            fast: s,
+            very_fast: s,
+            heading: s,
+            subheading: s,
+            code: s,
+            code_text: s,
+            keyword: s,
+            string: s,
+            comment: s,
+            link: s,
+            tag: s,
+            quote: s,
+            bullet: s,
+            thresholds: DEFAULT_THRESHOLDS,
+        }
+    }
+
+    /// Sets the style of `role` from a spec like `black bold on magenta`.
+    pub fn set(&mut self, role: &str, spec: &str) -> Result<(), String> {
+        let style = parse_style(spec)?;
+        let slot = match role {
+            "header" => &mut self.header,
+            "border" => &mut self.border,
+            "text" => &mut self.text,
+            "label" => &mut self.label,
+            "muted" => &mut self.muted,
+            "accent" => &mut self.accent,
+            "selection" => &mut self.selection,
+            "highlight" => &mut self.highlight,
+            "warning" => &mut self.warning,
+            "error" => &mut self.error,
+            "notice" => &mut self.notice,
+            "alert" => &mut self.alert,
+            "focus_gauge" => &mut self.focus_gauge,
+            "goals_gauge" => &mut self.goals_gauge,
+            "change" => &mut self.change,
+            "goal_met" => &mut self.goal_met,
+            "bars" => &mut self.bars,
+            "bar_values" => &mut self.bar_values,
+            "entry_bars" => &mut self.entry_bars,
+            "entry_values" => &mut self.entry_values,
+            "hours" => &mut self.hours,
+            "flow" => &mut self.flow,
+            "slow" => &mut self.slow,
+            "medium" => &mut self.medium,
+            "fast" => &mut self.fast,
+            "very_fast" => &mut self.very_fast,
+            "heading" => &mut self.heading,
+            "subheading" => &mut self.subheading,
+            "code" => &mut self.code,
+            "code_text" => &mut self.code_text,
+            "keyword" => &mut self.keyword,
+            "string" => &mut self.string,
+            "comment" => &mut self.comment,
+            "link" => &mut self.link,
+            "tag" => &mut self.tag,
+            "quote" => &mut self.quote,
+            "bullet" => &mut self.bullet,
+            _ => return Err(format!("unknown style `{}`", role)),
+        };
+        *slot = style;
+        Ok(())
+    }
+
+    /// Style of a waveform column typing at `lpm` letters per minute.
+    pub fn intensity(&self, lpm: u32) -> Style {
+        match self.thresholds.iter().filter(|&&t| lpm >= t).count() {
+            0 => self.slow,
+            1 => self.medium,
+            2 => self.fast,
+            _ => self.very_fast,
+        }
+    }
+}
+
+/// Whether the `NO_COLOR` convention asks for output without colors.
+pub fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+/// The theme `config` picks, with `[themes.*]` as the user's own. With
+/// `no_color` the monochrome theme is used whatever was picked.
+pub fn build(
+    config: &ThemeConfig,
+    themes: &BTreeMap<String, UserTheme>,
+    no_color: bool,
+) -> Result<Theme, String> {
+    if !config.thresholds.is_sorted() {
+        return Err(format!(
+            "[theme] thresholds must be in increasing order, not {:?}",
+            config.thresholds
+        ));
+    }
+    let mut theme = match (themes.get(&config.name), no_color) {
+        (_, true) => Theme::preset("monochrome").expect("monochrome is built in"),
+        (Some(user), false) => {
+            let base = user.base.as_deref().unwrap_or("cyberpunk");
+            let mut theme = Theme::preset(base).ok_or_else(|| {
+                format!(
+                    "[themes.{}] base `{}` is not one of {}",
+                    config.name,
+                    base,
+                    PRESETS.join(", ")
+                )
+            })?;
+            for (role, spec) in &user.styles {
+                theme
+                    .set(role, spec)
+                    .map_err(|e| format!("[themes.{}] {}: {}", config.name, role, e))?;
+            }
+            theme
+        }
+        (None, false) => Theme::preset(&config.name).ok_or_else(|| {
+            format!(
+                "[theme] name `{}` is not one of {} or a [themes.*] table",
+                config.name,
+                PRESETS.join(", ")
+            )
+        })?,
+    };
+    theme.thresholds = config.thresholds;
+    Ok(theme)
+}
+
+/// Parses a style such as `bold yellow`, `#002b36 on #859900` or `reversed`.
+pub fn parse_style(spec: &str) -> Result<Style, String> {
+    let mut style = Style::default();
+    let mut background = false;
+    for word in spec.split_whitespace() {
+        let modifier = match word.to_lowercase().as_str() {
+            "on" => {
+                background = true;
+                continue;
+            }
+            "none" => continue,
+            "bold" => Modifier::BOLD,
+            "dim" => Modifier::DIM,
+            "italic" => Modifier::ITALIC,
+            "underlined" | "underline" => Modifier::UNDERLINED,
+            "reversed" | "reverse" => Modifier::REVERSED,
+            "crossed_out" | "strikethrough" => Modifier::CROSSED_OUT,
+            _ => {
+                let color: Color = word
+                    .parse()
+                    .map_err(|_| format!("`{}` is not a color or modifier", word))?;
+                style = if background {
+                    style.bg(color)
+                } else {
+                    style.fg(color)
+                };
+                background = false;
+                continue;
+            }
+        };
+        style = style.add_modifier(modifier);
+    }
+    if background {
+        return Err(format!("`{}` is missing a color after `on`", spec));
+    }
+    Ok(style)
+}
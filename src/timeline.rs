@@ -0,0 +1,261 @@
+use crate::activity::ActivitySample;
+use crate::flow::FlowPeriod;
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use std::collections::VecDeque;
+
+/// Zoom levels of the activity timeline, from finest to coarsest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Zoom {
+    OneMinute,
+    FifteenMinutes,
+    OneHour,
+    EightHours,
+    Day,
+}
+
+impl Zoom {
+    /// Time covered by the full width of the chart.
+    pub fn span(self) -> TimeDelta {
+        match self {
+            Zoom::OneMinute => TimeDelta::minutes(1),
+            Zoom::FifteenMinutes => TimeDelta::minutes(15),
+            Zoom::OneHour => TimeDelta::hours(1),
+            Zoom::EightHours => TimeDelta::hours(8),
+            Zoom::Day => TimeDelta::hours(24),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Zoom::OneMinute => "1 MIN",
+            Zoom::FifteenMinutes => "15 MIN",
+            Zoom::OneHour => "1 H",
+            Zoom::EightHours => "8 H",
+            Zoom::Day => "24 H",
+        }
+    }
+
+    pub fn zoom_in(self) -> Self {
+        match self {
+            Zoom::OneMinute | Zoom::FifteenMinutes => Zoom::OneMinute,
+            Zoom::OneHour => Zoom::FifteenMinutes,
+            Zoom::EightHours => Zoom::OneHour,
+            Zoom::Day => Zoom::EightHours,
+        }
+    }
+
+    pub fn zoom_out(self) -> Self {
+        match self {
+            Zoom::OneMinute => Zoom::FifteenMinutes,
+            Zoom::FifteenMinutes => Zoom::OneHour,
+            Zoom::OneHour => Zoom::EightHours,
+            Zoom::EightHours | Zoom::Day => Zoom::Day,
+        }
+    }
+
+    /// How far one pan step moves the view.
+    pub fn pan_step(self) -> TimeDelta {
+        self.span() / 4
+    }
+
+    fn axis_format(self) -> &'static str {
+        match self {
+            Zoom::OneMinute => "%H:%M:%S",
+            _ => "%H:%M",
+        }
+    }
+}
+
+/// Recorded keystrokes the timeline draws from: fine-grained ticks for the
+/// recent past and persisted per-minute samples before that.
+pub struct History<'a> {
+    /// Keystrokes per tick, newest last.
+    pub ticks: &'a VecDeque<u32>,
+    pub tick_len: TimeDelta,
+    /// End of the newest tick.
+    pub ticks_end: DateTime<Utc>,
+    /// Per-minute samples, sorted by minute.
+    pub minutes: &'a [ActivitySample],
+}
+
+impl History<'_> {
+    fn ticks_start(&self) -> DateTime<Utc> {
+        self.ticks_end - self.tick_len * self.ticks.len() as i32
+    }
+
+    /// Keystrokes recorded in `[from, to)`, prorating partially covered ticks and minutes.
+    pub fn count_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+        if to <= from {
+            return 0.0;
+        }
+        let ticks_start = self.ticks_start();
+        let mut total = 0.0;
+
+        // Tick-resolution part
+        let (a, b) = (from.max(ticks_start), to.min(self.ticks_end));
+        if a < b && !self.ticks.is_empty() {
+            let tick_ms = self.tick_len.num_milliseconds().max(1);
+            let a_ms = (a - ticks_start).num_milliseconds();
+            let b_ms = (b - ticks_start).num_milliseconds();
+            let first = (a_ms / tick_ms) as usize;
+            let last = ((b_ms + tick_ms - 1) / tick_ms) as usize;
+            for (i, &count) in self.ticks.iter().enumerate().take(last).skip(first) {
+                let start = i as i64 * tick_ms;
+                let overlap = (b_ms.min(start + tick_ms) - a_ms.max(start)).max(0);
+                total += count as f64 * overlap as f64 / tick_ms as f64;
+            }
+        }
+
+        // Anything older than the ticks comes from the minute samples
+        let b = to.min(ticks_start);
+        if from < b {
+            let minute = TimeDelta::minutes(1);
+            let first = self.minutes.partition_point(|s| s.minute + minute <= from);
+            for s in self.minutes[first..].iter().take_while(|s| s.minute < b) {
+                let overlap = (b.min(s.minute + minute) - from.max(s.minute)).num_milliseconds();
+                total += s.keystrokes as f64 * overlap.max(0) as f64 / 60_000.0;
+            }
+        }
+        total
+    }
+}
+
+/// Start and end of column `column` out of `width`, the last one ending at `end`.
+pub fn column_span(
+    end: DateTime<Utc>,
+    zoom: Zoom,
+    width: usize,
+    column: usize,
+) -> (DateTime<Utc>, DateTime<Utc>) {
+    let span_ms = zoom.span().num_milliseconds();
+    let start = end - zoom.span();
+    let edge = |i: usize| start + TimeDelta::milliseconds(span_ms * i as i64 / width.max(1) as i64);
+    (edge(column), edge(column + 1))
+}
+
+/// Keystrokes per second for each of `width` columns, the last one ending at `end`.
+///
+/// Columns shorter than a second are averaged over the trailing second so the
+/// finest zoom keeps the smooth look of the original waveform.
+pub fn buckets(history: &History, end: DateTime<Utc>, zoom: Zoom, width: usize) -> Vec<u32> {
+    if width == 0 {
+        return Vec::new();
+    }
+    let span_ms = zoom.span().num_milliseconds();
+    (0..width)
+        .map(|i| {
+            let (_, bucket_end) = column_span(end, zoom, width, i);
+            let window = TimeDelta::milliseconds((span_ms / width as i64).max(1000));
+            let count = history.count_between(bucket_end - window, bucket_end);
+            (count / (window.num_milliseconds() as f64 / 1000.0)).round() as u32
+        })
+        .collect()
+}
+
+/// Which of `width` columns, the last one ending at `end`, overlap a flow period.
+pub fn flow_columns(
+    flows: &[FlowPeriod],
+    end: DateTime<Utc>,
+    zoom: Zoom,
+    width: usize,
+) -> Vec<bool> {
+    (0..width)
+        .map(|i| {
+            let (from, to) = column_span(end, zoom, width, i);
+            flows
+                .iter()
+                .any(|f| f.overlap(from, to) > TimeDelta::zero())
+        })
+        .collect()
+}
+
+/// A `width`-column axis line with local-time labels at the quarter marks of the view.
+pub fn axis_labels<Tz: TimeZone>(end: DateTime<Utc>, zoom: Zoom, width: usize, tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let mut axis = vec![' '; width];
+    let start = end - zoom.span();
+    let span_ms = zoom.span().num_milliseconds();
+    for quarter in 0..=4 {
+        let col = width * quarter / 4;
+        let time = start + TimeDelta::milliseconds(span_ms * quarter as i64 / 4);
+        let label: Vec<char> = time
+            .with_timezone(tz)
+            .format(zoom.axis_format())
+            .to_string()
+            .chars()
+            .collect();
+        // Centre labels on their mark, keeping the first and last inside the line
+        let pos = col
+            .saturating_sub(label.len() / 2)
+            .min(width.saturating_sub(label.len()));
+        let free = axis
+            .iter()
+            .skip(pos.saturating_sub(1))
+            .take(label.len() + 2)
+            .all(|c| *c == ' ');
+        if pos + label.len() <= width && free {
+            axis[pos..pos + label.len()].copy_from_slice(&label);
+        }
+    }
+    axis.into_iter().collect()
+}
+
+/// Smallest value the waveform scales to, so slow typing (~540 LPM at full
+/// height) does not fill the chart.
+pub const WAVE_MIN_SCALE: u32 = 9;
+
+/// Rows of the symmetrical waveform for `values` (keystrokes per second, one
+/// column each), top row first. Bars grow up and down from the middle row,
+/// which shows a `─` where a column is empty.
+pub fn waveform(values: &[u32], height: usize) -> Vec<Vec<char>> {
+    let max_val = values
+        .iter()
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(WAVE_MIN_SCALE);
+    let mid = height as f32 / 2.0;
+    (0..height)
+        .map(|row| {
+            values
+                .iter()
+                .map(|&val| {
+                    let amplitude = (val as f32 / max_val as f32) * mid;
+                    let overlap_top = (row as f32).max(mid - amplitude);
+                    let overlap_bottom = ((row + 1) as f32).min(mid + amplitude);
+                    let overlap = (overlap_bottom - overlap_top).max(0.0);
+                    match wave_glyph(overlap, row as f32 + 0.5 < mid) {
+                        ' ' if row == height / 2 => '─',
+                        c => c,
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Block character for a cell the bar covers `overlap` (0 to 1) of. Cells in
+/// the top half fill from the bottom, those in the bottom half from the top.
+pub fn wave_glyph(overlap: f32, top_half: bool) -> char {
+    if overlap >= 0.99 {
+        '█'
+    } else if !top_half {
+        if overlap > 0.5 { '▀' } else { ' ' }
+    } else if overlap > 0.875 {
+        '▇'
+    } else if overlap > 0.75 {
+        '▆'
+    } else if overlap > 0.625 {
+        '▅'
+    } else if overlap > 0.5 {
+        '▄'
+    } else if overlap > 0.375 {
+        '▃'
+    } else if overlap > 0.25 {
+        '▂'
+    } else {
+        ' '
+    }
+}
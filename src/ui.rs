@@ -1,198 +1,260 @@
 use crate::App;
+use crate::commands::{Action, Palette, PaletteMode};
+use crate::goals;
+use crate::journal;
+use crate::markdown;
+use crate::state::{LayoutPreset, Screen};
+use crate::stats::{self, Report};
+use crate::timeline;
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    layout::{Constraint, Direction, Layout, Margin, Position, Rect},
+    style::Style,
     text::{Line, Span},
-    widgets::{Block, Borders, Gauge, List, ListItem, Paragraph},
+    widgets::{
+        BarChart, Block, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph, Sparkline,
+    },
 };
+use std::rc::Rc;
+
+/// Most lines an entry takes up in the journal panel.
+const LIST_LINES: usize = 3;
+
+/// What is drawn at a point of the screen, for mouse clicks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Alert,
+    /// A journal entry, as an index into `App::logs`.
+    Entry(usize),
+    /// Column `column` of an activity chart `width` columns wide.
+    Chart {
+        column: usize,
+        width: usize,
+    },
+    Other,
+}
 
-pub fn render(f: &mut Frame, app: &App) {
-    let chunks = Layout::default()
+/// The layout `app` is drawn with on a terminal of this size.
+pub fn layout_for(app: &App, area: Rect) -> LayoutPreset {
+    app.layout
+        .unwrap_or_else(|| LayoutPreset::for_size(area.width, area.height))
+}
+
+/// What is under the cell at `x`, `y` when `app` is drawn on `screen`.
+pub fn target_at(app: &App, screen: Rect, x: u16, y: u16) -> Target {
+    let position = Position::new(x, y);
+    if app.alert_active && alert_area(screen).contains(position) {
+        return Target::Alert;
+    }
+    if app.screen != Screen::Journal {
+        return Target::Other;
+    }
+    let preset = layout_for(app, screen);
+    let (activity, journal) = journal_screen_areas(preset, frame_areas(preset, screen)[1]);
+    let chart = activity_chunks(activity)[0];
+    if chart.contains(position) {
+        return Target::Chart {
+            column: (x - chart.x) as usize,
+            width: chart.width as usize,
+        };
+    }
+    let Some(journal) = journal else {
+        return Target::Other;
+    };
+    let list = journal_chunks(app, journal)[0];
+    let inner = list.inner(Margin::new(1, 1));
+    if !inner.contains(position) {
+        return Target::Other;
+    }
+    let visible = app.visible_logs();
+    let heights: Vec<usize> = log_items(app, &visible, list.width)
+        .iter()
+        .map(ListItem::height)
+        .collect();
+    let offset = list_offset(&heights, selected_row(app, &visible), inner.height as usize);
+    let mut row = (y - inner.y) as usize;
+    for (i, &height) in heights.iter().enumerate().skip(offset) {
+        if row < height {
+            return Target::Entry(visible[i]);
+        }
+        row -= height;
+    }
+    Target::Other
+}
+
+/// Header, body and footer of the screen.
+fn frame_areas(preset: LayoutPreset, area: Rect) -> Rc<[Rect]> {
+    // Compact header and footer drop their borders
+    let (header, footer) = match preset {
+        LayoutPreset::Standard | LayoutPreset::Wide => (3, 3),
+        LayoutPreset::Compact => (1, 1),
+        LayoutPreset::Minimal => (0, 1),
+    };
+    Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3),  // Header
-            Constraint::Length(10), // Activity Stream
-            Constraint::Min(0),     // Journal + Input
-            Constraint::Length(3),  // Footer
+            Constraint::Length(header),
+            Constraint::Min(0), // Body
+            Constraint::Length(footer),
         ])
-        .split(f.size());
+        .split(area)
+}
 
-    render_header(f, chunks[0]);
-    render_activity_stream(f, app, chunks[1]);
-    render_journal_section(f, app, chunks[2]);
-    render_footer(f, chunks[3]);
+/// The activity panel and, unless `preset` hides it, the journal panel.
+fn journal_screen_areas(preset: LayoutPreset, area: Rect) -> (Rect, Option<Rect>) {
+    let direction = match preset {
+        LayoutPreset::Minimal => return (area, None),
+        LayoutPreset::Wide => Direction::Horizontal,
+        _ => Direction::Vertical,
+    };
+    let constraints = match preset {
+        LayoutPreset::Compact => [Constraint::Length(7), Constraint::Min(0)],
+        LayoutPreset::Wide => [Constraint::Percentage(45), Constraint::Percentage(55)],
+        _ => [Constraint::Length(10), Constraint::Min(0)],
+    };
+    let chunks = Layout::default()
+        .direction(direction)
+        .constraints(constraints)
+        .split(area);
+    if preset == LayoutPreset::Wide {
+        // Journal on the left, the chart gets the full height on the right
+        (chunks[1], Some(chunks[0]))
+    } else {
+        (chunks[0], Some(chunks[1]))
+    }
+}
 
+pub fn render(f: &mut Frame, app: &App) {
+    let preset = layout_for(app, f.size());
+    let chunks = frame_areas(preset, f.size());
+    let body = chunks[1];
+
+    render_header(f, app, chunks[0]);
+    match app.screen {
+        Screen::Journal => render_journal_screen(f, app, preset, body),
+        Screen::Stats => {
+            if let Some(report) = &app.stats {
+                render_stats(f, app, report, body);
+            }
+        }
+        Screen::Editor => f.render_widget(app.textarea.widget(), body),
+        Screen::Detail => render_detail(f, app, body),
+    }
+    render_footer(f, app, chunks[2]);
+
+    if let Some(selected) = app.picker {
+        render_picker(f, app, selected);
+    }
+    if app.help {
+        render_help(f, app);
+    }
+    if let Some(palette) = &app.palette {
+        render_palette(f, app, palette);
+    }
     if app.alert_active {
-        render_alert(f);
+        render_alert(f, app);
     }
 }
 
-fn render_header(f: &mut Frame, area: Rect) {
+fn render_header(f: &mut Frame, app: &App, area: Rect) {
     let text = ">>> CAPTAIN'S LOG <<<";
-    let paragraph = Paragraph::new(text)
-        .style(
-            Style::default()
-                .fg(Color::Green)
-                .add_modifier(Modifier::BOLD),
-        )
-        .alignment(ratatui::layout::Alignment::Center)
-        .block(Block::default().borders(Borders::ALL));
+    let mut paragraph = Paragraph::new(text)
+        .style(app.theme.header)
+        .alignment(ratatui::layout::Alignment::Center);
+    if area.height >= 3 {
+        paragraph = paragraph.block(Block::default().borders(Borders::ALL));
+    }
     f.render_widget(paragraph, area);
 }
 
+/// Activity chart and journal, arranged for `preset`.
+fn render_journal_screen(f: &mut Frame, app: &App, preset: LayoutPreset, area: Rect) {
+    let (activity, journal) = journal_screen_areas(preset, area);
+    render_activity_stream(f, app, activity);
+    if let Some(journal) = journal {
+        render_journal_section(f, app, journal);
+    }
+}
+
+/// Chart, time axis and gauges inside the activity panel.
+fn activity_chunks(area: Rect) -> Rc<[Rect]> {
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),    // Chart
+            Constraint::Length(1), // Time axis
+            Constraint::Length(2), // Gauge + Stats (Stacked)
+        ])
+        .split(area.inner(Margin::new(1, 1)))
+}
+
 fn render_activity_stream(f: &mut Frame, app: &App, area: Rect) {
+    let title = if app.timeline_offset.is_zero() {
+        format!("KEYBOARD ACTIVITY [{}]", app.timeline_zoom.label())
+    } else {
+        format!(
+            "KEYBOARD ACTIVITY [{}] -{}",
+            app.timeline_zoom.label(),
+            stats::format_minutes(app.timeline_offset.num_minutes() as u32)
+        )
+    };
     let block = Block::default()
-        .title("KEYBOARD ACTIVITY")
+        .title(title)
         .borders(Borders::ALL)
-        .border_style(
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        ); // "Glow" with bold
+        .border_style(app.theme.border); // "Glow" with bold
 
-    let inner_area = block.inner(area);
     f.render_widget(block, area);
 
     // Split into Chart area and Bottom Info Bar (Gauge + Stats)
-    let activity_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Min(1),    // Chart
-            Constraint::Length(2), // Gauge + Stats (Stacked)
-        ])
-        .split(inner_area);
+    let activity_chunks = activity_chunks(area);
 
     // --- Waveform Visualization (Bars) ---
     let chart_area = activity_chunks[0];
     let width = chart_area.width as usize;
     let height = chart_area.height as usize;
 
-    let data_len = app.activity_stream.len();
-    let mut data = Vec::with_capacity(width);
-
-    if data_len < width {
-        let padding = width - data_len;
-        let left_pad = padding / 2;
-        let right_pad = padding - left_pad;
-
-        data.extend([0].iter().cycle().take(left_pad));
-
-        data.extend(app.activity_stream.iter().cloned());
-
-        data.extend([0].iter().cycle().take(right_pad));
-    } else {
-        data.extend(app.activity_stream.iter().skip(data_len - width).cloned());
-    }
-
-    // Create smoothed data (rolling sum over 1 second / 4 ticks)
-    // This gives us a range of roughly 0-10+ for WPM 0-120+
-    let mut smoothed_data = Vec::with_capacity(width);
-    for i in 0..width {
-        let mut sum = 0;
-        for j in 0..4 {
-            if i >= j {
-                sum += data[i - j];
-            }
-        }
-        smoothed_data.push(sum);
-    }
-
-    // Determine max value for scaling
-    // Scale to ~500 LPM (8.33 chars/sec).
-    // smoothed_data is chars/sec.
-    let max_val = smoothed_data.iter().max().cloned().unwrap_or(1).max(9);
-
-    let mut lines = Vec::with_capacity(height);
-    let mid = height as f32 / 2.0;
-
-    // Render from top row to bottom row
-    for row in 0..height {
-        let mut spans = Vec::with_capacity(width);
-        let row_top = row as f32;
-        let row_bottom = (row + 1) as f32;
-
-        for &val in &smoothed_data {
-            // Color logic based on intensity (smoothed chars/sec)
-            // 1-500 LPM range requested.
-            // 100 LPM ~= 1.6 cps.
-            // 300 LPM ~= 5 cps.
-            // 500 LPM ~= 8.3 cps.
-
-            let color = match val {
-                0 => Color::DarkGray,   // Idle
-                1..=2 => Color::Blue,   // < 120 LPM (Slow)
-                3..=5 => Color::Green,  // 120-300 LPM (Medium)
-                6..=8 => Color::Yellow, // 300-480 LPM (Fast)
-                _ => Color::Red,        // > 480 LPM (Very Fast)
-            };
-
-            // Waveform logic
-            let amplitude = (val as f32 / max_val as f32) * mid;
-
-            let wave_top = mid - amplitude;
-            let wave_bottom = mid + amplitude;
-
-            let overlap_top = row_top.max(wave_top);
-            let overlap_bottom = row_bottom.min(wave_bottom);
-            let overlap = (overlap_bottom - overlap_top).max(0.0);
-
-            let mut c = ' ';
-
-            if overlap > 0.0 {
-                if row as f32 + 0.5 < mid {
-                    // Top half
-                    c = if overlap > 0.875 {
-                        '▇'
-                    } else if overlap > 0.75 {
-                        '▆'
-                    } else if overlap > 0.625 {
-                        '▅'
-                    } else if overlap > 0.5 {
-                        '▄'
-                    } else if overlap > 0.375 {
-                        '▃'
-                    } else if overlap > 0.25 {
-                        '▂'
-                    } else {
-                        ' '
+    // Downsample the visible window into one bucket per column, in chars/sec
+    let end = app.timeline_end();
+    let smoothed_data = timeline::buckets(&app.history_view(), end, app.timeline_zoom, width);
+
+    // Color each bar by intensity: chars/sec against the theme's LPM thresholds
+    let lines: Vec<Line> = timeline::waveform(&smoothed_data, height)
+        .into_iter()
+        .map(|row| {
+            let spans: Vec<Span> = row
+                .into_iter()
+                .zip(&smoothed_data)
+                .map(|(c, &val)| {
+                    let style = match c {
+                        ' ' => Style::default(),
+                        '─' => app.theme.muted, // Centre line
+                        _ => app.theme.intensity(val * 60),
                     };
-                } else {
-                    // Bottom half
-                    if overlap > 0.5 {
-                        c = '▀';
-                    } else {
-                        c = ' ';
-                    }
-                }
-                if overlap >= 0.99 {
-                    c = '█';
-                }
-            }
-
-            // Center line decoration
-            if c == ' ' && row == height / 2 {
-                c = '─';
-                spans.push(Span::styled(
-                    c.to_string(),
-                    Style::default().fg(Color::DarkGray),
-                ));
-            } else {
-                // Use the calculated color for the bar
-                let style = if c == ' ' {
-                    Style::default()
-                } else {
-                    Style::default().fg(color).add_modifier(Modifier::BOLD)
-                };
-                spans.push(Span::styled(c.to_string(), style));
-            }
-        }
-        lines.push(Line::from(spans));
-    }
+                    Span::styled(c.to_string(), style)
+                })
+                .collect();
+            Line::from(spans)
+        })
+        .collect();
 
     let paragraph = Paragraph::new(lines).block(Block::default().borders(Borders::NONE));
     f.render_widget(paragraph, chart_area);
 
+    // --- Time Axis ---
+    // Flow periods are drawn as a bar along the axis
+    let axis = timeline::axis_labels(end, app.timeline_zoom, width, &chrono::Local);
+    let flow = timeline::flow_columns(&app.all_flows(), end, app.timeline_zoom, width);
+    let axis: Vec<Span> = axis
+        .chars()
+        .zip(flow)
+        .map(|(c, in_flow)| match (c, in_flow) {
+            (' ', true) => Span::styled("━", app.theme.flow),
+            (c, true) => Span::styled(c.to_string(), app.theme.flow),
+            (c, false) => Span::styled(c.to_string(), app.theme.muted),
+        })
+        .collect();
+    f.render_widget(Paragraph::new(Line::from(axis)), activity_chunks[1]);
+
     // --- Bottom Info Bar (Gauge + Stats) ---
     let info_chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -200,93 +262,540 @@ fn render_activity_stream(f: &mut Frame, app: &App, area: Rect) {
             Constraint::Length(1), // Gauge
             Constraint::Length(1), // Stats
         ])
-        .split(activity_chunks[1]);
+        .split(activity_chunks[2]);
 
-    let label = format!("CURRENT FOCUS LEVEL: {:.0}% (LOCKED IN)", app.focus_level);
+    let label = format!(
+        "CURRENT FOCUS LEVEL: {:.0}% ({})",
+        app.focus_level,
+        app.config.focus.describe(app.focus_level)
+    );
     let gauge = Gauge::default()
-        .gauge_style(
-            Style::default()
-                .fg(Color::Blue)
-                .bg(Color::DarkGray)
-                .add_modifier(Modifier::BOLD),
-        )
+        .gauge_style(app.theme.focus_gauge)
         .ratio(app.focus_level / 100.0)
         .label(label);
-    f.render_widget(gauge, info_chunks[0]);
 
-    let stats_text = format!("WPM: {:03} | LPM: {:04}", app.wpm, app.lpm);
+    let gauge_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(info_chunks[0]);
+    f.render_widget(gauge, gauge_chunks[0]);
+    render_goals_gauge(f, app, gauge_chunks[1]);
+
+    let mut stats_text = format!("WPM: {:03} | LPM: {:04}", app.wpm, app.lpm);
+    if let Some(flow) = app.flow_detector.current() {
+        stats_text.push_str(&format!(
+            " | IN FLOW: {}",
+            stats::format_minutes(flow.duration().num_minutes() as u32)
+        ));
+    }
     let stats = Paragraph::new(stats_text)
-        .style(
-            Style::default()
-                .fg(Color::White)
-                .add_modifier(Modifier::BOLD),
-        )
+        .style(app.theme.text)
         .alignment(ratatui::layout::Alignment::Center); // Centered stats
     f.render_widget(stats, info_chunks[1]);
 }
 
-fn render_journal_section(f: &mut Frame, app: &App, area: Rect) {
-    let chunks = Layout::default()
+fn render_goals_gauge(f: &mut Frame, app: &App, area: Rect) {
+    let goals = &app.config.goals;
+    let status = goals.evaluate(&app.progress);
+    let met = status.iter().filter(|g| g.is_met()).count();
+    let records = app.goal_records();
+    let mut label = format!(
+        "DAILY GOALS: {}/{} | STREAK {} (BEST {})",
+        met,
+        status.len(),
+        goals::current_streak(&records, app.today),
+        goals::best_streak(&records)
+    );
+    if let Some(pomodoro) = app.pomodoro {
+        let remaining = pomodoro.remaining(chrono::Utc::now()).num_seconds();
+        label.push_str(&format!(
+            " | POMODORO {:02}:{:02}",
+            remaining / 60,
+            remaining % 60
+        ));
+    }
+    let gauge = Gauge::default()
+        .gauge_style(app.theme.goals_gauge)
+        .ratio(goals.ratio(&app.progress))
+        .label(label);
+    f.render_widget(gauge, area);
+}
+
+/// Log list and input box of the journal panel.
+fn journal_chunks(app: &App, area: Rect) -> Rc<[Rect]> {
+    // The input grows with the entry, but never past half the panel
+    let rows = (app.textarea.lines().len() as u16)
+        .max(app.input_rows)
+        .min((area.height / 2).saturating_sub(2))
+        .max(1);
+    Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Min(1),    // Logs
-            Constraint::Length(3), // Input
+            Constraint::Min(1),           // Logs
+            Constraint::Length(rows + 2), // Input
         ])
-        .split(area);
+        .split(area)
+}
 
-    // Logs
-    let logs: Vec<ListItem> = app
-        .logs
+/// The `visible` entries, each shortened to a few lines of rendered Markdown
+/// for a list `width` columns wide.
+fn log_items(app: &App, visible: &[usize], width: u16) -> Vec<ListItem<'static>> {
+    let text_width = width.saturating_sub(2 + 11);
+    visible
         .iter()
-        .map(|log| {
+        .map(|&i| {
+            let log = &app.logs[i];
             let local_time: chrono::DateTime<chrono::Local> = chrono::DateTime::from(log.timestamp);
-            let content = format!("[{}] {}", local_time.format("%H:%M:%S"), log.content);
-            ListItem::new(Line::from(Span::raw(content)))
+            let mut lines = markdown::condensed(&log.content, text_width, LIST_LINES, &app.theme);
+            for (i, line) in lines.iter_mut().enumerate() {
+                let prefix = match i {
+                    0 => format!("[{}] ", local_time.format("%H:%M:%S")),
+                    _ => " ".repeat(11),
+                };
+                line.spans.insert(0, Span::raw(prefix));
+            }
+            ListItem::new(lines)
         })
-        .collect();
+        .collect()
+}
 
-    let logs_list = List::new(logs).block(
-        Block::default()
-            .title("JOURNAL LOGS (PERSISTENT)")
-            .borders(Borders::ALL),
+/// Row of the selected entry among the `visible` ones.
+fn selected_row(app: &App, visible: &[usize]) -> Option<usize> {
+    app.selected
+        .and_then(|i| visible.iter().position(|&v| v == i))
+}
+
+/// First row of a list of items `heights` tall that keeps `selected` in view,
+/// scrolling no further than needed.
+fn list_offset(heights: &[usize], selected: Option<usize>, height: usize) -> usize {
+    let Some(selected) = selected else {
+        return 0;
+    };
+    let mut offset = 0;
+    while offset < selected && heights[offset..=selected].iter().sum::<usize>() > height {
+        offset += 1;
+    }
+    offset
+}
+
+fn render_journal_section(f: &mut Frame, app: &App, area: Rect) {
+    let chunks = journal_chunks(app, area);
+    let visible = app.visible_logs();
+    let logs = log_items(app, &visible, chunks[0].width);
+    // Scrolled here rather than by the list, so clicks can find the entries
+    let heights: Vec<usize> = logs.iter().map(ListItem::height).collect();
+    let selected = selected_row(app, &visible);
+    let offset = list_offset(
+        &heights,
+        selected,
+        chunks[0].height.saturating_sub(2) as usize,
     );
-    f.render_widget(logs_list, chunks[0]);
+
+    let mut title = vec![Span::raw("JOURNAL LOGS (PERSISTENT)")];
+    if let Some(search) = &app.search {
+        let mut label = format!(" SEARCH: {} ({}) ", search, visible.len());
+        let hint = app.key_hints(&[(Action::ClearSelection, "CLEAR")]);
+        if !hint.is_empty() {
+            label.push_str(&format!("| {} ", hint));
+        }
+        title.push(Span::styled(label, app.theme.accent));
+    }
+    if let Some(warning) = &app.journal_warning {
+        title.push(Span::styled(format!(" ⚠ {} ", warning), app.theme.warning));
+    }
+    let logs_list = List::new(logs)
+        .block(
+            Block::default()
+                .title(Line::from(title))
+                .borders(Borders::ALL),
+        )
+        .highlight_style(app.theme.selection);
+    let mut state = ListState::default()
+        .with_selected(selected)
+        .with_offset(offset);
+    f.render_stateful_widget(logs_list, chunks[0], &mut state);
 
     // Input
     f.render_widget(app.textarea.widget(), chunks[1]);
 }
 
-fn render_footer(f: &mut Frame, area: Rect) {
-    let block = Block::default().borders(Borders::TOP);
+fn render_detail(f: &mut Frame, app: &App, area: Rect) {
+    let Some(entry) = app.selected_entry() else {
+        return;
+    };
+    let local_time: chrono::DateTime<chrono::Local> = chrono::DateTime::from(entry.timestamp);
+    let mut title = local_time.format("%A, %-d %B %Y %H:%M:%S").to_string();
+    let tags = journal::tags(&entry.content);
+    if !tags.is_empty() {
+        title = format!("{} | #{}", title, tags.join(" #"));
+    }
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(app.theme.border);
     let inner = block.inner(area);
-    f.render_widget(block, area);
+    let lines = markdown::render(&entry.content, inner.width, &app.theme);
+    let scroll = app
+        .detail_scroll
+        .min((lines.len() as u16).saturating_sub(inner.height));
+    f.render_widget(Paragraph::new(lines).block(block).scroll((scroll, 0)), area);
+}
+
+fn render_stats(f: &mut Frame, app: &App, report: &Report, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(10),     // Summary
+            Constraint::Percentage(50), // Active minutes per day
+            Constraint::Min(0),         // Time of day + entries
+        ])
+        .split(area);
+
+    // --- Summary with comparison against the previous period ---
+    let s = &report.summary;
+    let p = &report.previous;
+    let row = |name: &str, value: String, current: u32, previous: u32| {
+        Line::from(vec![
+            Span::styled(format!("{:<18}", name), app.theme.label),
+            Span::styled(format!("{:>10}", value), app.theme.text),
+            Span::styled(
+                format!("{:>8}", stats::format_change(current, previous)),
+                app.theme.change,
+            ),
+        ])
+    };
+    let mut summary = vec![
+        row(
+            "Active time",
+            stats::format_minutes(s.active_minutes),
+            s.active_minutes,
+            p.active_minutes,
+        ),
+        row("Average WPM", s.avg_wpm.to_string(), s.avg_wpm, p.avg_wpm),
+        row("Peak WPM", s.peak_wpm.to_string(), s.peak_wpm, p.peak_wpm),
+        row(
+            "Longest streak",
+            stats::format_minutes(s.longest_streak_minutes),
+            s.longest_streak_minutes,
+            p.longest_streak_minutes,
+        ),
+        row(
+            "Flow time",
+            stats::format_minutes(s.flow_minutes),
+            s.flow_minutes,
+            p.flow_minutes,
+        ),
+        row(
+            "Journal entries",
+            s.entries.to_string(),
+            s.entries,
+            p.entries,
+        ),
+        row(
+            "Keystrokes",
+            s.keystrokes.to_string(),
+            s.keystrokes,
+            p.keystrokes,
+        ),
+    ];
+    // Met/missed daily goals over the last two weeks, oldest first
+    let mut history = vec![Span::styled(
+        format!("{:<18}", "Goals (14 days)"),
+        app.theme.label,
+    )];
+    let records = app.goal_records();
+    for days_ago in (0..14).rev() {
+        let date = app.today - chrono::Days::new(days_ago);
+        let met = records.iter().any(|r| r.date == date && r.met);
+        history.push(if met {
+            Span::styled("■", app.theme.goal_met)
+        } else {
+            Span::styled("□", app.theme.muted)
+        });
+    }
+    summary.push(Line::from(history));
+
+    let title = format!(
+        "STATS: {} {} .. {} (VS PREVIOUS {})",
+        report.period.label(),
+        report.start,
+        report.end,
+        report.period.label()
+    );
+    let summary = Paragraph::new(summary).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(app.theme.border),
+    );
+    f.render_widget(summary, chunks[0]);
+
+    // --- Active minutes per day ---
+    let day_label = |date: chrono::NaiveDate| match report.period {
+        stats::Period::Month => date.format("%d").to_string(),
+        _ => date.format("%a").to_string(),
+    };
+    let labels: Vec<String> = report.days.iter().map(|d| day_label(d.date)).collect();
+    let active: Vec<(&str, u64)> = labels
+        .iter()
+        .zip(&report.days)
+        .map(|(l, d)| (l.as_str(), d.summary.active_minutes as u64))
+        .collect();
+    let active_bar_width = bar_width(chunks[1].width, report.days.len());
+    let active_chart = BarChart::default()
+        .block(
+            Block::default()
+                .title("ACTIVE MINUTES PER DAY")
+                .borders(Borders::ALL),
+        )
+        .data(&active)
+        .bar_width(active_bar_width)
+        .bar_gap(1)
+        .bar_style(app.theme.bars)
+        .value_style(app.theme.bar_values);
+    f.render_widget(active_chart, chunks[1]);
+
+    // --- Time-of-day distribution and entries per day ---
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(chunks[2]);
+
+    let hourly: Vec<u64> = report.hourly.iter().map(|&c| c as u64).collect();
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .title("TIME OF DAY (00:00 - 23:00)")
+                .borders(Borders::ALL),
+        )
+        .data(&hourly)
+        .style(app.theme.hours);
+    f.render_widget(sparkline, bottom[0]);
 
-    let left_text = "CAPTAIN'S LOG | V1.0.0 | F10: EXIT";
+    let entries: Vec<(&str, u64)> = labels
+        .iter()
+        .zip(&report.days)
+        .map(|(l, d)| (l.as_str(), d.summary.entries as u64))
+        .collect();
+    let entries_chart = BarChart::default()
+        .block(
+            Block::default()
+                .title("ENTRIES PER DAY")
+                .borders(Borders::ALL),
+        )
+        .data(&entries)
+        .bar_width(bar_width(bottom[1].width, report.days.len()))
+        .bar_gap(1)
+        .bar_style(app.theme.entry_bars)
+        .value_style(app.theme.entry_values);
+    f.render_widget(entries_chart, bottom[1]);
+}
+
+/// Widest bar that fits `bars` bars (with 1-column gaps) inside a bordered block.
+fn bar_width(width: u16, bars: usize) -> u16 {
+    let bars = bars.max(1) as u16;
+    (width.saturating_sub(2) / bars).saturating_sub(1).max(1)
+}
+
+fn render_footer(f: &mut Frame, app: &App, area: Rect) {
+    let inner = if area.height >= 2 {
+        let block = Block::default().borders(Borders::TOP);
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        inner
+    } else {
+        area
+    };
+
+    let hints: &[(Action, &str)] = match app.screen {
+        Screen::Journal => &[
+            (Action::Palette, "COMMANDS"),
+            (Action::Help, "HELP"),
+            (Action::OpenEntry, "OPEN"),
+            (Action::ToggleStats, "STATS"),
+            (Action::TogglePomodoro, "POMODORO"),
+            (Action::OpenEditor, "EDITOR"),
+            (Action::InsertTemplate, "TEMPLATE"),
+            (Action::CycleLayout, "LAYOUT"),
+            (Action::Quit, "EXIT"),
+        ],
+        Screen::Stats => &[
+            (Action::CycleStatsPeriod, "PERIOD"),
+            (Action::ShowJournal, "BACK"),
+            (Action::Help, "HELP"),
+            (Action::Quit, "EXIT"),
+        ],
+        Screen::Editor => &[
+            (Action::Submit, "SAVE"),
+            (Action::ShowJournal, "BACK"),
+            (Action::Help, "HELP"),
+            (Action::Quit, "EXIT"),
+        ],
+        Screen::Detail => &[
+            (Action::ScrollDown, "SCROLL"),
+            (Action::PageDown, "PAGE"),
+            (Action::ShowJournal, "BACK"),
+            (Action::Help, "HELP"),
+            (Action::Quit, "EXIT"),
+        ],
+    };
+    let left_text = format!("CAPTAIN'S LOG | V1.0.0 | {}", app.key_hints(hints));
     let right_text = "github: yaga-simha";
 
-    let left_p = Paragraph::new(left_text).style(Style::default().fg(Color::Gray));
-    let right_p = Paragraph::new(right_text)
-        .style(Style::default().fg(Color::Gray))
-        .alignment(ratatui::layout::Alignment::Right);
+    let (left_text, left_style) = match &app.error {
+        Some(error) => {
+            let hint = app.key_hints(&[(Action::RetrySaving, "RETRY")]);
+            if app.unsaved.is_empty() || hint.is_empty() {
+                (format!("⚠ {}", error), app.theme.error)
+            } else {
+                (format!("⚠ {} | {}", error, hint), app.theme.error)
+            }
+        }
+        None => match &app.notice {
+            Some(notice) => (notice.clone(), app.theme.notice),
+            None => (left_text, app.theme.label),
+        },
+    };
+    // The link makes way for the hints on narrow terminals
+    let fits = left_text.chars().count() + right_text.len() < inner.width as usize;
+    f.render_widget(Paragraph::new(left_text).style(left_style), inner);
+    if fits {
+        let right_p = Paragraph::new(right_text)
+            .style(app.theme.label)
+            .alignment(ratatui::layout::Alignment::Right);
+        f.render_widget(right_p, inner);
+    }
+}
+
+fn render_picker(f: &mut Frame, app: &App, selected: usize) {
+    let area = centered_rect(50, 40, f.size());
+    let items: Vec<ListItem> = app
+        .templates
+        .iter()
+        .map(|t| match t.at {
+            Some(at) => ListItem::new(format!("{} ({})", t.name, at.format("%H:%M"))),
+            None => ListItem::new(t.name.clone()),
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title("TEMPLATES | ENTER: INSERT | ESC: CLOSE")
+                .borders(Borders::ALL)
+                .border_style(app.theme.accent),
+        )
+        .highlight_style(app.theme.highlight);
+    let mut state = ListState::default().with_selected(Some(selected));
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn render_palette(f: &mut Frame, app: &App, palette: &Palette) {
+    let area = centered_rect(60, 50, f.size());
+    let title = match palette.mode {
+        PaletteMode::Commands => "COMMANDS | ↑/↓: SELECT | ENTER: RUN | ESC: CLOSE",
+        PaletteMode::Search => "SEARCH JOURNAL | ENTER: FILTER | ESC: CLOSE",
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(app.theme.accent);
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(0)])
+        .split(inner);
+    let prompt = Line::from(vec![
+        Span::styled("> ", app.theme.accent),
+        Span::raw(palette.query.as_str()),
+        Span::styled("▏", app.theme.accent),
+    ]);
+    f.render_widget(Paragraph::new(prompt), chunks[0]);
+    if palette.mode == PaletteMode::Search {
+        return;
+    }
 
-    f.render_widget(left_p, inner);
-    f.render_widget(right_p, inner);
+    let width = chunks[1].width as usize;
+    let layers = app.key_layers();
+    let items: Vec<ListItem> = palette
+        .matches()
+        .iter()
+        .map(|c| {
+            let key = app
+                .keymap
+                .key_for(layers, c.action)
+                .map(|chord| chord.to_string())
+                .unwrap_or_default();
+            let gap = width.saturating_sub(c.name.chars().count() + key.chars().count() + 1);
+            ListItem::new(Line::from(vec![
+                Span::raw(c.name),
+                Span::raw(" ".repeat(gap)),
+                Span::styled(key, app.theme.muted),
+            ]))
+        })
+        .collect();
+    let list = List::new(items).highlight_style(app.theme.highlight);
+    let mut state = ListState::default().with_selected(Some(palette.selected));
+    f.render_stateful_widget(list, chunks[1], &mut state);
 }
 
-fn render_alert(f: &mut Frame) {
-    let area = centered_rect(60, 20, f.size());
-    let block = Block::default().title("ALERT").borders(Borders::ALL).style(
-        Style::default()
-            .bg(Color::Red)
-            .fg(Color::White)
-            .add_modifier(Modifier::BOLD),
+fn render_help(f: &mut Frame, app: &App) {
+    let bindings = app.keymap.help(app.key_layers());
+    let screen = f.size();
+    let width = centered_rect(60, 100, screen).width;
+    let area = popup_rect(width, bindings.len() as u16 + 2, screen);
+    let lines: Vec<Line> = bindings
+        .into_iter()
+        .map(|(chord, action)| {
+            Line::from(vec![
+                Span::styled(format!("{:>12}  ", chord.to_string()), app.theme.border),
+                Span::raw(action.command().name),
+            ])
+        })
+        .collect();
+    let help = Paragraph::new(lines).block(
+        Block::default()
+            .title("KEY BINDINGS | ANY KEY: CLOSE")
+            .borders(Borders::ALL)
+            .border_style(app.theme.border),
     );
+    f.render_widget(Clear, area);
+    f.render_widget(help, area);
+}
+
+/// Sized to its two lines, so it stays readable in small splits.
+fn alert_area(screen: Rect) -> Rect {
+    popup_rect((screen.width * 3 / 5).clamp(24, 60), 4, screen)
+}
+
+fn render_alert(f: &mut Frame, app: &App) {
+    let area = alert_area(f.size());
+    let block = Block::default()
+        .title("ALERT")
+        .borders(Borders::ALL)
+        .style(app.theme.alert);
     let paragraph = Paragraph::new("ACTIVITY LOW!\nSTAY FOCUSED!")
         .block(block)
         .alignment(ratatui::layout::Alignment::Center);
+    f.render_widget(Clear, area);
     f.render_widget(paragraph, area);
 }
 
+/// A `width` by `height` rectangle centred in `r`, shrunk to fit.
+fn popup_rect(width: u16, height: u16, r: Rect) -> Rect {
+    let width = width.min(r.width);
+    let height = height.min(r.height);
+    Rect::new(
+        r.x + (r.width - width) / 2,
+        r.y + (r.height - height) / 2,
+        width,
+        height,
+    )
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
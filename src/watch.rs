@@ -0,0 +1,49 @@
+use crate::journal;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::mpsc::Sender;
+
+/// Sent when the journal changed on disk, possibly by another process.
+pub struct JournalChanged;
+
+/// Watches the journal at `location` (a directory or a database file) and
+/// sends [`JournalChanged`] whenever something in it is created, modified or
+/// removed. Watching stops when the returned watcher is dropped.
+pub fn watch_journal(
+    location: &Path,
+    tx: Sender<JournalChanged>,
+) -> notify::Result<RecommendedWatcher> {
+    let targets = if !location.is_dir() && location.extension().is_some() {
+        // A database changes in place, or through its write-ahead log
+        let wal = journal::with_suffix(location, "-wal");
+        let mut targets = vec![location.to_path_buf()];
+        if wal.exists() {
+            targets.push(wal);
+        }
+        targets
+    } else {
+        std::fs::create_dir_all(location)?;
+        vec![location.to_path_buf()]
+    };
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        if let Ok(event) = event
+            && matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            )
+            && !event.paths.iter().all(|p| is_scratch(p))
+        {
+            let _ = tx.send(JournalChanged);
+        }
+    })?;
+    for target in &targets {
+        watcher.watch(target, RecursiveMode::NonRecursive)?;
+    }
+    Ok(watcher)
+}
+
+/// Lock and temporary files, which change on every save without adding entries.
+fn is_scratch(path: &Path) -> bool {
+    let name = path.file_name().and_then(|s| s.to_str()).unwrap_or("");
+    name == ".lock" || name.ends_with(".tmp")
+}
@@ -0,0 +1,60 @@
+use captains_log::activity::{ActivityLog, ActivitySample, minute_of};
+use chrono::{TimeZone, Utc};
+use tempfile::tempdir;
+
+#[test]
+fn test_minute_of_truncates_seconds() {
+    let time = Utc.with_ymd_and_hms(2025, 11, 21, 20, 38, 34).unwrap();
+    assert_eq!(
+        minute_of(time),
+        Utc.with_ymd_and_hms(2025, 11, 21, 20, 38, 0).unwrap()
+    );
+}
+
+#[test]
+fn test_activity_append_and_load() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let log = ActivityLog {
+        path: dir.path().join("activity"),
+    };
+
+    let later = ActivitySample {
+        minute: Utc.with_ymd_and_hms(2025, 11, 22, 9, 1, 0).unwrap(),
+        keystrokes: 120,
+    };
+    let earlier = ActivitySample {
+        minute: Utc.with_ymd_and_hms(2025, 11, 21, 9, 0, 0).unwrap(),
+        keystrokes: 40,
+    };
+    log.append(&later)?;
+    log.append(&earlier)?;
+
+    // One file per day, loaded back in chronological order
+    assert_eq!(std::fs::read_dir(&log.path)?.count(), 2);
+    assert_eq!(log.load_all()?, vec![earlier, later]);
+    Ok(())
+}
+
+#[test]
+fn test_bad_lines_are_skipped_and_reported() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let log = ActivityLog {
+        path: dir.path().join("activity"),
+    };
+    let sample = ActivitySample {
+        minute: Utc.with_ymd_and_hms(2025, 11, 21, 9, 0, 0).unwrap(),
+        keystrokes: 40,
+    };
+    log.append(&sample)?;
+    let file = log.path.join("2025-11-21.jsonl");
+    let mut content = std::fs::read_to_string(&file)?;
+    content.push_str("{\"minute\": \"not a time\"}\n");
+    std::fs::write(&file, content)?;
+
+    let (samples, problems) = log.load_checked()?;
+    assert_eq!(samples, vec![sample]);
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].line, Some(2));
+    assert_eq!(log.load_all()?, vec![sample]);
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use captains_log::commands::{self, Action, COMMANDS, KEY_ACTIONS};
+
+#[test]
+fn test_fuzzy_score() {
+    assert!(commands::fuzzy_score("xyz", "Search journal").is_none());
+    assert!(commands::fuzzy_score("SJ", "Search journal").is_some());
+    // Consecutive letters and word starts beat scattered ones
+    let prefix = commands::fuzzy_score("sea", "Search journal").unwrap();
+    let scattered = commands::fuzzy_score("sea", "Show key bindings area").unwrap();
+    assert!(prefix > scattered);
+    assert_eq!(commands::fuzzy_score("", "Quit"), Some(0));
+}
+
+#[test]
+fn test_matching_ranks_best_first() {
+    assert_eq!(commands::matching("").len(), COMMANDS.len());
+    assert_eq!(commands::matching("quit")[0].action, Action::Quit);
+    assert_eq!(commands::matching("pomo")[0].action, Action::TogglePomodoro);
+    let exports: Vec<Action> = commands::matching("export")
+        .iter()
+        .map(|c| c.action)
+        .collect();
+    assert_eq!(exports.len(), 4);
+    assert!(commands::matching("exp html")[0].name.ends_with("HTML"));
+    assert!(commands::matching("zzz").is_empty());
+}
+
+#[test]
+fn test_action_ids_round_trip() {
+    for command in COMMANDS.iter().chain(KEY_ACTIONS) {
+        assert_eq!(Action::from_id(command.id), Some(command.action));
+        assert_eq!(command.action.command(), command);
+    }
+    assert_eq!(Action::from_id("launch_missiles"), None);
+}
@@ -0,0 +1,254 @@
+use captains_log::crypto::{self, EncryptedStore};
+use captains_log::journal::{Backend, Journal, JournalConfig, JournalEntry, JournalStore};
+use captains_log::sqlite_journal::SqliteJournal;
+use chrono::{TimeDelta, TimeZone, Utc};
+use tempfile::tempdir;
+
+fn entry(content: &str) -> JournalEntry {
+    JournalEntry {
+        timestamp: Utc.with_ymd_and_hms(2025, 5, 1, 9, 0, 0).unwrap(),
+        content: content.to_string(),
+    }
+}
+
+fn read_tree(path: &std::path::Path) -> Result<String, Box<dyn std::error::Error>> {
+    let mut all = String::new();
+    for file in std::fs::read_dir(path)? {
+        let file = file?.path();
+        if file.is_file() {
+            all.push_str(&String::from_utf8_lossy(&std::fs::read(file)?));
+        }
+    }
+    Ok(all)
+}
+
+#[test]
+fn test_unlock_checks_passphrase() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let key_path = dir.path().join(".journal-key");
+    let cipher = crypto::init(&key_path, "correct horse")?;
+    assert_eq!(crypto::unlock(&key_path, "correct horse")?, cipher);
+    let wrong = crypto::unlock(&key_path, "battery staple").unwrap_err();
+    assert_eq!(wrong.kind(), std::io::ErrorKind::PermissionDenied);
+    assert!(crypto::init(&key_path, "again").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_entry_round_trip_and_tamper_detection() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let cipher = crypto::init(&dir.path().join("key"), "pw")?;
+    let plain = entry("met with the doctor\nsecond line");
+    let sealed = cipher.encrypt(&plain);
+    assert!(crypto::is_encrypted(&sealed.content));
+    assert!(!sealed.content.contains("doctor"));
+    assert_eq!(sealed.timestamp, plain.timestamp);
+    assert_eq!(cipher.decrypt(&sealed)?, plain);
+    // Plain entries are passed through
+    assert_eq!(cipher.decrypt(&plain)?, plain);
+
+    let mut moved = sealed.clone();
+    moved.timestamp += TimeDelta::seconds(1);
+    assert!(cipher.decrypt(&moved).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_encrypted_store_is_transparent() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let cipher = crypto::init(&dir.path().join("key"), "pw")?;
+    let store = EncryptedStore {
+        inner: Box::new(Journal {
+            path: dir.path().join("journals"),
+        }),
+        cipher,
+        location: dir.path().join("journals"),
+    };
+    store.save(&entry("secret plans #work"))?;
+    assert_eq!(store.load_all()?, vec![entry("secret plans #work")]);
+    assert_eq!(store.search("PLANS")?.len(), 1);
+    assert!(!read_tree(&dir.path().join("journals"))?.contains("secret"));
+    Ok(())
+}
+
+#[test]
+fn test_encrypt_and_decrypt_existing_journal() -> Result<(), Box<dyn std::error::Error>> {
+    for backend in [Backend::Files, Backend::Jsonl] {
+        let dir = tempdir()?;
+        let mut config = JournalConfig {
+            backend,
+            path: Some(dir.path().join("journal")),
+            ..JournalConfig::default()
+        };
+        config.open().save(&entry("private one"))?;
+        config.open().save(&JournalEntry {
+            timestamp: entry("").timestamp + TimeDelta::minutes(5),
+            content: "private two".to_string(),
+        })?;
+
+        assert_eq!(crypto::encrypt_journal(&config, "pw")?, 2);
+        assert!(config.is_encrypted());
+        assert!(!read_tree(&config.location())?.contains("private"));
+        assert!(crypto::encrypt_journal(&config, "pw").is_err());
+
+        config.cipher = Some(crypto::unlock(&config.key_path(), "pw")?);
+        let mut contents: Vec<String> = config
+            .open()
+            .load_all()?
+            .into_iter()
+            .map(|e| e.content)
+            .collect();
+        contents.sort();
+        assert_eq!(contents, ["private one", "private two"]);
+
+        assert_eq!(crypto::decrypt_journal(&config)?, 2);
+        assert!(!config.is_encrypted());
+        config.cipher = None;
+        assert_eq!(config.open().load_all()?.len(), 2);
+        assert!(read_tree(&config.location())?.contains("private one"));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_undecryptable_entries_are_not_skipped_silently() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let journal = Journal {
+        path: dir.path().join("journals"),
+    };
+    let other = crypto::init(&dir.path().join("other"), "other")?;
+    journal.save(&other.encrypt(&entry("under another key")))?;
+    let store = EncryptedStore {
+        inner: Box::new(journal),
+        cipher: crypto::init(&dir.path().join("key"), "pw")?,
+        location: dir.path().join("journals"),
+    };
+    store.save(&JournalEntry {
+        timestamp: entry("").timestamp + TimeDelta::minutes(1),
+        content: "readable".to_string(),
+    })?;
+
+    assert_eq!(store.load_checked()?.problems.len(), 1);
+    let error = store.for_each_entry(&mut |_| Ok(())).unwrap_err();
+    assert!(error.to_string().contains("cannot decrypt"), "{}", error);
+    Ok(())
+}
+
+#[test]
+fn test_rewrite_keeps_other_files() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let mut config = JournalConfig {
+        path: Some(dir.path().join("journal")),
+        ..JournalConfig::default()
+    };
+    config.open().save(&entry("private"))?;
+    let quarantined = config.location().join("quarantine").join("bad.json");
+    std::fs::create_dir_all(quarantined.parent().unwrap())?;
+    std::fs::write(&quarantined, "{ not json")?;
+
+    crypto::encrypt_journal(&config, "pw")?;
+    assert_eq!(std::fs::read_to_string(&quarantined)?, "{ not json");
+    config.cipher = Some(crypto::unlock(&config.key_path(), "pw")?);
+    crypto::decrypt_journal(&config)?;
+    assert_eq!(std::fs::read_to_string(&quarantined)?, "{ not json");
+    assert!(!dir.path().join("journal.rewrite").exists());
+    assert!(!dir.path().join("journal.old").exists());
+    Ok(())
+}
+
+#[test]
+fn test_rewrite_moves_the_database_key() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let mut config = JournalConfig {
+        backend: Backend::Sqlite,
+        path: Some(dir.path().join("log.db")),
+        ..JournalConfig::default()
+    };
+    config.open().save(&entry("private"))?;
+
+    crypto::encrypt_journal(&config, "pw")?;
+    config.cipher = Some(crypto::unlock(&config.key_path(), "pw")?);
+    assert_eq!(config.open().load_all()?[0].content, "private");
+    crypto::decrypt_journal(&config)?;
+    assert!(!config.is_encrypted());
+    for leftover in [
+        "log.db.rewrite",
+        "log.db.rewrite.key",
+        "log.db.rewrite.lock",
+        "log.db.old",
+    ] {
+        assert!(!dir.path().join(leftover).exists(), "{}", leftover);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_interrupted_rewrite_is_recovered() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let config = JournalConfig {
+        path: Some(dir.path().join("journal")),
+        ..JournalConfig::default()
+    };
+    let at = |path: &str| JournalConfig {
+        path: Some(dir.path().join(path)),
+        ..JournalConfig::default()
+    };
+    assert!(!crypto::recover(&config)?);
+
+    // Stopped before the swap: the staging copy is dropped
+    config.open().save(&entry("live"))?;
+    at("journal.rewrite").open().save(&entry("staged"))?;
+    assert!(crypto::recover(&config)?);
+    assert_eq!(config.open().load_all()?[0].content, "live");
+    assert!(!dir.path().join("journal.rewrite").exists());
+
+    // Stopped mid-swap: the staging copy is moved in, not deleted
+    std::fs::rename(dir.path().join("journal"), dir.path().join("journal.old"))?;
+    at("journal.rewrite").open().save(&entry("staged"))?;
+    assert!(crypto::recover(&config)?);
+    assert_eq!(config.open().load_all()?[0].content, "staged");
+    assert!(!dir.path().join("journal.old").exists());
+
+    // All three copies: nothing is guessed
+    at("journal.old").open().save(&entry("old"))?;
+    at("journal.rewrite").open().save(&entry("staged"))?;
+    assert!(crypto::recover(&config).is_err());
+    assert!(crypto::encrypt_journal(&config, "pw").is_err());
+    assert!(dir.path().join("journal.old").exists());
+    assert!(dir.path().join("journal.rewrite").exists());
+    Ok(())
+}
+
+#[test]
+fn test_encrypted_database_keeps_sources_and_skips_indexing()
+-> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let mut config = JournalConfig {
+        backend: Backend::Sqlite,
+        path: Some(dir.path().join("log.db")),
+        ..JournalConfig::default()
+    };
+    let db = SqliteJournal {
+        path: dir.path().join("log.db"),
+    };
+    let named = vec![
+        ("a.json".to_string(), entry("fixed the #parser")),
+        ("b.json".to_string(), entry("fixed the #parser")),
+    ];
+    db.import(&named)?;
+
+    crypto::encrypt_journal(&config, "pw")?;
+    assert!(db.by_tag("parser")?.is_empty());
+    assert!(db.search("parser")?.is_empty());
+    assert!(db.search("enc")?.is_empty());
+    // Migrating the same files again finds them already there
+    assert_eq!(db.import(&named)?.skipped, 2);
+    assert_eq!(db.load_all()?.len(), 2);
+
+    config.cipher = Some(crypto::unlock(&config.key_path(), "pw")?);
+    crypto::decrypt_journal(&config)?;
+    assert_eq!(db.by_tag("parser")?.len(), 2);
+    assert_eq!(db.search("parser")?.len(), 2);
+    assert_eq!(db.import(&named)?.skipped, 2);
+    Ok(())
+}
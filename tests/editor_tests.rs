@@ -0,0 +1,14 @@
+use captains_log::editor;
+
+#[test]
+fn test_edit_returns_saved_text() -> Result<(), Box<dyn std::error::Error>> {
+    let edited = editor::edit_with("sed -i 's/draft/final/'", "a draft entry\n\n")?;
+    assert_eq!(edited.as_deref(), Some("a final entry"));
+    Ok(())
+}
+
+#[test]
+fn test_edit_empty_or_failed() {
+    assert_eq!(editor::edit_with("truncate -s 0", "draft").unwrap(), None);
+    assert!(editor::edit_with("false", "draft").is_err());
+}
@@ -0,0 +1,135 @@
+use captains_log::export::{self, Filter, Format};
+use captains_log::journal::{JournalEntry, JournalStore};
+use captains_log::jsonl_journal::JsonlJournal;
+use chrono::{TimeZone, Utc};
+use tempfile::tempdir;
+
+fn entry(day: u32, hour: u32, content: &str) -> JournalEntry {
+    JournalEntry {
+        timestamp: Utc.with_ymd_and_hms(2025, 3, day, hour, 0, 0).unwrap(),
+        content: content.to_string(),
+    }
+}
+
+fn journal(dir: &std::path::Path) -> std::io::Result<JsonlJournal> {
+    let journal = JsonlJournal {
+        path: dir.join("journal_log"),
+    };
+    journal.save(&entry(2, 9, "Fixed the renderer, \"finally\" #work"))?;
+    journal.save(&entry(1, 18, "Long walk\nthen <dinner> & a book #home"))?;
+    journal.save(&entry(3, 10, "Planning #Work #ideas"))?;
+    Ok(journal)
+}
+
+fn run(store: &dyn JournalStore, format: Format, filter: &Filter) -> (usize, String) {
+    let mut out = Vec::new();
+    let count = export::export(store, format, filter, &Utc, &mut out).unwrap();
+    (count, String::from_utf8(out).unwrap())
+}
+
+#[test]
+fn test_export_json_is_sorted_array() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let journal = journal(dir.path())?;
+    let (count, out) = run(&journal, Format::Json, &Filter::default());
+    assert_eq!(count, 3);
+
+    let value: serde_json::Value = serde_json::from_str(&out)?;
+    let items = value.as_array().unwrap();
+    assert_eq!(items.len(), 3);
+    assert_eq!(
+        items[0]["content"],
+        "Long walk\nthen <dinner> & a book #home"
+    );
+    assert_eq!(items[2]["tags"], serde_json::json!(["work", "ideas"]));
+
+    let (count, out) = run(
+        &journal,
+        Format::Json,
+        &Filter {
+            tag: Some("nothing".into()),
+            ..Filter::default()
+        },
+    );
+    assert_eq!(count, 0);
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&out)?,
+        serde_json::json!([])
+    );
+    Ok(())
+}
+
+#[test]
+fn test_export_csv_quotes_fields() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let journal = journal(dir.path())?;
+    let (_, out) = run(&journal, Format::Csv, &Filter::default());
+    assert_eq!(
+        out,
+        "timestamp,tags,content\n\
+         2025-03-01T18:00:00+00:00,home,\"Long walk\nthen <dinner> & a book #home\"\n\
+         2025-03-02T09:00:00+00:00,work,\"Fixed the renderer, \"\"finally\"\" #work\"\n\
+         2025-03-03T10:00:00+00:00,work ideas,Planning #Work #ideas\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_export_filters_by_range_and_tag() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let journal = journal(dir.path())?;
+    let range = Filter {
+        from: Some(entry(2, 0, "").timestamp),
+        to: Some(entry(3, 0, "").timestamp),
+        tag: None,
+    };
+    let (count, out) = run(&journal, Format::Csv, &range);
+    assert_eq!(count, 1);
+    assert!(out.contains("renderer"));
+
+    let tagged = Filter {
+        tag: Some("#WORK".into()),
+        ..Filter::default()
+    };
+    let (count, _) = run(&journal, Format::Csv, &tagged);
+    assert_eq!(count, 2);
+    Ok(())
+}
+
+#[test]
+fn test_export_html_escapes_and_groups_by_day() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let journal = journal(dir.path())?;
+    let (_, out) = run(&journal, Format::Html, &Filter::default());
+    assert!(out.starts_with("<!DOCTYPE html>"));
+    assert!(out.trim_end().ends_with("</html>"));
+    assert!(out.contains("<h2>Saturday, 1 March 2025</h2>"));
+    assert_eq!(out.matches("<section>").count(), 3);
+    assert_eq!(out.matches("</section>").count(), 3);
+    assert!(out.contains("then &lt;dinner&gt; &amp; a book <span class=\"tag\">#home</span>"));
+    assert!(!out.contains("<dinner>"));
+    Ok(())
+}
+
+#[test]
+fn test_export_ical_vjournal() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let journal = journal(dir.path())?;
+    journal.save(&entry(4, 8, &"x".repeat(100)))?;
+    let (_, out) = run(&journal, Format::Ical, &Filter::default());
+
+    assert!(out.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(out.ends_with("END:VCALENDAR\r\n"));
+    assert!(!out.replace("\r\n", "").contains('\n'));
+    assert_eq!(out.matches("BEGIN:VJOURNAL\r\n").count(), 4);
+    assert!(out.contains("DTSTART:20250302T090000Z\r\n"));
+    assert!(out.contains("SUMMARY:Fixed the renderer\\, \"finally\" #work\r\n"));
+    assert!(out.contains("DESCRIPTION:Long walk\\nthen <dinner> & a book #home\r\n"));
+    assert!(out.contains("CATEGORIES:work,ideas\r\n"));
+    assert!(out.split("\r\n").all(|line| line.len() <= 75));
+    assert!(out.contains("\r\n x"));
+
+    let (_, again) = run(&journal, Format::Ical, &Filter::default());
+    assert_eq!(out, again);
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use captains_log::flow::{FlowConfig, FlowDetector, FlowLog, FlowPeriod};
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use tempfile::tempdir;
+
+fn start() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 11, 21, 9, 0, 0).unwrap()
+}
+
+/// Types one keystroke per second for `secs` seconds, returning any finished periods.
+fn type_for(detector: &mut FlowDetector, now: &mut DateTime<Utc>, secs: i64) -> Vec<FlowPeriod> {
+    let mut ended = Vec::new();
+    for _ in 0..secs {
+        *now += TimeDelta::seconds(1);
+        ended.extend(detector.on_tick(*now, 1));
+    }
+    ended
+}
+
+fn idle_for(detector: &mut FlowDetector, now: &mut DateTime<Utc>, secs: i64) -> Vec<FlowPeriod> {
+    let mut ended = Vec::new();
+    for _ in 0..secs {
+        *now += TimeDelta::seconds(1);
+        ended.extend(detector.on_tick(*now, 0));
+    }
+    ended
+}
+
+#[test]
+fn test_flow_detected_after_sustained_typing() {
+    let mut detector = FlowDetector::new(FlowConfig::default());
+    let mut now = start();
+
+    assert!(type_for(&mut detector, &mut now, 19 * 60).is_empty());
+    assert!(detector.current().is_none());
+
+    type_for(&mut detector, &mut now, 6 * 60);
+    let current = detector.current().expect("in flow after 20 minutes");
+    assert_eq!(current.start, start() + TimeDelta::seconds(1));
+
+    // A five minute break ends the period at the last keystroke
+    let ended = idle_for(&mut detector, &mut now, 5 * 60);
+    assert_eq!(ended.len(), 1);
+    assert_eq!(ended[0].duration(), TimeDelta::seconds(25 * 60 - 1));
+    assert_eq!(ended[0].keystrokes, 25 * 60);
+    assert!(detector.current().is_none());
+}
+
+#[test]
+fn test_too_many_long_pauses_is_not_flow() {
+    let mut detector = FlowDetector::new(FlowConfig::default());
+    let mut now = start();
+
+    // Typing in bursts with 90 second pauses: ~10 long pauses in 25 minutes
+    for _ in 0..10 {
+        type_for(&mut detector, &mut now, 60);
+        idle_for(&mut detector, &mut now, 90);
+    }
+    assert!(detector.current().is_none());
+    assert!(detector.finish().is_none());
+}
+
+#[test]
+fn test_short_session_is_not_flow() {
+    let mut detector = FlowDetector::new(FlowConfig::default());
+    let mut now = start();
+
+    type_for(&mut detector, &mut now, 10 * 60);
+    assert!(idle_for(&mut detector, &mut now, 10 * 60).is_empty());
+}
+
+#[test]
+fn test_flow_log_append_and_load() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let log = FlowLog {
+        path: dir.path().join("flow"),
+    };
+    let period = FlowPeriod {
+        start: start(),
+        end: start() + TimeDelta::minutes(30),
+        keystrokes: 4200,
+    };
+    log.append(&period)?;
+    // A bad line does not hide the rest
+    std::fs::write(log.path.join("1999-01.jsonl"), "garbage\n")?;
+
+    assert_eq!(log.load_all()?, vec![period]);
+    assert_eq!(log.load_checked()?.1.len(), 1);
+    assert_eq!(
+        period.overlap(
+            start() + TimeDelta::minutes(20),
+            start() + TimeDelta::hours(1)
+        ),
+        TimeDelta::minutes(10)
+    );
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use captains_log::config::Config;
+use captains_log::focus::{
+    ClassicModel, ClassicParams, EmaModel, EmaParams, FocusConfig, FocusModel, ModelKind,
+};
+use serde::Deserialize;
+use tempfile::tempdir;
+
+/// A recorded activity trace: run-length encoded `[keystrokes, ticks]` pairs.
+#[derive(Deserialize)]
+struct Trace {
+    tick_ms: u64,
+    ticks: Vec<(u32, usize)>,
+}
+
+fn load_trace(name: &str) -> Trace {
+    let path = format!(
+        "{}/tests/fixtures/focus/{}.json",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// Replays a trace and returns the level after every tick.
+fn replay(model: &mut dyn FocusModel, trace: &Trace) -> Vec<f64> {
+    let dt = trace.tick_ms as f64 / 1000.0;
+    let mut levels = Vec::new();
+    for &(keystrokes, repeat) in &trace.ticks {
+        for _ in 0..repeat {
+            model.on_tick(keystrokes, dt);
+            levels.push(model.level());
+        }
+    }
+    levels
+}
+
+#[test]
+fn test_ema_steady_typing_locks_in_gradually() {
+    let trace = load_trace("steady_typing");
+    let mut model = EmaModel::new(EmaParams::default());
+    let levels = replay(&mut model, &trace);
+
+    // Not saturated after the first minute...
+    assert!(levels[4 * 60] < 40.0);
+    // ...but locked in after 20 minutes of work
+    let config = FocusConfig::default();
+    assert_eq!(config.describe(*levels.last().unwrap()), "LOCKED IN");
+}
+
+#[test]
+fn test_ema_sporadic_bursts_stay_low() {
+    let trace = load_trace("sporadic_bursts");
+    let mut ema = EmaModel::new(EmaParams::default());
+    let levels = replay(&mut ema, &trace);
+    assert!(levels.iter().all(|&l| l < 30.0));
+
+    // The classic model saturates on the same trace
+    let mut classic = ClassicModel::new(ClassicParams::default());
+    let levels = replay(&mut classic, &trace);
+    assert!(levels.iter().any(|&l| l >= 99.0));
+}
+
+#[test]
+fn test_ema_long_pause_penalty() {
+    let trace = load_trace("deep_work_then_break");
+    let params = EmaParams::default();
+    let mut model = EmaModel::new(params.clone());
+    let levels = replay(&mut model, &trace);
+
+    let end_of_work = 15 * 60 * 4 - 1;
+    let before = levels[end_of_work];
+    let ticks_to_penalty = (params.long_pause_secs * 4.0) as usize;
+    let just_before = levels[end_of_work + ticks_to_penalty - 1];
+    let after = levels[end_of_work + ticks_to_penalty];
+
+    assert!(before > 60.0);
+    // The penalty removes a quarter of the level in one step
+    assert!((after - just_before * 0.75).abs() < 0.5);
+    assert!(*levels.last().unwrap() < before / 2.0);
+}
+
+#[test]
+fn test_classic_model_matches_original_rules() {
+    let mut model = ClassicModel::new(ClassicParams::default());
+    assert_eq!(model.level(), 100.0);
+
+    // 10 seconds idle costs nothing, then 0.5 per tick
+    for _ in 0..41 {
+        model.on_tick(0, 0.25);
+    }
+    assert_eq!(model.level(), 99.5);
+
+    model.on_tick(3, 0.25);
+    assert_eq!(model.level(), 100.0);
+}
+
+#[test]
+fn test_focus_config_from_file() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("captains_log.toml");
+
+    // Missing file means defaults
+    assert_eq!(Config::load(&path)?, Config::default());
+
+    std::fs::write(
+        &path,
+        "[focus]\nmodel = \"classic\"\nlocked_in_at = 90\n\n[focus.ema]\nhalf_life_secs = 60\n",
+    )?;
+    let config = Config::load(&path)?;
+    assert_eq!(config.focus.model, ModelKind::Classic);
+    assert_eq!(config.focus.locked_in_at, 90.0);
+    assert_eq!(config.focus.ema.half_life_secs, 60.0);
+    assert_eq!(config.focus.ema.active_grace_secs, 5.0);
+    assert_eq!(config.focus.build().level(), 100.0);
+
+    std::fs::write(&path, "[focus]\nmodel = \"nope\"\n")?;
+    assert!(Config::load(&path).is_err());
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use captains_log::crypto;
+use captains_log::git_journal::{self, GitConfig};
+use captains_log::journal::{Backend, JournalConfig, JournalEntry};
+use chrono::{TimeZone, Utc};
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn entry(minute: u32, content: &str) -> JournalEntry {
+    JournalEntry {
+        timestamp: Utc.with_ymd_and_hms(2025, 6, 1, 10, minute, 0).unwrap(),
+        content: content.to_string(),
+    }
+}
+
+fn config(path: &Path, push: Option<String>) -> JournalConfig {
+    JournalConfig {
+        backend: Backend::Files,
+        path: Some(path.to_path_buf()),
+        git: GitConfig {
+            enabled: true,
+            push,
+        },
+        ..JournalConfig::default()
+    }
+}
+
+#[test]
+fn test_every_save_is_committed() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let journal_dir = dir.path().join("journals");
+    let journal = config(&journal_dir, None).open();
+    journal.save(&entry(0, "Started the migration\nwith details"))?;
+    journal.save(&entry(1, "Finished"))?;
+
+    let log = git(&journal_dir, &["log", "--format=%s"]);
+    let subjects: Vec<&str> = log.lines().collect();
+    assert_eq!(
+        subjects,
+        [
+            "Add entry 2025-06-01 10:01:00: Finished",
+            "Add entry 2025-06-01 10:00:00: Started the migration"
+        ]
+    );
+    assert!(git(&journal_dir, &["status", "--porcelain"]).is_empty());
+    assert!(journal.status().is_none());
+    assert_eq!(journal.load_all()?.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_pushes_only_when_configured() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let remote = dir.path().join("remote.git");
+    Command::new("git")
+        .args(["init", "--quiet", "--bare"])
+        .arg(&remote)
+        .status()?;
+
+    let quiet = config(&dir.path().join("local"), None).open();
+    quiet.save(&entry(0, "stays here"))?;
+    assert!(git(&remote, &["log", "--all", "--format=%s"]).is_empty());
+
+    let pushing = config(
+        &dir.path().join("pushed"),
+        Some(remote.to_string_lossy().into_owned()),
+    )
+    .open();
+    pushing.save(&entry(1, "goes to the remote"))?;
+    // The push runs in the background; dropping the journal waits for it
+    drop(pushing);
+    assert_eq!(
+        git(&remote, &["log", "--all", "--format=%s"]).trim(),
+        "Add entry 2025-06-01 10:01:00: goes to the remote"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_failed_push_keeps_the_entry() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let journal_dir = dir.path().join("journals");
+    let missing = dir.path().join("no-such-remote.git");
+    let journal = config(&journal_dir, Some(missing.to_string_lossy().into_owned())).open();
+    journal.save(&entry(0, "offline"))?;
+    let started = std::time::Instant::now();
+    while journal.status().is_none() && started.elapsed().as_secs() < 10 {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    assert!(journal.status().unwrap().contains("git push"));
+    assert_eq!(journal.load_all()?.len(), 1);
+    assert_eq!(
+        git(&journal_dir, &["log", "--format=%s"]).lines().count(),
+        1
+    );
+    Ok(())
+}
+
+#[test]
+fn test_encryption_keeps_history_and_hides_messages() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let journal_dir = dir.path().join("journals");
+    let mut journal = config(&journal_dir, None);
+    journal.open().save(&entry(0, "before"))?;
+    crypto::encrypt_journal(&journal, "pw")?;
+    journal.cipher = Some(crypto::unlock(&journal.key_path(), "pw")?);
+    journal.open().save(&entry(1, "secret"))?;
+
+    let log = git(&journal_dir, &["log", "--format=%s"]);
+    assert_eq!(
+        log.lines().collect::<Vec<_>>(),
+        [
+            "Add entry 2025-06-01 10:01:00",
+            "Encrypt journal",
+            "Add entry 2025-06-01 10:00:00: before"
+        ]
+    );
+    assert_eq!(journal.open().load_all()?.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_entry_message_truncates_long_lines() {
+    let message = git_journal::entry_message(&entry(0, &"x".repeat(80)));
+    assert!(message.ends_with(&format!("{}…", "x".repeat(50))));
+}
@@ -0,0 +1,101 @@
+use captains_log::goals::{
+    DayRecord, GoalLog, GoalsConfig, Pomodoro, Progress, best_streak, current_streak,
+};
+use chrono::{NaiveDate, TimeDelta, TimeZone, Utc};
+use tempfile::tempdir;
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+}
+
+fn record(day: u32, met: bool) -> DayRecord {
+    DayRecord {
+        date: date(day),
+        progress: Progress::default(),
+        met,
+    }
+}
+
+#[test]
+fn test_goals_evaluate_and_ratio() {
+    let goals = GoalsConfig {
+        active_minutes: 60,
+        pomodoros: 4,
+        journal_entries: 0,
+        keystrokes: 0,
+        pomodoro_minutes: 25,
+    };
+    let progress = Progress {
+        active_minutes: 90,
+        pomodoros: 2,
+        journal_entries: 5,
+        keystrokes: 100,
+    };
+
+    // Disabled goals are skipped
+    let status = goals.evaluate(&progress);
+    assert_eq!(status.len(), 2);
+    assert!(status[0].is_met());
+    assert!(!status[1].is_met());
+    assert!(!goals.is_met(&progress));
+    assert_eq!(goals.ratio(&progress), 0.75);
+
+    let no_goals = GoalsConfig {
+        active_minutes: 0,
+        pomodoros: 0,
+        ..goals
+    };
+    assert!(!no_goals.is_met(&progress));
+}
+
+#[test]
+fn test_streaks() {
+    let records = vec![
+        record(10, true),
+        record(11, true),
+        record(12, true),
+        record(13, false),
+        // 14th missing: app not run
+        record(15, true),
+        record(16, true),
+    ];
+
+    assert_eq!(best_streak(&records), 3);
+    assert_eq!(current_streak(&records, date(16)), 2);
+    // Today not met yet keeps yesterday's streak alive
+    assert_eq!(current_streak(&records, date(17)), 2);
+    // A missed day breaks it
+    assert_eq!(current_streak(&records, date(18)), 0);
+}
+
+#[test]
+fn test_goal_log_upsert() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let log = GoalLog {
+        path: dir.path().join("goals.json"),
+    };
+    assert!(log.load()?.is_empty());
+
+    log.upsert(record(12, false))?;
+    log.upsert(record(11, true))?;
+    let records = log.upsert(record(12, true))?;
+
+    assert_eq!(records, vec![record(11, true), record(12, true)]);
+    assert_eq!(log.load()?, records);
+    Ok(())
+}
+
+#[test]
+fn test_pomodoro_remaining() {
+    let started = Utc.with_ymd_and_hms(2025, 11, 21, 9, 0, 0).unwrap();
+    let pomodoro = Pomodoro {
+        started,
+        length: TimeDelta::minutes(25),
+    };
+    assert_eq!(
+        pomodoro.remaining(started + TimeDelta::minutes(10)),
+        TimeDelta::minutes(15)
+    );
+    assert!(!pomodoro.is_done(started + TimeDelta::minutes(24)));
+    assert!(pomodoro.is_done(started + TimeDelta::minutes(26)));
+}
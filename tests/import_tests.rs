@@ -0,0 +1,131 @@
+use captains_log::import::{self, Format};
+use captains_log::journal::{self, JournalEntry};
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/import")
+        .join(name)
+}
+
+/// Central European winter time, so local and UTC times differ.
+fn cet() -> FixedOffset {
+    FixedOffset::east_opt(3600).unwrap()
+}
+
+fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+}
+
+#[test]
+fn test_jrnl_text() -> Result<(), Box<dyn std::error::Error>> {
+    let imported = import::read(Format::Jrnl, &fixture("jrnl.txt"), &cet())?;
+    assert!(imported.warnings.is_empty());
+    let entries = imported.entries;
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].timestamp, utc(2024, 3, 1, 8, 15));
+    assert_eq!(
+        entries[0].content,
+        "Standup with the team. #work\nDiscussed the release."
+    );
+    assert_eq!(journal::tags(&entries[1].content), ["reading", "books"]);
+    assert_eq!(entries[2].content, "Morning run");
+    Ok(())
+}
+
+#[test]
+fn test_jrnl_export_matches_text() -> Result<(), Box<dyn std::error::Error>> {
+    let export = import::read(Format::Jrnl, &fixture("jrnl_export.json"), &cet())?;
+    let text = import::read(Format::Jrnl, &fixture("jrnl.txt"), &cet())?;
+    assert_eq!(export.entries.len(), 2);
+    assert_eq!(export.entries[0], text.entries[0]);
+    assert_eq!(export.entries[1], text.entries[2]);
+    Ok(())
+}
+
+#[test]
+fn test_day_one() -> Result<(), Box<dyn std::error::Error>> {
+    let imported = import::read(Format::DayOne, &fixture("dayone/Journal.json"), &cet())?;
+    assert_eq!(imported.entries.len(), 2);
+    assert_eq!(imported.warnings.len(), 1);
+    let first = &imported.entries[0];
+    assert_eq!(first.timestamp, utc(2024, 3, 1, 8, 15));
+    assert_eq!(
+        first.content,
+        "Shipped v2.0 - finally!\n\nCelebrated with cake.\n#work #milestones"
+    );
+    // Tags already in the text are not repeated
+    assert_eq!(imported.entries[1].content, "Quiet morning #calm");
+    Ok(())
+}
+
+#[test]
+fn test_obsidian_daily_notes() -> Result<(), Box<dyn std::error::Error>> {
+    let imported = import::read(Format::Obsidian, &fixture("obsidian"), &cet())?;
+    let entries = imported.entries;
+    assert_eq!(entries.len(), 5);
+    assert!(imported.warnings.is_empty());
+    assert_eq!(entries[0].timestamp, utc(2024, 3, 1, 8, 15));
+    assert_eq!(
+        entries[0].content,
+        "Standup\nnotes on the release\n#daily #work"
+    );
+    assert_eq!(entries[1].timestamp, utc(2024, 3, 1, 12, 0));
+    assert_eq!(entries[1].content, "Lunch with Sam\n#daily #work");
+    // A note without timed items is one entry at the start of its day
+    assert_eq!(entries[2].timestamp, utc(2024, 3, 1, 23, 0));
+    assert_eq!(
+        entries[2].content,
+        "Slept in and went for a long walk.\n#weekend"
+    );
+    // A timed item right after the front matter
+    assert_eq!(entries[3].timestamp, utc(2024, 3, 3, 6, 30));
+    assert_eq!(entries[3].content, "Ran 5k\n#health");
+    assert_eq!(entries[4].content, "Stretching\n#health");
+    Ok(())
+}
+
+#[test]
+fn test_text_log() -> Result<(), Box<dyn std::error::Error>> {
+    let imported = import::read(Format::Text, &fixture("log.md"), &cet())?;
+    assert_eq!(imported.warnings.len(), 1);
+    let entries = imported.entries;
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].timestamp, utc(2024, 3, 1, 9, 0));
+    assert_eq!(entries[0].content, "Fixed the flaky test.");
+    assert_eq!(entries[1].content, "Review\nReviewed two PRs #review");
+
+    let fallback = utc(2024, 5, 5, 5, 5);
+    let undated = import::parse_text("just a thought\n", &cet(), Some(fallback));
+    assert_eq!(
+        undated.entries,
+        [JournalEntry {
+            timestamp: fallback,
+            content: "just a thought".to_string(),
+        }]
+    );
+    // Hashtags and dates inside words do not start entries
+    let tagged = import::parse_text("# 2024-03-01\n#2024-03-02 tag\n2024-03-03x\n", &cet(), None);
+    assert_eq!(tagged.entries.len(), 1);
+    assert_eq!(tagged.entries[0].content, "#2024-03-02 tag\n2024-03-03x");
+    Ok(())
+}
+
+#[test]
+fn test_plan_deduplicates() -> Result<(), Box<dyn std::error::Error>> {
+    let imported = import::read(Format::Jrnl, &fixture("jrnl.txt"), &cet())?.entries;
+    let existing = vec![imported[1].clone()];
+    let mut twice = imported.clone();
+    twice.extend(imported.clone());
+
+    let plan = import::plan(twice, &existing);
+    assert_eq!(plan.new.len(), 2);
+    assert_eq!(plan.duplicates, 4);
+    assert!(
+        plan.new
+            .windows(2)
+            .all(|w| w[0].timestamp <= w[1].timestamp)
+    );
+    Ok(())
+}
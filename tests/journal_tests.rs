@@ -81,3 +81,79 @@ fn test_journal_save_directory_creation() -> Result<(), Box<dyn std::error::Erro
     assert!(journal_path.is_dir());
     Ok(())
 }
+
+#[test]
+fn test_journal_skips_and_quarantines_corrupt_files() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let journal = Journal {
+        path: dir.path().to_path_buf(),
+    };
+    journal.save(&JournalEntry {
+        timestamp: Utc::now(),
+        content: "still readable".to_string(),
+    })?;
+    std::fs::write(dir.path().join("broken.json"), "{\"timestamp\": ")?;
+    std::fs::write(dir.path().join("binary.json"), [0xff, 0xfe, 0x00])?;
+
+    let loaded = journal.load_checked()?;
+    assert_eq!(loaded.entries.len(), 1);
+    assert_eq!(loaded.problems.len(), 2);
+    assert_eq!(loaded.problems[0].path, dir.path().join("binary.json"));
+    assert_eq!(journal.load_all()?.len(), 1);
+
+    assert_eq!(journal.quarantine()?, 2);
+    assert!(journal.quarantine_dir().join("broken.json").exists());
+    let loaded = journal.load_checked()?;
+    assert_eq!(loaded.entries.len(), 1);
+    assert!(loaded.problems.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_write_atomic_replaces_without_leftovers() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("goals.json");
+    captains_log::journal::write_atomic(&path, b"old")?;
+    captains_log::journal::write_atomic(&path, b"new")?;
+    assert_eq!(std::fs::read_to_string(&path)?, "new");
+    assert_eq!(std::fs::read_dir(dir.path())?.count(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_concurrent_saves_never_overwrite() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().to_path_buf();
+    let threads: Vec<_> = (0..4)
+        .map(|t| {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                let journal = Journal { path };
+                for i in 0..25 {
+                    journal
+                        .save(&JournalEntry {
+                            timestamp: Utc::now(),
+                            content: format!("{} {}", t, i),
+                        })
+                        .unwrap();
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    let journal = Journal { path };
+    assert_eq!(journal.load_all()?.len(), 100);
+    Ok(())
+}
+
+#[test]
+fn test_entry_filenames_are_unique_within_a_millisecond() {
+    let now = Utc::now();
+    let a = captains_log::journal::entry_filename(now);
+    let b = captains_log::journal::entry_filename(now);
+    assert_ne!(a, b);
+    assert!(a.starts_with(&now.format("%Y-%m-%d-%H-%M-%S-%3f").to_string()));
+    assert!(a.ends_with(".json"));
+}
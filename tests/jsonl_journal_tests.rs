@@ -0,0 +1,189 @@
+use captains_log::journal::{self, Journal, JournalEntry, JournalStore};
+use captains_log::jsonl_journal::JsonlJournal;
+use chrono::{TimeZone, Utc};
+use tempfile::tempdir;
+
+fn entry(month: u32, day: u32, content: &str) -> JournalEntry {
+    JournalEntry {
+        timestamp: Utc.with_ymd_and_hms(2025, month, day, 12, 0, 0).unwrap(),
+        content: content.to_string(),
+    }
+}
+
+#[test]
+fn test_jsonl_save_and_load_by_month() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let journal = JsonlJournal {
+        path: dir.path().join("journal_log"),
+    };
+    journal.save(&entry(10, 31, "October"))?;
+    journal.save(&entry(11, 1, "November 1"))?;
+    journal.save(&entry(11, 2, "November 2\nwith a second line"))?;
+
+    assert!(journal.path.join("2025-10.jsonl").exists());
+    assert!(journal.path.join("2025-11.jsonl").exists());
+
+    let entries = journal.load_all()?;
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[2].content, "November 2\nwith a second line");
+
+    let index = journal.index()?;
+    assert_eq!(index.months["2025-11"].entries, 2);
+    assert_eq!(
+        index.months["2025-10"].last,
+        Some(entry(10, 31, "").timestamp)
+    );
+
+    let november = journal.load_range(entry(11, 1, "").timestamp, entry(12, 1, "").timestamp)?;
+    assert_eq!(november.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_jsonl_index_keeps_months_without_entries() -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::MetadataExt;
+
+    let dir = tempdir()?;
+    let journal = JsonlJournal {
+        path: dir.path().join("journal_log"),
+    };
+    journal.save(&entry(11, 1, "First"))?;
+    std::fs::write(journal.path.join("2025-09.jsonl"), "{ not json\n")?;
+    std::fs::write(journal.path.join("2025-10.jsonl"), "")?;
+    journal.save(&entry(11, 2, "Second"))?;
+
+    let index = journal.index()?;
+    assert_eq!(index.months.len(), 3);
+    assert_eq!(index.months["2025-09"].entries, 0);
+    assert_eq!(index.months["2025-10"].first, None);
+    // Up to date now, so neither reading it nor the next save rebuilds it
+    let inode = std::fs::metadata(journal.path.join("index.json"))?.ino();
+    journal.index()?;
+    assert_eq!(
+        std::fs::metadata(journal.path.join("index.json"))?.ino(),
+        inode
+    );
+    journal.save(&entry(11, 3, "Third"))?;
+    assert_eq!(journal.index()?.months["2025-11"].entries, 3);
+    assert_eq!(
+        journal.load_range(entry(9, 1, "").timestamp, entry(11, 1, "").timestamp)?,
+        []
+    );
+    Ok(())
+}
+
+#[test]
+fn test_jsonl_index_rebuilt_when_stale() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let journal = JsonlJournal {
+        path: dir.path().join("journal_log"),
+    };
+    journal.save(&entry(11, 1, "First"))?;
+
+    // Another writer appended without updating the index
+    let line = serde_json::to_string(&entry(11, 5, "Second"))?;
+    let month = journal.path.join("2025-11.jsonl");
+    let content = std::fs::read_to_string(&month)? + &line + "\n";
+    std::fs::write(&month, content)?;
+
+    assert_eq!(journal.index()?.months["2025-11"].entries, 2);
+
+    // A corrupt index is rebuilt too
+    std::fs::write(journal.path.join("index.json"), "{ not json")?;
+    journal.save(&entry(11, 6, "Third"))?;
+    assert_eq!(journal.index()?.months["2025-11"].entries, 3);
+    Ok(())
+}
+
+#[test]
+fn test_migrate_from_files_is_idempotent() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let files = Journal {
+        path: dir.path().join("journals"),
+    };
+    let jsonl = JsonlJournal {
+        path: dir.path().join("journal_log"),
+    };
+    files.save(&entry(11, 2, "Second"))?;
+    std::thread::sleep(std::time::Duration::from_millis(10)); // Files are named by save time
+    files.save(&entry(11, 1, "First"))?;
+
+    let result = journal::migrate(&files, &jsonl)?;
+    assert_eq!((result.migrated, result.skipped), (2, 0));
+    let result = journal::migrate(&files, &jsonl)?;
+    assert_eq!((result.migrated, result.skipped), (0, 2));
+
+    // Oldest first in the append-only log, and the source is untouched
+    let migrated = JournalStore::load_all(&jsonl)?;
+    assert_eq!(
+        migrated,
+        vec![entry(11, 1, "First"), entry(11, 2, "Second")]
+    );
+    assert_eq!(files.load_all()?.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_jsonl_reports_and_quarantines_bad_lines() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let store = JsonlJournal {
+        path: dir.path().to_path_buf(),
+    };
+    store.save(&entry(3, 1, "first"))?;
+    let file = dir.path().join("2025-03.jsonl");
+    let mut content = std::fs::read_to_string(&file)?;
+    content.push_str("not json\n");
+    std::fs::write(&file, content)?;
+    store.save(&entry(3, 2, "after the damage"))?;
+
+    let loaded = store.load_checked()?;
+    assert_eq!(loaded.entries.len(), 2);
+    assert_eq!(loaded.problems.len(), 1);
+    assert_eq!(loaded.problems[0].line, Some(2));
+    assert!(loaded.problems[0].to_string().contains("2025-03.jsonl:2:"));
+
+    assert_eq!(store.quarantine()?, 1);
+    assert_eq!(
+        std::fs::read_to_string(store.quarantine_dir().join("2025-03.jsonl"))?,
+        "not json\n"
+    );
+    let loaded = store.load_checked()?;
+    assert_eq!(loaded.entries.len(), 2);
+    assert!(loaded.problems.is_empty());
+    assert_eq!(store.index()?.months["2025-03"].entries, 2);
+    Ok(())
+}
+
+#[test]
+fn test_save_after_torn_write() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let journal = JsonlJournal {
+        path: dir.path().join("journal_log"),
+    };
+    journal.save(&entry(11, 1, "before the crash"))?;
+    // A crash mid-write leaves half a record without its newline
+    let file = journal.path.join("2025-11.jsonl");
+    let mut content = std::fs::read_to_string(&file)?;
+    content.push_str("{\"version\":1,\"timest");
+    std::fs::write(&file, content)?;
+
+    journal.save(&entry(11, 2, "after the crash"))?;
+    let loaded = journal.load_checked()?;
+    let contents: Vec<&str> = loaded.entries.iter().map(|e| e.content.as_str()).collect();
+    assert_eq!(contents, ["before the crash", "after the crash"]);
+    assert_eq!(loaded.problems.len(), 1);
+    assert_eq!(loaded.problems[0].line, Some(2));
+    Ok(())
+}
+
+#[test]
+fn test_append_line_ends_a_torn_line() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("log.jsonl");
+    journal::append_line(&path, "one")?;
+    std::fs::write(&path, "one\ntw")?;
+    let len = journal::append_line(&path, "three")?;
+    assert_eq!(std::fs::read_to_string(&path)?, "one\ntw\nthree\n");
+    assert_eq!(len, 13);
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use captains_log::commands::Action;
+use captains_log::config::Config;
+use captains_log::keymap::{KeyChord, KeyStyle, Keymap, Section};
+use captains_log::state::Screen;
+use crossterm::event::{KeyCode, KeyModifiers};
+
+fn chord(text: &str) -> KeyChord {
+    text.parse().unwrap()
+}
+
+#[test]
+fn test_parse_and_display_chords() {
+    let ctrl_p = KeyChord::new(KeyCode::Char('p'), KeyModifiers::CONTROL);
+    assert_eq!(chord("ctrl+p"), ctrl_p);
+    assert_eq!(chord("Ctrl+P"), ctrl_p);
+    assert_eq!(chord("shift+tab").code, KeyCode::BackTab);
+    assert_eq!(
+        chord("shift+e"),
+        KeyChord::new(KeyCode::Char('E'), KeyModifiers::SHIFT)
+    );
+    assert_eq!(chord("alt++").code, KeyCode::Char('+'));
+    assert_eq!(chord("PgDn").code, KeyCode::PageDown);
+    assert_eq!(chord("f12").code, KeyCode::F(12));
+    assert_eq!(chord("?").code, KeyCode::Char('?'));
+    assert!("hyper+x".parse::<KeyChord>().is_err());
+    assert!("f99".parse::<KeyChord>().is_err());
+    assert!("banana".parse::<KeyChord>().is_err());
+
+    assert_eq!(ctrl_p.to_string(), "Ctrl+P");
+    assert_eq!(chord("alt+up").to_string(), "Alt+↑");
+    assert_eq!(chord("space").to_string(), "Space");
+}
+
+#[test]
+fn test_sections_are_looked_up_in_order() {
+    let keymap = Keymap::default();
+    let browsing = keymap.layers(Screen::Journal, true);
+    let typing = keymap.layers(Screen::Journal, false);
+    assert_eq!(
+        keymap.lookup(browsing, chord("enter")),
+        Some(Action::OpenEntry)
+    );
+    assert_eq!(
+        keymap.lookup(typing, chord("enter")),
+        Some(Action::SubmitOrNewLine)
+    );
+    assert_eq!(keymap.lookup(typing, chord("?")), None);
+    // An entry may start with `:` or `?` even though the input is empty
+    assert_eq!(keymap.lookup(browsing, chord(":")), None);
+    assert_eq!(keymap.lookup(browsing, chord("?")), None);
+    let stats = keymap.layers(Screen::Stats, false);
+    assert_eq!(keymap.lookup(stats, chord("?")), Some(Action::Help));
+    assert_eq!(keymap.lookup(typing, chord("f10")), Some(Action::Quit));
+    assert!(keymap.types_text(Screen::Journal, true));
+
+    let vim = Keymap::preset(KeyStyle::Vim);
+    let normal = vim.layers(Screen::Journal, true);
+    assert_eq!(vim.lookup(normal, chord("j")), Some(Action::SelectNext));
+    assert_eq!(vim.lookup(normal, chord(":")), Some(Action::Palette));
+    assert!(!vim.types_text(Screen::Journal, true));
+    assert_eq!(vim.key_for(normal, Action::Palette), Some(chord("ctrl+p")));
+    assert_eq!(vim.key_for(normal, Action::InsertMode), Some(chord("i")));
+
+    let emacs = Keymap::preset(KeyStyle::Emacs);
+    let layers = emacs.layers(Screen::Stats, false);
+    assert_eq!(emacs.lookup(layers, chord("ctrl+p")), None);
+    assert_eq!(emacs.lookup(layers, chord("alt+x")), Some(Action::Palette));
+    assert!(emacs.conflicts().is_empty());
+}
+
+#[test]
+fn test_help_lists_active_bindings() {
+    let keymap = Keymap::default();
+    let stats = keymap.help(keymap.layers(Screen::Stats, false));
+    assert!(stats.contains(&(chord("tab"), Action::CycleStatsPeriod)));
+    assert!(stats.contains(&(chord("ctrl+p"), Action::Palette)));
+    assert!(
+        !stats
+            .iter()
+            .any(|(_, action)| *action == Action::ExternalEditor)
+    );
+
+    // A key hidden by a more specific section is listed once
+    let journal = keymap.help(keymap.layers(Screen::Journal, true));
+    let enters: Vec<_> = journal
+        .iter()
+        .filter(|(c, _)| *c == chord("enter"))
+        .collect();
+    assert_eq!(enters, [&(chord("enter"), Action::OpenEntry)]);
+}
+
+#[test]
+fn test_keys_from_config() -> Result<(), Box<dyn std::error::Error>> {
+    let config: Config = toml::from_str(
+        r#"
+        [keys]
+        style = "vim"
+
+        [keys.global]
+        "ctrl+p" = "none"
+        "alt+x" = "palette"
+
+        [keys.normal]
+        q = "quit"
+        "ctrl+s" = "export_html"
+        "#,
+    )?;
+    let keymap = config.keys.keymap()?;
+    assert!(keymap.modal);
+    let normal = keymap.layers(Screen::Journal, true);
+    assert_eq!(keymap.lookup(normal, chord("q")), Some(Action::Quit));
+    assert_eq!(
+        keymap.lookup(normal, chord("ctrl+s")),
+        Some(Action::Export(captains_log::export::Format::Html))
+    );
+    assert_eq!(keymap.lookup(normal, chord("ctrl+p")), None);
+    assert_eq!(keymap.lookup(normal, chord("alt+x")), Some(Action::Palette));
+    assert!(keymap.conflicts().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_bad_keys_are_rejected() {
+    let keys = |toml: &str| {
+        toml::from_str::<Config>(toml)
+            .unwrap()
+            .keys
+            .keymap()
+            .unwrap_err()
+    };
+    assert!(keys("[keys.global]\n\"ctrl+p\" = \"fly\"").contains("unknown action `fly`"));
+    assert!(keys("[keys.global]\n\"ctrl+banana\" = \"quit\"").contains("unknown key"));
+    assert!(keys("[keys.global]\n\"ctrl+q+\" = \"quit\"").contains("unknown modifier `q`"));
+    assert!(
+        keys("[keys.global]\n\"ctrl+q\" = \"quit\"\n\"Ctrl+Q\" = \"help\"")
+            .contains("bound more than once")
+    );
+}
+
+#[test]
+fn test_conflicts_are_reported() -> Result<(), Box<dyn std::error::Error>> {
+    let config: Config = toml::from_str(
+        r#"
+        [keys.global]
+        "ctrl+s" = "search"
+
+        [keys.insert]
+        x = "help"
+        "#,
+    )?;
+    let keymap = config.keys.keymap()?;
+    let conflicts = keymap.conflicts();
+    assert_eq!(
+        conflicts,
+        [
+            "x in [keys.insert] can no longer be typed into an entry",
+            "Ctrl+S runs submit from [keys.insert], so search in [keys.global] never runs there",
+            "Ctrl+S runs submit from [keys.editor], so search in [keys.global] never runs there",
+        ]
+    );
+    assert_eq!(
+        keymap.lookup(&[Section::Global], chord("ctrl+s")),
+        Some(Action::Search)
+    );
+    Ok(())
+}
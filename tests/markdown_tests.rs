@@ -0,0 +1,116 @@
+use captains_log::markdown;
+use captains_log::theme::Theme;
+use ratatui::style::{Color, Modifier};
+use ratatui::text::Line;
+
+fn text(line: &Line) -> String {
+    line.spans.iter().map(|s| s.content.as_ref()).collect()
+}
+
+fn texts(lines: &[Line]) -> Vec<String> {
+    lines.iter().map(text).collect()
+}
+
+#[test]
+fn test_blocks() {
+    let lines = markdown::render(
+        "# Release day\n\n\n- shipped *it*\n  - nested\n1. first\n- [x] done\n> quoted\n---",
+        20,
+        &Theme::default(),
+    );
+    assert_eq!(
+        texts(&lines),
+        [
+            "Release day",
+            "",
+            "• shipped it",
+            "  ◦ nested",
+            "1. first",
+            "☑ done",
+            "│ quoted",
+            "────────────────────",
+        ]
+    );
+    let title = &lines[0].spans[0];
+    assert!(title.style.add_modifier.contains(Modifier::BOLD));
+    let italic = lines[2].spans.iter().find(|s| s.content == "it").unwrap();
+    assert!(italic.style.add_modifier.contains(Modifier::ITALIC));
+}
+
+#[test]
+fn test_inline_styles() {
+    let lines = markdown::render(
+        "**bold** and `code` in snake_case, see [docs](https://x.io) #work",
+        80,
+        &Theme::default(),
+    );
+    assert_eq!(
+        text(&lines[0]),
+        "bold and code in snake_case, see docs (https://x.io) #work"
+    );
+    let span = |content: &str| {
+        lines[0]
+            .spans
+            .iter()
+            .find(|s| s.content == content)
+            .unwrap()
+            .style
+    };
+    assert!(span("bold").add_modifier.contains(Modifier::BOLD));
+    assert_eq!(span("code").fg, Some(Color::Yellow));
+    assert!(span("docs").add_modifier.contains(Modifier::UNDERLINED));
+    assert_eq!(span("#work").fg, Some(Color::Cyan));
+    // Unmatched markers stay as typed
+    assert_eq!(
+        texts(&markdown::render("2 * 3 = 6 **", 80, &Theme::default())),
+        ["2 * 3 = 6 **"]
+    );
+}
+
+#[test]
+fn test_wraps_to_width() {
+    let lines = markdown::render("- one two three four five six\n", 14, &Theme::default());
+    assert_eq!(texts(&lines), ["• one two", "  three four", "  five six"]);
+    assert!(lines.iter().all(|l| l.width() <= 14));
+
+    let long = markdown::render(&"x".repeat(25), 10, &Theme::default());
+    assert_eq!(texts(&long), ["xxxxxxxxxx", "xxxxxxxxxx", "xxxxx"]);
+}
+
+#[test]
+fn test_code_fence_highlighting() {
+    let lines = markdown::render(
+        "```rust\nfn main() { // entry\n    let s = \"hi\";\n```\nafter",
+        40,
+        &Theme::default(),
+    );
+    assert_eq!(
+        texts(&lines),
+        ["▎ fn main() { // entry", "▎     let s = \"hi\";", "after"]
+    );
+    let style = |line: usize, content: &str| {
+        lines[line]
+            .spans
+            .iter()
+            .find(|s| s.content == content)
+            .unwrap()
+            .style
+    };
+    assert_eq!(style(0, "fn").fg, Some(Color::Magenta));
+    assert_eq!(style(0, "// entry").fg, Some(Color::DarkGray));
+    assert_eq!(style(1, "\"hi\"").fg, Some(Color::Green));
+
+    // Code keeps its spaces and wraps at the exact width
+    let wrapped = markdown::render("```\nabcdefghij klmnop\n```", 10, &Theme::default());
+    assert_eq!(texts(&wrapped), ["▎ abcdefgh", "▎ ij klmno", "▎ p"]);
+}
+
+#[test]
+fn test_condensed() {
+    let lines = markdown::condensed("# Title\n\nfirst\nsecond\nthird", 40, 3, &Theme::default());
+    assert_eq!(texts(&lines), ["Title", "first", "second…"]);
+    assert_eq!(
+        texts(&markdown::condensed("short", 40, 3, &Theme::default())),
+        ["short"]
+    );
+}
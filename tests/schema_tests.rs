@@ -0,0 +1,149 @@
+use captains_log::journal::{Journal, JournalEntry};
+use captains_log::jsonl_journal::JsonlJournal;
+use captains_log::schema::{self, CURRENT_VERSION};
+use chrono::{TimeZone, Utc};
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+fn fixtures(version: u64) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/schema")
+        .join(format!("v{}", version))
+}
+
+fn expected() -> Vec<JournalEntry> {
+    vec![
+        JournalEntry {
+            timestamp: Utc.timestamp_opt(1_705_311_000, 123_456_789).unwrap(),
+            content: "Planned the week\nwith a second line".to_string(),
+        },
+        JournalEntry {
+            timestamp: Utc.with_ymd_and_hms(2024, 1, 16, 18, 5, 0).unwrap(),
+            content: "Café visit ✓ #life".to_string(),
+        },
+    ]
+}
+
+fn sorted(mut entries: Vec<JournalEntry>) -> Vec<JournalEntry> {
+    entries.sort_by_key(|e| e.timestamp);
+    entries
+}
+
+/// Contents of every file directly in `dir`, in name order.
+fn contents(dir: &Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .map(|e| e.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    files.retain(|f| f.extension().is_some_and(|e| e == "json" || e == "jsonl"));
+    files.sort();
+    Ok(files
+        .iter()
+        .map(std::fs::read_to_string)
+        .collect::<Result<_, _>>()?)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(to)?;
+    for file in std::fs::read_dir(from)? {
+        let file = file?;
+        std::fs::copy(file.path(), to.join(file.file_name()))?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_every_version_loads() -> Result<(), Box<dyn std::error::Error>> {
+    for version in 1..=CURRENT_VERSION {
+        let files = Journal {
+            path: fixtures(version).join("journals"),
+        };
+        let lines = JsonlJournal {
+            path: fixtures(version).join("journal_log"),
+        };
+        assert_eq!(sorted(files.load_all()?), expected(), "v{} files", version);
+        assert_eq!(lines.load_all()?, expected(), "v{} jsonl", version);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_current_version_round_trips() -> Result<(), Box<dyn std::error::Error>> {
+    let current = fixtures(CURRENT_VERSION);
+    let written: Vec<String> = expected()
+        .iter()
+        .map(schema::encode)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(contents(&current.join("journals"))?, written);
+    assert_eq!(
+        contents(&current.join("journal_log"))?,
+        [written.join("\n") + "\n"]
+    );
+    for line in &written {
+        let (entry, version) = schema::decode(line)?;
+        assert_eq!(version, CURRENT_VERSION);
+        assert_eq!(&schema::encode(&entry)?, line);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_upgrade_rewrites_old_versions() -> Result<(), Box<dyn std::error::Error>> {
+    for version in 1..CURRENT_VERSION {
+        let dir = tempdir()?;
+        copy_dir(
+            &fixtures(version).join("journals"),
+            &dir.path().join("journals"),
+        )?;
+        copy_dir(
+            &fixtures(version).join("journal_log"),
+            &dir.path().join("journal_log"),
+        )?;
+        let files = Journal {
+            path: dir.path().join("journals"),
+        };
+        let lines = JsonlJournal {
+            path: dir.path().join("journal_log"),
+        };
+
+        assert_eq!(files.upgrade()?, 2);
+        assert_eq!(lines.upgrade()?, 2);
+        let current = fixtures(CURRENT_VERSION);
+        assert_eq!(contents(&files.path)?, contents(&current.join("journals"))?);
+        assert_eq!(
+            contents(&lines.path)?[0],
+            contents(&current.join("journal_log"))?[0]
+        );
+        assert_eq!(lines.index()?.months["2024-01"].entries, 2);
+        // Nothing left to do the second time
+        assert_eq!(files.upgrade()?, 0);
+        assert_eq!(lines.upgrade()?, 0);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_newer_versions_are_reported_not_dropped() -> Result<(), Box<dyn std::error::Error>> {
+    let future = r#"{"version":99,"timestamp":"2030-01-01T00:00:00Z","content":"?"}"#;
+    let error = schema::decode(future).unwrap_err();
+    assert!(error.to_string().contains("newer"));
+    assert!(
+        schema::decode(r#"{"version":"two","timestamp":"2030-01-01T00:00:00Z","content":"?"}"#)
+            .is_err()
+    );
+
+    let dir = tempdir()?;
+    std::fs::write(dir.path().join("future.json"), future)?;
+    let journal = Journal {
+        path: dir.path().to_path_buf(),
+    };
+    let loaded = journal.load_checked()?;
+    assert!(loaded.entries.is_empty());
+    assert_eq!(loaded.problems.len(), 1);
+    // Upgrading leaves records it does not understand alone
+    assert_eq!(journal.upgrade()?, 0);
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("future.json"))?,
+        future
+    );
+    Ok(())
+}
@@ -0,0 +1,224 @@
+use captains_log::activity::ActivitySample;
+use captains_log::journal::{self, Backend, Journal, JournalConfig, JournalEntry, JournalStore};
+use captains_log::sqlite_journal::SqliteJournal;
+use chrono::{TimeZone, Utc};
+use tempfile::tempdir;
+
+fn entry(secs: i64, nanos: u32, content: &str) -> JournalEntry {
+    JournalEntry {
+        timestamp: Utc.timestamp_opt(secs, nanos).unwrap(),
+        content: content.to_string(),
+    }
+}
+
+#[test]
+fn test_round_trip_is_lossless() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let db = SqliteJournal {
+        path: dir.path().join("log.db"),
+    };
+    let entries = vec![
+        entry(1_700_000_000, 123_456_789, "first line\nsecond line"),
+        entry(1_700_000_060, 0, "unicode ✓ and \"quotes\""),
+    ];
+    for e in entries.iter().rev() {
+        db.save(e)?;
+    }
+    assert_eq!(db.load_all()?, entries);
+    Ok(())
+}
+
+#[test]
+fn test_tags() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(
+        journal::tags("# Heading\nfixed #Bug in #parser, see ## and #bug"),
+        vec!["bug", "parser"]
+    );
+
+    let dir = tempdir()?;
+    let db = SqliteJournal {
+        path: dir.path().join("log.db"),
+    };
+    db.save(&entry(1_700_000_000, 0, "refactor #parser"))?;
+    db.save(&entry(1_700_000_060, 0, "lunch"))?;
+    db.save(&entry(1_700_000_120, 0, "#Parser tests"))?;
+    let tagged = db.by_tag("parser")?;
+    assert_eq!(tagged.len(), 2);
+    assert_eq!(tagged[0].content, "refactor #parser");
+    Ok(())
+}
+
+#[test]
+fn test_full_text_search() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let db = SqliteJournal {
+        path: dir.path().join("log.db"),
+    };
+    db.save(&entry(1_700_000_000, 0, "Debugging the renderer"))?;
+    db.save(&entry(1_700_000_060, 0, "Reviewed pull requests"))?;
+    db.save(&entry(1_700_000_120, 0, "renderer is fast now"))?;
+
+    let found = db.search("renderer")?;
+    assert_eq!(found.len(), 2);
+    assert!(
+        found
+            .iter()
+            .all(|e| e.content.to_lowercase().contains("renderer"))
+    );
+    assert_eq!(db.search("debug*")?.len(), 1);
+    assert!(db.search("missing")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_default_search_matches_all_words() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let journal = Journal {
+        path: dir.path().to_path_buf(),
+    };
+    journal.save(&entry(1_700_000_000, 0, "Fixed the Parser bug"))?;
+    assert_eq!(journal.search("parser fixed")?.len(), 1);
+    assert!(journal.search("parser lexer")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_import_is_idempotent() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let db = SqliteJournal {
+        path: dir.path().join("log.db"),
+    };
+    let entries = vec![
+        ("a.json".to_string(), entry(1_700_000_000, 5, "a #x")),
+        ("b.json".to_string(), entry(1_700_000_060, 0, "b")),
+    ];
+    let first = db.import(&entries)?;
+    assert_eq!((first.migrated, first.skipped), (2, 0));
+    let second = db.import(&entries)?;
+    assert_eq!((second.migrated, second.skipped), (0, 2));
+    assert_eq!(
+        db.load_all()?,
+        entries.into_iter().map(|(_, e)| e).collect::<Vec<_>>()
+    );
+    assert_eq!(db.by_tag("x")?.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_import_keeps_identical_entries() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let files = Journal {
+        path: dir.path().join("journals"),
+    };
+    let twice = entry(1_700_000_000, 0, "standup");
+    files.save(&twice)?;
+    files.save(&twice)?;
+    let db = SqliteJournal {
+        path: dir.path().join("log.db"),
+    };
+    // Saved from the app, then imported: the same entry, not a third one
+    db.save(&twice)?;
+
+    let named = files.load_named()?;
+    assert_eq!(named.len(), 2);
+    let result = db.import(&named)?;
+    assert_eq!((result.migrated, result.skipped), (1, 1));
+    assert_eq!(db.load_all()?, vec![twice.clone(), twice.clone()]);
+    assert_eq!(db.import(&named)?.skipped, 2);
+    assert_eq!(db.load_all()?.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_migrate_from_files() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let files = Journal {
+        path: dir.path().join("journals"),
+    };
+    files.save(&entry(1_700_000_000, 42, "from the file store"))?;
+    let db = SqliteJournal {
+        path: dir.path().join("log.db"),
+    };
+    let result = journal::migrate(&files, &db)?;
+    assert_eq!(result.migrated, 1);
+    assert_eq!(db.load_all()?, files.load_all()?);
+    Ok(())
+}
+
+#[test]
+fn test_schema_version() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let db = SqliteJournal {
+        path: dir.path().join("log.db"),
+    };
+    assert_eq!(db.schema_version()?, 1);
+    // Reopening an up-to-date database leaves it alone.
+    db.save(&entry(1_700_000_000, 0, "kept"))?;
+    assert_eq!(db.schema_version()?, 1);
+    assert_eq!(db.load_all()?.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_activity() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let db = SqliteJournal {
+        path: dir.path().join("log.db"),
+    };
+    let minute = Utc.with_ymd_and_hms(2024, 3, 1, 9, 30, 0).unwrap();
+    let samples = vec![
+        ActivitySample {
+            minute,
+            keystrokes: 120,
+        },
+        ActivitySample {
+            minute: minute + chrono::TimeDelta::minutes(1),
+            keystrokes: 80,
+        },
+    ];
+    assert_eq!(db.import_activity(&samples)?, 2);
+    // Re-importing the same minute replaces rather than duplicates it.
+    db.import_activity(&samples[..1])?;
+    assert_eq!(db.load_activity()?, samples);
+
+    // A restart within a minute logs it twice; both halves count
+    let restarted = [
+        samples[0],
+        samples[1],
+        ActivitySample {
+            minute,
+            keystrokes: 30,
+        },
+    ];
+    assert_eq!(db.import_activity(&restarted)?, 2);
+    assert_eq!(db.load_activity()?[0].keystrokes, 150);
+    Ok(())
+}
+
+#[test]
+fn test_activity_is_recorded_in_the_database() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let config = JournalConfig {
+        backend: Backend::Sqlite,
+        path: Some(dir.path().join("log.db")),
+        ..JournalConfig::default()
+    };
+    let store = config.open_activity();
+    let minute = Utc.with_ymd_and_hms(2024, 3, 1, 9, 30, 0).unwrap();
+    // A restart within the minute adds to it
+    for keystrokes in [30, 12] {
+        store.append(&ActivitySample { minute, keystrokes })?;
+    }
+    let db = SqliteJournal {
+        path: dir.path().join("log.db"),
+    };
+    assert_eq!(
+        db.load_activity()?,
+        vec![ActivitySample {
+            minute,
+            keystrokes: 42
+        }]
+    );
+    assert_eq!(store.load_all()?, db.load_activity()?);
+    Ok(())
+}
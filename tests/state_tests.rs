@@ -0,0 +1,421 @@
+use captains_log::App;
+use captains_log::activity::{ActivitySample, minute_of};
+use captains_log::commands::{Action, PaletteMode};
+use captains_log::config::Config;
+use captains_log::export::Format;
+use captains_log::journal::{Backend, JournalEntry, JournalStore};
+use captains_log::sqlite_journal::SqliteJournal;
+use captains_log::state::{InputMode, MAX_INPUT_ROWS, Screen};
+use captains_log::templates::Template;
+use captains_log::watch;
+use chrono::{NaiveDate, NaiveTime, Utc};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::time::Duration;
+use tempfile::tempdir;
+
+#[test]
+fn test_failed_saves_are_kept_and_retried() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let journal_path = dir.path().join("journal_log");
+    // A plain file where the journal directory should be makes every save fail
+    std::fs::write(&journal_path, "")?;
+    let mut config = Config::default();
+    config.journal.backend = Backend::Jsonl;
+    config.journal.path = Some(journal_path.clone());
+    let mut app = App::with_config(config);
+
+    app.add_log("first".to_string());
+    app.add_log("second".to_string());
+    assert_eq!(app.logs.len(), 2);
+    assert_eq!(app.unsaved.len(), 2);
+    assert!(app.error.as_deref().unwrap().contains("2 PENDING"));
+
+    std::fs::remove_file(&journal_path)?;
+    app.retry_unsaved();
+    assert!(app.unsaved.is_empty());
+    assert!(app.error.is_none());
+    let saved = app.journal.load_all()?;
+    assert_eq!(saved.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_reload_picks_up_entries_from_other_writers() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let mut config = Config::default();
+    config.journal.backend = Backend::Jsonl;
+    config.journal.path = Some(dir.path().join("journal_log"));
+    let mut app = App::with_config(config.clone());
+    let (tx, rx) = std::sync::mpsc::channel();
+    let _watcher = watch::watch_journal(&config.journal.location(), tx)?;
+
+    app.add_log("from the tui".to_string());
+    rx.recv_timeout(Duration::from_secs(5))?;
+    // Another instance writes to the same journal
+    let other = App::with_config(config);
+    other.journal.save(&JournalEntry {
+        timestamp: Utc::now(),
+        content: "from the cli".to_string(),
+    })?;
+    rx.recv_timeout(Duration::from_secs(5))?;
+
+    app.reload_journal();
+    let contents: Vec<&str> = app.logs.iter().map(|e| e.content.as_str()).collect();
+    assert_eq!(contents, ["from the tui", "from the cli"]);
+    assert_eq!(app.progress.journal_entries, 2);
+    Ok(())
+}
+
+#[test]
+fn test_reload_keeps_the_selected_entry() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let mut config = Config::default();
+    config.journal.backend = Backend::Jsonl;
+    config.journal.path = Some(dir.path().join("journal_log"));
+    let mut app = App::with_config(config.clone());
+    app.add_log("first".to_string());
+    app.add_log("second".to_string());
+    app.select_previous();
+    assert_eq!(app.selected_entry().unwrap().content, "second");
+
+    // Another process adds an older entry, which shifts the indices
+    let other = App::with_config(config);
+    other.journal.save(&JournalEntry {
+        timestamp: app.logs[0].timestamp - chrono::TimeDelta::minutes(5),
+        content: "older".to_string(),
+    })?;
+    app.reload_journal();
+    assert_eq!(app.logs.len(), 3);
+    assert_eq!(app.selected_entry().unwrap().content, "second");
+    Ok(())
+}
+
+#[test]
+fn test_database_watch_ignores_other_files() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let db = SqliteJournal {
+        path: dir.path().join("captains_log.db"),
+    };
+    db.connect()?;
+    let (tx, rx) = std::sync::mpsc::channel();
+    let _watcher = watch::watch_journal(&db.path, tx)?;
+
+    // Goals and activity files live next to the database
+    std::fs::write(dir.path().join("goals.json"), "[]")?;
+    assert!(rx.recv_timeout(Duration::from_millis(300)).is_err());
+    db.save(&JournalEntry {
+        timestamp: Utc::now(),
+        content: "from the cli".to_string(),
+    })?;
+    rx.recv_timeout(Duration::from_secs(5))?;
+    Ok(())
+}
+
+#[test]
+fn test_submit_multiline_entry() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let mut config = Config::default();
+    config.journal.backend = Backend::Jsonl;
+    config.journal.path = Some(dir.path().join("journal_log"));
+    let mut app = App::with_config(config);
+
+    app.toggle_multiline();
+    assert!(app.enter_adds_line());
+    app.textarea.insert_str("first line");
+    app.textarea.insert_newline();
+    app.textarea.insert_str("second line");
+    app.submit_entry();
+    assert_eq!(app.logs[0].content, "first line\nsecond line");
+    assert_eq!(
+        app.journal.load_all()?[0].content,
+        "first line\nsecond line"
+    );
+    assert_eq!(app.textarea.lines(), [""]);
+
+    // Blank input is not saved
+    app.textarea.insert_newline();
+    app.submit_entry();
+    assert_eq!(app.logs.len(), 1);
+
+    // Saving from the full-screen editor returns to the journal
+    app.toggle_multiline();
+    app.toggle_editor();
+    assert_eq!(app.screen, Screen::Editor);
+    assert!(app.enter_adds_line());
+    app.textarea.insert_str("long entry");
+    app.submit_entry();
+    assert_eq!(app.screen, Screen::Journal);
+    assert!(!app.enter_adds_line());
+    assert_eq!(app.logs.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_resize_input_is_clamped() {
+    let mut app = App::with_config(Config::default());
+    app.resize_input(-5);
+    assert_eq!(app.input_rows, 1);
+    app.resize_input(3);
+    assert_eq!(app.input_rows, 4);
+    app.resize_input(100);
+    assert_eq!(app.input_rows, MAX_INPUT_ROWS);
+}
+
+#[test]
+fn test_save_edited_entry() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let mut config = Config::default();
+    config.journal.backend = Backend::Jsonl;
+    config.journal.path = Some(dir.path().join("journal_log"));
+    let mut app = App::with_config(config);
+
+    app.textarea.insert_str("draft");
+    app.save_edited(Ok(None));
+    assert!(app.logs.is_empty());
+    assert_eq!(app.textarea.lines(), ["draft"]);
+
+    app.save_edited(Err(std::io::Error::other("no editor")));
+    assert!(app.error.as_deref().unwrap().contains("no editor"));
+
+    app.save_edited(Ok(Some("written elsewhere\nover two lines".to_string())));
+    assert_eq!(
+        app.journal.load_all()?[0].content,
+        "written elsewhere\nover two lines"
+    );
+    assert_eq!(app.textarea.lines(), [""]);
+    Ok(())
+}
+
+#[test]
+fn test_select_and_open_entry() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let mut config = Config::default();
+    config.journal.backend = Backend::Jsonl;
+    config.journal.path = Some(dir.path().join("journal_log"));
+    let mut app = App::with_config(config);
+    app.open_detail();
+    assert_eq!(app.screen, Screen::Journal);
+
+    app.add_log("# First".to_string());
+    app.add_log("second".to_string());
+    assert!(app.browsing());
+    app.select_previous();
+    assert_eq!(app.selected_entry().unwrap().content, "second");
+    app.select_previous();
+    app.select_previous();
+    assert_eq!(app.selected, Some(0));
+    app.select_next();
+    app.select_next();
+    assert_eq!(app.selected, None);
+
+    app.select_previous();
+    app.open_detail();
+    assert_eq!(app.screen, Screen::Detail);
+    assert!(!app.browsing());
+    app.close_detail();
+    assert_eq!(app.screen, Screen::Journal);
+
+    app.textarea.insert_str("typing");
+    assert!(!app.browsing());
+    Ok(())
+}
+
+#[test]
+fn test_templates_fill_the_input() {
+    let config = Config {
+        templates: vec![
+            Template {
+                name: "Quick".to_string(),
+                body: "{entries} entries so far".to_string(),
+                at: None,
+            },
+            Template {
+                name: "End of day".to_string(),
+                body: "## {date}\nShipped: ".to_string(),
+                at: NaiveTime::from_hms_opt(17, 30, 0),
+            },
+        ],
+        ..Config::default()
+    };
+    let mut app = App::with_config(config);
+    app.progress.journal_entries = 3;
+
+    app.open_picker();
+    app.move_picker(-1);
+    assert_eq!(app.picker, Some(0));
+    app.apply_picked();
+    assert_eq!(app.picker, None);
+    assert_eq!(app.textarea.lines(), ["3 entries so far"]);
+    assert_eq!(app.screen, Screen::Journal);
+
+    // A scheduled template does not replace text being written
+    let day = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+    app.last_prompt_check = day.and_hms_opt(17, 29, 0).unwrap();
+    app.check_prompts(day.and_hms_opt(17, 30, 0).unwrap());
+    assert_eq!(app.picker, Some(1));
+    assert_eq!(app.textarea.lines(), ["3 entries so far"]);
+
+    // With an empty input it opens in the editor, filled in
+    app.picker = None;
+    app.textarea = Default::default();
+    app.last_prompt_check = day.pred_opt().unwrap().and_hms_opt(17, 29, 0).unwrap();
+    app.check_prompts(day.pred_opt().unwrap().and_hms_opt(17, 31, 0).unwrap());
+    assert_eq!(app.screen, Screen::Editor);
+    assert_eq!(app.textarea.lines()[1], "Shipped: ");
+    assert!(app.textarea.lines()[0].starts_with("## 20"));
+}
+
+#[test]
+fn test_palette_runs_commands_and_searches() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let mut config = Config::default();
+    config.journal.path = Some(dir.path().join("journals"));
+    let mut app = App::with_config(config);
+    for content in ["fixed the renderer", "lunch", "renderer is fast now"] {
+        app.add_log(content.to_string());
+    }
+
+    app.open_palette(PaletteMode::Commands);
+    for c in "alerts".chars() {
+        app.palette_input(c);
+    }
+    let action = app.choose_command();
+    assert_eq!(action, Some(Action::ToggleAlerts));
+    app.run(Action::ToggleAlerts);
+    assert!(!app.alerts_enabled);
+    assert!(app.palette.is_none());
+
+    app.run(Action::Search);
+    for c in "RENDERER".chars() {
+        app.palette_input(c);
+    }
+    assert_eq!(app.choose_command(), None);
+    assert_eq!(app.visible_logs(), [0, 2]);
+    app.select_previous();
+    assert_eq!(app.selected, Some(2));
+    app.select_previous();
+    assert_eq!(app.selected, Some(0));
+    app.select_next();
+    app.select_next();
+    assert_eq!(app.selected, None);
+
+    app.clear_selection();
+    assert!(app.search.is_none());
+    assert_eq!(app.visible_logs().len(), 3);
+
+    let path = dir.path().join("out.csv");
+    app.export_to(Format::Csv, &path);
+    assert!(
+        app.notice
+            .as_deref()
+            .unwrap()
+            .starts_with("EXPORTED 3 ENTRIES")
+    );
+    assert_eq!(std::fs::read_to_string(&path)?.lines().count(), 4);
+    Ok(())
+}
+
+fn press(app: &mut App, code: KeyCode) -> Option<Action> {
+    app.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+#[test]
+fn test_keys_follow_the_keymap() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let mut config = Config::default();
+    config.journal.path = Some(dir.path().join("journals"));
+    let mut app = App::with_config(config.clone());
+    app.add_log("earlier".to_string());
+
+    // Without modes, characters always type; arrows browse while the input is empty
+    press(&mut app, KeyCode::F(1));
+    assert!(app.help);
+    press(&mut app, KeyCode::Char('x'));
+    assert!(!app.help);
+    press(&mut app, KeyCode::Up);
+    assert_eq!(app.selected, Some(0));
+    press(&mut app, KeyCode::Esc);
+    press(&mut app, KeyCode::Char('?'));
+    press(&mut app, KeyCode::Char('h'));
+    assert!(!app.help);
+    assert_eq!(app.textarea.lines(), ["?h"]);
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.logs.last().unwrap().content, "?h");
+    assert_eq!(press(&mut app, KeyCode::F(7)), Some(Action::ExternalEditor));
+
+    // Vim style starts in normal mode
+    config.keys = toml::from_str("style = \"vim\"\n[normal]\nq = \"quit\"")?;
+    let mut app = App::with_config(config);
+    press(&mut app, KeyCode::Char('k'));
+    assert_eq!(app.selected, Some(1));
+    assert!(app.textarea.is_empty());
+    press(&mut app, KeyCode::Char('i'));
+    assert_eq!(app.input_mode, InputMode::Insert);
+    press(&mut app, KeyCode::Char('q'));
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.input_mode, InputMode::Normal);
+    assert_eq!(app.textarea.lines(), ["q"]);
+    press(&mut app, KeyCode::Char(':'));
+    assert!(app.palette.is_some());
+    press(&mut app, KeyCode::Esc);
+    press(&mut app, KeyCode::Char('q'));
+    assert!(app.should_quit);
+    Ok(())
+}
+
+#[test]
+fn test_saving_keeps_unrelated_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let journal_path = dir.path().join("journal_log");
+    let mut config = Config::default();
+    config.journal.backend = Backend::Jsonl;
+    config.journal.path = Some(journal_path.clone());
+    let mut app = App::with_config(config);
+
+    app.error = Some("NOT WATCHING JOURNAL: no inotify".to_string());
+    app.add_log("saved".to_string());
+    assert_eq!(
+        app.error.as_deref(),
+        Some("NOT WATCHING JOURNAL: no inotify")
+    );
+
+    // A failed save takes over the footer, and its retry clears only that
+    std::fs::rename(&journal_path, dir.path().join("moved"))?;
+    std::fs::write(&journal_path, "")?;
+    app.add_log("pending".to_string());
+    assert!(app.error.as_deref().unwrap().contains("1 PENDING"));
+    std::fs::remove_file(&journal_path)?;
+    app.error = Some("FAILED TO SAVE GOALS: disk full".to_string());
+    app.retry_unsaved();
+    assert!(app.unsaved.is_empty());
+    assert_eq!(
+        app.error.as_deref(),
+        Some("FAILED TO SAVE GOALS: disk full")
+    );
+    Ok(())
+}
+
+#[test]
+fn test_a_minute_logged_twice_counts_once() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let mut config = Config::default();
+    config.journal.backend = Backend::Sqlite;
+    config.journal.path = Some(dir.path().join("log.db"));
+    let minute = minute_of(Utc::now());
+    config.journal.open_activity().append(&ActivitySample {
+        minute,
+        keystrokes: 30,
+    })?;
+    let mut app = App::with_config(config);
+    assert_eq!(app.progress.active_minutes, 1);
+
+    // Restarted within the same minute: the minute is logged again
+    app.current_minute = minute;
+    app.minute_keystrokes = 12;
+    app.flush_activity();
+    assert_eq!(app.progress.active_minutes, 1);
+    assert_eq!(app.progress.keystrokes, 30);
+    assert_eq!(app.history.len(), 1);
+    assert_eq!(app.history[0].keystrokes, 42);
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use captains_log::activity::ActivitySample;
+use captains_log::flow::FlowPeriod;
+use captains_log::journal::JournalEntry;
+use captains_log::stats::{Period, Records, build_report, change_percent, summarize};
+use chrono::{NaiveDate, TimeZone, Utc};
+
+fn sample(day: u32, hour: u32, min: u32, keystrokes: u32) -> ActivitySample {
+    ActivitySample {
+        minute: Utc.with_ymd_and_hms(2025, 11, day, hour, min, 0).unwrap(),
+        keystrokes,
+    }
+}
+
+#[test]
+fn test_period_ranges() {
+    // Friday
+    let today = NaiveDate::from_ymd_opt(2025, 11, 21).unwrap();
+    let date = |d| NaiveDate::from_ymd_opt(2025, 11, d).unwrap();
+
+    assert_eq!(Period::Day.range(today), (today, date(22)));
+    assert_eq!(Period::Week.range(today), (date(17), date(24)));
+    assert_eq!(Period::Week.previous_range(today), (date(10), date(17)));
+    assert_eq!(
+        Period::Month.range(today),
+        (date(1), NaiveDate::from_ymd_opt(2025, 12, 1).unwrap())
+    );
+}
+
+#[test]
+fn test_summarize_streak_and_wpm() {
+    let samples = vec![
+        sample(21, 9, 0, 100),
+        sample(21, 9, 1, 300),
+        sample(21, 9, 2, 200),
+        sample(21, 9, 3, 0),
+        sample(21, 9, 10, 50),
+        sample(21, 9, 11, 50),
+    ];
+    let summary = summarize(&samples, 2);
+
+    assert_eq!(summary.active_minutes, 5);
+    assert_eq!(summary.keystrokes, 700);
+    assert_eq!(summary.avg_wpm, 28);
+    assert_eq!(summary.peak_wpm, 60);
+    assert_eq!(summary.longest_streak_minutes, 3);
+    assert_eq!(summary.entries, 2);
+}
+
+#[test]
+fn test_restart_within_a_minute_counts_it_once() {
+    // Quitting at 9:01:20 flushes the minute; the restart writes it again
+    let samples = vec![
+        sample(21, 9, 0, 100),
+        sample(21, 9, 1, 40),
+        sample(21, 9, 1, 60),
+        sample(21, 9, 2, 100),
+    ];
+    let summary = summarize(&samples, 0);
+
+    assert_eq!(summary.active_minutes, 3);
+    assert_eq!(summary.keystrokes, 300);
+    assert_eq!(summary.peak_wpm, 20);
+    assert_eq!(summary.longest_streak_minutes, 3);
+}
+
+#[test]
+fn test_build_week_report_with_comparison() {
+    let today = NaiveDate::from_ymd_opt(2025, 11, 21).unwrap();
+    let samples = vec![
+        // Previous week
+        sample(14, 10, 0, 100),
+        // This week: Monday and Friday
+        sample(17, 9, 0, 100),
+        sample(17, 9, 1, 100),
+        sample(21, 15, 30, 50),
+    ];
+    let entries = vec![JournalEntry {
+        timestamp: Utc.with_ymd_and_hms(2025, 11, 21, 16, 0, 0).unwrap(),
+        content: "Shipped it".to_string(),
+    }];
+
+    let flows = vec![FlowPeriod {
+        start: Utc.with_ymd_and_hms(2025, 11, 17, 8, 30, 0).unwrap(),
+        end: Utc.with_ymd_and_hms(2025, 11, 17, 9, 15, 0).unwrap(),
+        keystrokes: 5000,
+    }];
+    let records = Records {
+        samples: &samples,
+        entries: &entries,
+        flows: &flows,
+    };
+
+    let report = build_report(Period::Week, today, &Utc, records);
+
+    assert_eq!(report.days.len(), 7);
+    assert_eq!(report.days[0].summary.active_minutes, 2);
+    assert_eq!(report.days[0].summary.flow_minutes, 45);
+    assert_eq!(report.days[4].summary.entries, 1);
+    assert_eq!(report.summary.active_minutes, 3);
+    assert_eq!(report.previous.active_minutes, 1);
+    assert_eq!(report.hourly[9], 200);
+    assert_eq!(report.hourly[15], 50);
+    assert_eq!(
+        change_percent(
+            report.summary.active_minutes,
+            report.previous.active_minutes
+        ),
+        Some(200)
+    );
+
+    let text = report.to_text();
+    assert!(text.contains("WEEK 2025-11-17 .. 2025-11-23"));
+    assert!(text.contains("+200%"));
+
+    let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["period"], "week");
+    assert_eq!(json["days"][0]["active_minutes"], 2);
+}
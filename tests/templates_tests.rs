@@ -0,0 +1,71 @@
+use captains_log::config::Config;
+use captains_log::templates::{self, Template};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2025, 3, day)
+        .unwrap()
+        .and_hms_opt(hour, minute, 0)
+        .unwrap()
+}
+
+#[test]
+fn test_expand_placeholders() {
+    let values = [
+        ("date", "2025-03-01".to_string()),
+        ("wpm", "72".to_string()),
+    ];
+    assert_eq!(
+        templates::expand("## {date}\n{wpm} WPM, {unknown} {{wpm}} }", &values),
+        "## 2025-03-01\n72 WPM, {unknown} {wpm} }"
+    );
+    assert_eq!(templates::expand("{", &values), "{");
+}
+
+#[test]
+fn test_templates_from_config() -> Result<(), Box<dyn std::error::Error>> {
+    let config: Config = toml::from_str(
+        r#"
+        [[templates]]
+        name = "End of day"
+        body = "Shipped: "
+        at = "17:30"
+
+        [[templates]]
+        name = "Standup"
+        body = "Yesterday:\nToday:"
+        "#,
+    )?;
+    assert_eq!(config.templates.len(), 2);
+    assert_eq!(config.templates[0].at, NaiveTime::from_hms_opt(17, 30, 0));
+    assert_eq!(config.templates[1].at, None);
+
+    let bad = toml::from_str::<Config>("[[templates]]\nname = \"x\"\nbody = \"\"\nat = \"5pm\"");
+    assert!(bad.unwrap_err().to_string().contains("not a time"));
+    Ok(())
+}
+
+#[test]
+fn test_due_once_when_time_passes() {
+    let list = vec![
+        Template {
+            name: "Anytime".to_string(),
+            body: String::new(),
+            at: None,
+        },
+        Template {
+            name: "Night".to_string(),
+            body: String::new(),
+            at: NaiveTime::from_hms_opt(23, 59, 30),
+        },
+    ];
+    assert!(templates::due(&list, at(1, 23, 58), at(1, 23, 59)).is_none());
+    assert_eq!(
+        templates::due(&list, at(1, 23, 59), at(2, 0, 0))
+            .unwrap()
+            .name,
+        "Night"
+    );
+    assert!(templates::due(&list, at(2, 0, 0), at(2, 0, 1)).is_none());
+    assert!(templates::due(&templates::defaults(), at(1, 0, 0), at(1, 23, 0)).is_none());
+}
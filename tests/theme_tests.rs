@@ -0,0 +1,123 @@
+use captains_log::config::Config;
+use captains_log::theme::{self, PRESETS, Theme};
+use ratatui::style::{Color, Modifier, Style};
+
+#[test]
+fn test_parse_style() {
+    assert_eq!(
+        theme::parse_style("black bold on magenta").unwrap(),
+        Style::default()
+            .fg(Color::Black)
+            .bg(Color::Magenta)
+            .add_modifier(Modifier::BOLD)
+    );
+    assert_eq!(
+        theme::parse_style("#268bd2 underlined").unwrap(),
+        Style::default()
+            .fg(Color::Rgb(0x26, 0x8b, 0xd2))
+            .add_modifier(Modifier::UNDERLINED)
+    );
+    assert_eq!(
+        theme::parse_style("on 236").unwrap(),
+        Style::default().bg(Color::Indexed(236))
+    );
+    assert_eq!(theme::parse_style("").unwrap(), Style::default());
+    assert!(theme::parse_style("blinking").is_err());
+    assert!(theme::parse_style("white on").is_err());
+}
+
+#[test]
+fn test_presets() {
+    for name in PRESETS {
+        assert!(Theme::preset(name).is_some(), "{}", name);
+    }
+    assert!(Theme::preset("neon").is_none());
+
+    // The default keeps the colors the TUI has always had
+    let cyberpunk = Theme::default();
+    assert_eq!(cyberpunk.header.fg, Some(Color::Green));
+    assert_eq!(cyberpunk.border.fg, Some(Color::Cyan));
+    assert_eq!(cyberpunk.alert.bg, Some(Color::Red));
+
+    let monochrome = Theme::preset("monochrome").unwrap();
+    for style in [
+        monochrome.header,
+        monochrome.border,
+        monochrome.selection,
+        monochrome.alert,
+        monochrome.very_fast,
+        monochrome.keyword,
+    ] {
+        assert_eq!((style.fg, style.bg), (None, None));
+    }
+    assert!(
+        monochrome
+            .selection
+            .add_modifier
+            .contains(Modifier::REVERSED)
+    );
+}
+
+#[test]
+fn test_intensity_thresholds() {
+    let mut theme = Theme::default();
+    assert_eq!(theme.intensity(60), theme.slow);
+    assert_eq!(theme.intensity(180), theme.medium);
+    assert_eq!(theme.intensity(400), theme.fast);
+    assert_eq!(theme.intensity(540), theme.very_fast);
+
+    theme.thresholds = [30, 60, 90];
+    assert_eq!(theme.intensity(60), theme.fast);
+}
+
+#[test]
+fn test_user_themes() -> Result<(), Box<dyn std::error::Error>> {
+    let config: Config = toml::from_str(
+        r##"
+        [theme]
+        name = "night"
+        thresholds = [120, 240, 480]
+
+        [themes.night]
+        base = "solarized"
+        header = "lightred bold"
+        border = "#ffffff"
+        "##,
+    )?;
+    let theme = theme::build(&config.theme, &config.themes, false)?;
+    let solarized = Theme::preset("solarized").unwrap();
+    assert_eq!(theme.header, theme::parse_style("lightred bold")?);
+    assert_eq!(theme.border.fg, Some(Color::Rgb(255, 255, 255)));
+    assert_eq!(theme.muted, solarized.muted);
+    assert_eq!(theme.thresholds, [120, 240, 480]);
+
+    // NO_COLOR wins over any theme, but the thresholds still apply
+    let plain = theme::build(&config.theme, &config.themes, true)?;
+    assert_eq!(plain.header, Theme::preset("monochrome").unwrap().header);
+    assert_eq!(plain.thresholds, [120, 240, 480]);
+    Ok(())
+}
+
+#[test]
+fn test_bad_themes_are_rejected() -> Result<(), Box<dyn std::error::Error>> {
+    let error = |toml: &str| -> Result<String, Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("captains_log.toml");
+        std::fs::write(&path, toml)?;
+        Ok(Config::load(&path).unwrap_err().to_string())
+    };
+    assert!(error("[theme]\nname = \"neon\"")?.contains("name `neon` is not one of"));
+    assert!(error("[theme]\nthresholds = [300, 200, 400]")?.contains("increasing order"));
+    assert!(
+        error("[theme]\nname = \"mine\"\n[themes.mine]\nbase = \"neon\"")?.contains("base `neon`")
+    );
+    assert!(
+        error("[theme]\nname = \"mine\"\n[themes.mine]\nglow = \"red\"")?
+            .contains("unknown style `glow`")
+    );
+    assert!(
+        error("[theme]\nname = \"mine\"\n[themes.mine]\nheader = \"shiny\"")?
+            .contains("`shiny` is not a color")
+    );
+    Ok(())
+}
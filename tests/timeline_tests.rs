@@ -0,0 +1,109 @@
+use captains_log::activity::ActivitySample;
+use captains_log::timeline::{History, Zoom, axis_labels, buckets, wave_glyph, waveform};
+use chrono::{TimeDelta, TimeZone, Utc};
+use std::collections::VecDeque;
+
+fn at(hour: u32, min: u32, sec: u32) -> chrono::DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 11, 21, hour, min, sec).unwrap()
+}
+
+#[test]
+fn test_zoom_levels_clamp() {
+    assert_eq!(Zoom::OneMinute.zoom_in(), Zoom::OneMinute);
+    assert_eq!(Zoom::OneMinute.zoom_out(), Zoom::FifteenMinutes);
+    assert_eq!(Zoom::EightHours.zoom_out(), Zoom::Day);
+    assert_eq!(Zoom::Day.zoom_out(), Zoom::Day);
+    assert_eq!(Zoom::OneHour.pan_step(), TimeDelta::minutes(15));
+}
+
+#[test]
+fn test_count_between_uses_ticks_then_minutes() {
+    // 8 ticks of 250ms ending at 10:00:02, 4 keystrokes each
+    let ticks: VecDeque<u32> = std::iter::repeat_n(4, 8).collect();
+    let minutes = vec![
+        ActivitySample {
+            minute: at(9, 58, 0),
+            keystrokes: 60,
+        },
+        // Overlaps the ticks and must not be counted twice
+        ActivitySample {
+            minute: at(10, 0, 0),
+            keystrokes: 600,
+        },
+    ];
+    let history = History {
+        ticks: &ticks,
+        tick_len: TimeDelta::milliseconds(250),
+        ticks_end: at(10, 0, 2),
+        minutes: &minutes,
+    };
+
+    assert_eq!(history.count_between(at(10, 0, 0), at(10, 0, 2)), 32.0);
+    // Half a tick at each edge
+    let half = TimeDelta::milliseconds(125);
+    assert_eq!(
+        history.count_between(at(10, 0, 0) + half, at(10, 0, 1) + half),
+        16.0
+    );
+    // Half of the 09:58 minute is prorated in
+    assert_eq!(history.count_between(at(9, 58, 30), at(10, 0, 0)), 30.0);
+}
+
+#[test]
+fn test_buckets_downsample_to_rate() {
+    let ticks = VecDeque::new();
+    let minutes: Vec<ActivitySample> = (0..60)
+        .map(|m| ActivitySample {
+            minute: at(9, m, 0),
+            keystrokes: if m < 30 { 120 } else { 0 },
+        })
+        .collect();
+    let history = History {
+        ticks: &ticks,
+        tick_len: TimeDelta::milliseconds(250),
+        ticks_end: at(10, 0, 0),
+        minutes: &minutes,
+    };
+
+    let data = buckets(&history, at(10, 0, 0), Zoom::OneHour, 4);
+    // 120 keystrokes/min is 2/s in the first half hour
+    assert_eq!(data, vec![2, 2, 0, 0]);
+}
+
+#[test]
+fn test_axis_labels_quarter_marks() {
+    let axis = axis_labels(at(10, 0, 0), Zoom::OneHour, 60, &Utc);
+    assert_eq!(axis.chars().count(), 60);
+    assert!(axis.starts_with("09:00"));
+    assert!(axis.contains("09:30"));
+    assert!(axis.ends_with("10:00"));
+}
+
+#[test]
+fn test_wave_glyphs() {
+    assert_eq!(wave_glyph(1.0, true), '█');
+    assert_eq!(wave_glyph(1.0, false), '█');
+    assert_eq!(wave_glyph(0.9, true), '▇');
+    assert_eq!(wave_glyph(0.3, true), '▂');
+    assert_eq!(wave_glyph(0.2, true), ' ');
+    // The bottom half only has a half block
+    assert_eq!(wave_glyph(0.9, false), '▀');
+    assert_eq!(wave_glyph(0.4, false), ' ');
+    assert_eq!(wave_glyph(0.0, true), ' ');
+}
+
+#[test]
+fn test_waveform_grows_from_the_middle() {
+    let rows = |values: &[u32], height: usize| -> Vec<String> {
+        waveform(values, height)
+            .into_iter()
+            .map(|row| row.into_iter().collect())
+            .collect()
+    };
+    // 9 fills the height; 4 reaches just under half of each side
+    assert_eq!(rows(&[0, 9, 4], 4), [" █ ", " █▇", "─█▀", " █ "]);
+    // Larger values scale everything down
+    assert_eq!(rows(&[18, 9, 0], 4), ["█  ", "██ ", "██─", "█  "]);
+    assert_eq!(rows(&[], 3), ["", "", ""]);
+    assert!(waveform(&[5], 0).is_empty());
+}
@@ -0,0 +1,456 @@
+use captains_log::App;
+use captains_log::commands::Action;
+use captains_log::config::Config;
+use captains_log::goals::Progress;
+use captains_log::journal::{Backend, JournalEntry};
+use captains_log::state::{LayoutPreset, Screen};
+use captains_log::theme;
+use captains_log::ui;
+use chrono::{DateTime, Local, TimeDelta, TimeZone, Utc};
+use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::{Terminal, backend::TestBackend};
+use std::collections::VecDeque;
+use tempfile::TempDir;
+
+/// Noon on a fixed day, as the newest activity tick.
+fn noon() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 3, 14, 12, 0, 0).unwrap()
+}
+
+/// An app that draws the same whatever the clock, the files in the working
+/// directory or `NO_COLOR` say: no history, goals or entries beyond what the
+/// test adds, and the default colours.
+fn fixture(dir: &TempDir) -> App<'static> {
+    let mut config = Config::default();
+    config.journal.backend = Backend::Jsonl;
+    config.journal.path = Some(dir.path().join("journal_log"));
+    let mut app = App::with_config(config);
+    app.theme = theme::build(&app.config.theme, &app.config.themes, false).unwrap();
+    app.last_tick_at = noon();
+    app.activity_stream.clear();
+    app.history.clear();
+    app.flows.clear();
+    app.today = noon().date_naive();
+    app.progress = Progress::default();
+    app.goal_history.clear();
+    app.logs.clear();
+    app.focus_level = 0.0;
+    app
+}
+
+/// One tick per 250 ms over the last minute: 15 seconds idle, then 4, 8 and
+/// 12 keystrokes per second.
+fn ramp() -> VecDeque<u32> {
+    (0..240).map(|tick| tick / 60).collect()
+}
+
+fn entry(minutes_before_noon: i64, content: &str) -> JournalEntry {
+    JournalEntry {
+        timestamp: noon() - TimeDelta::minutes(minutes_before_noon),
+        content: content.to_string(),
+    }
+}
+
+/// The screen after drawing `app` on a `width` by `height` terminal.
+fn render(app: &App, width: u16, height: u16) -> Result<Buffer, Box<dyn std::error::Error>> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    terminal.draw(|f| ui::render(f, app))?;
+    Ok(terminal.backend().buffer().clone())
+}
+
+/// The rows of the screen after drawing `app` on a `width` by `height` terminal.
+fn draw(app: &App, width: u16, height: u16) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let buffer = render(app, width, height)?;
+    Ok((0..height)
+        .map(|y| (0..width).map(|x| buffer.get(x, y).symbol()).collect())
+        .collect())
+}
+
+/// The screen as text, with the digits of clock times (which depend on the
+/// local time zone) replaced by `#`.
+fn snapshot(app: &App, width: u16, height: u16) -> Result<String, Box<dyn std::error::Error>> {
+    let rows = draw(app, width, height)?;
+    Ok(rows
+        .iter()
+        .map(|row| mask_times(row))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn mask_times(row: &str) -> String {
+    let mut chars: Vec<char> = row.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let len = chars[i..]
+            .iter()
+            .take_while(|c| c.is_ascii_digit() || **c == ':')
+            .count();
+        let run = &mut chars[i..i + len];
+        let digits = run.iter().filter(|c| c.is_ascii_digit()).count();
+        if run.contains(&':') && digits >= 4 {
+            run.iter_mut()
+                .filter(|c| c.is_ascii_digit())
+                .for_each(|c| *c = '#');
+        }
+        i += len.max(1);
+    }
+    chars.into_iter().collect()
+}
+
+#[test]
+fn test_layout_follows_terminal_size() -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(LayoutPreset::for_size(100, 30), LayoutPreset::Standard);
+    assert_eq!(LayoutPreset::for_size(200, 40), LayoutPreset::Wide);
+    assert_eq!(LayoutPreset::for_size(200, 20), LayoutPreset::Compact);
+    assert_eq!(LayoutPreset::for_size(60, 16), LayoutPreset::Compact);
+    assert_eq!(LayoutPreset::for_size(36, 10), LayoutPreset::Minimal);
+
+    let dir = tempfile::tempdir()?;
+    let mut app = fixture(&dir);
+    app.add_log("hello **world**".to_string());
+
+    let standard = draw(&app, 100, 30)?;
+    assert_eq!(standard[0], format!("┌{}┐", "─".repeat(98)));
+    assert_eq!(
+        standard[1].trim_matches(['│', ' ']),
+        ">>> CAPTAIN'S LOG <<<"
+    );
+    assert!(standard[3].starts_with("┌KEYBOARD ACTIVITY [1 MIN]─"));
+    assert!(standard[13].starts_with("┌JOURNAL LOGS (PERSISTENT)─"));
+    assert!(standard[14].ends_with(
+        "] hello world                                                                            │"
+    ));
+    assert_eq!(standard[27], "─".repeat(100));
+    assert!(standard[28].starts_with("CAPTAIN'S LOG | V1.0.0 | CTRL+P: COMMANDS"));
+
+    // Journal on the left, the activity chart on the right
+    let wide = draw(&app, 200, 40)?;
+    assert!(wide[3].starts_with("┌JOURNAL LOGS (PERSISTENT)─"));
+    assert!(wide[3].contains("┐┌KEYBOARD ACTIVITY [1 MIN]─"));
+    assert!(wide[34].starts_with("┌Input─"));
+    assert!(wide[35].starts_with("│Add log entry..."));
+    assert!(
+        wide[35].ends_with("WPM: 000 | LPM: 0000                                            │")
+    );
+    assert!(
+        wide[38].ends_with(
+            "F9: LAYOUT | F10: EXIT                                    github: yaga-simha"
+        )
+    );
+
+    // One-line header and footer without borders
+    let compact = draw(&app, 60, 16)?;
+    assert_eq!(compact[0].trim(), ">>> CAPTAIN'S LOG <<<");
+    assert!(compact[1].starts_with("┌KEYBOARD ACTIVITY [1 MIN]─"));
+    assert!(compact[8].starts_with("┌JOURNAL LOGS (PERSISTENT)─"));
+    assert_eq!(
+        compact[15],
+        "CAPTAIN'S LOG | V1.0.0 | CTRL+P: COMMANDS | F1: HELP | ENTER"
+    );
+
+    // Only the chart and a status line
+    let minimal = draw(&app, 36, 10)?;
+    assert!(minimal[0].starts_with("┌KEYBOARD ACTIVITY [1 MIN]─"));
+    assert_eq!(minimal[8], format!("└{}┘", "─".repeat(34)));
+    assert_eq!(minimal[9], "CAPTAIN'S LOG | V1.0.0 | CTRL+P: COM");
+    assert!(!minimal.iter().any(|row| row.contains("JOURNAL")));
+    Ok(())
+}
+
+#[test]
+fn test_layout_can_be_picked_by_key() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let mut app = fixture(&dir);
+    app.run(Action::CycleLayout);
+    assert_eq!(app.layout, Some(LayoutPreset::Compact));
+    assert_eq!(app.notice.as_deref(), Some("LAYOUT: COMPACT"));
+    assert_eq!(draw(&app, 200, 40)?[0].trim(), ">>> CAPTAIN'S LOG <<<");
+
+    for _ in 0..3 {
+        app.run(Action::CycleLayout);
+    }
+    assert_eq!(app.layout, Some(LayoutPreset::Minimal));
+    assert!(draw(&app, 200, 40)?[0].starts_with("┌KEYBOARD ACTIVITY"));
+
+    // Back to following the terminal size
+    app.run(Action::CycleLayout);
+    assert_eq!(app.layout, None);
+    assert_eq!(app.notice.as_deref(), Some("LAYOUT: AUTOMATIC"));
+    assert!(draw(&app, 200, 40)?[3].starts_with("┌JOURNAL LOGS"));
+    Ok(())
+}
+
+#[test]
+fn test_alert_fits_small_terminals() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let mut app = fixture(&dir);
+    app.alert_active = true;
+    for (width, height) in [(100, 30), (60, 16), (36, 10), (24, 6)] {
+        let rows = draw(&app, width, height)?;
+        let line = rows
+            .iter()
+            .position(|row| row.contains("ACTIVITY LOW!"))
+            .unwrap_or_else(|| panic!("no alert at {}x{}", width, height));
+        assert!(rows[line - 1].contains("┌ALERT─"));
+        assert!(rows[line + 1].contains("STAY FOCUSED!"));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_journal_snapshot() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let mut app = fixture(&dir);
+    app.activity_stream = ramp();
+    app.lpm = 480;
+    app.wpm = 96;
+    app.focus_level = 42.0;
+    app.logs = vec![
+        entry(90, "Standup: **ship** the `theme` work #release"),
+        entry(5, "# Retro\n- went well\n- went badly\n- try next"),
+    ];
+    app.selected = Some(1);
+    let expected = [
+        "┌──────────────────────────────────────────────────────────────────────────────┐",
+        "│                             >>> CAPTAIN'S LOG <<<                            │",
+        "└──────────────────────────────────────────────────────────────────────────────┘",
+        "┌KEYBOARD ACTIVITY [1 MIN]─────────────────────────────────────────────────────┐",
+        "│                                                          ▄███████████████████│",
+        "│                    ▂▂▂▂▂▂▂▂▂▂▂▂▂▂▂▂▂▂▂▇██████████████████████████████████████│",
+        "│───────────────────▀██████████████████████████████████████████████████████████│",
+        "│                                       ▀██████████████████████████████████████│",
+        "│                                                          ▀███████████████████│",
+        "│##:##:##       ##:##:##            ##:##:##           ##:##:##        ##:##:##│",
+        "│██CURRENT FOCUS LEVEL: 42% (FOCUSED)    DAILY GOALS: 0/2 | STREAK 0 (BEST 0)  │",
+        "│                             WPM: 096 | LPM: 0480                             │",
+        "└──────────────────────────────────────────────────────────────────────────────┘",
+        "┌JOURNAL LOGS (PERSISTENT)─────────────────────────────────────────────────────┐",
+        "│[##:##:##] Standup: ship the theme work #release                              │",
+        "│[##:##:##] Retro                                                              │",
+        "│           • went well                                                        │",
+        "│           • went badly…                                                      │",
+        "│                                                                              │",
+        "│                                                                              │",
+        "│                                                                              │",
+        "└──────────────────────────────────────────────────────────────────────────────┘",
+        "┌Input─────────────────────────────────────────────────────────────────────────┐",
+        "│Add log entry...                                                              │",
+        "└──────────────────────────────────────────────────────────────────────────────┘",
+        "────────────────────────────────────────────────────────────────────────────────",
+        "CAPTAIN'S LOG | V1.0.0 | CTRL+P: COMMANDS | F1: HELP | ENTER: OPEN | F2: STATS |",
+        "                                                                                ",
+    ]
+    .join("\n");
+    assert_eq!(snapshot(&app, 80, 28)?, expected);
+
+    // Bars are colored by speed, the selected entry is highlighted
+    let buffer = render(&app, 80, 28)?;
+    assert_eq!(buffer.get(25, 5).fg, Color::Green);
+    assert_eq!(buffer.get(45, 5).fg, Color::Yellow);
+    assert_eq!(buffer.get(70, 5).fg, Color::Red);
+    assert_eq!(buffer.get(5, 6).fg, Color::DarkGray);
+    assert_eq!(buffer.get(20, 15).bg, Color::DarkGray);
+    Ok(())
+}
+
+#[test]
+fn test_alert_snapshot() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let mut app = fixture(&dir);
+    app.alert_active = true;
+    let expected = [
+        "          >>> CAPTAIN'S LOG <<<         ",
+        "┌KEYBOARD ACTIVITY [1 MIN]─────────────┐",
+        "│                                      │",
+        "│──────────────────────────────────────│",
+        "│##:##:#┌ALERT─────────────────┐5      │",
+        "│CURRENT│     ACTIVITY LOW!    │ 0/2 | │",
+        "│       │     STAY FOCUSED!    │       │",
+        "└───────└──────────────────────┘───────┘",
+        "┌JOURNAL LOGS (PERSISTENT)─────────────┐",
+        "┌Input─────────────────────────────────┐",
+        "└──────────────────────────────────────┘",
+        "CAPTAIN'S LOG | V1.0.0 | CTRL+P: COMMAND",
+    ]
+    .join("\n");
+    assert_eq!(snapshot(&app, 40, 12)?, expected);
+    Ok(())
+}
+
+#[test]
+fn test_detail_snapshot() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let mut app = fixture(&dir);
+    app.logs = vec![entry(
+        30,
+        "## Plan\n1. Fix `render`\n2. Write tests\n> keep it small\n\n```rust\nlet x = 1; // one\n```",
+    )];
+    app.selected = Some(0);
+    app.screen = Screen::Detail;
+    let rows = draw(&app, 50, 14)?;
+    assert_eq!(rows[0].trim(), ">>> CAPTAIN'S LOG <<<");
+    // The title is the local date and time of the entry
+    let title = (noon() - TimeDelta::minutes(30))
+        .with_timezone(&Local)
+        .format("%A, %-d %B %Y %H:%M:%S");
+    assert!(rows[1].starts_with(&format!("┌{}─", title)));
+    let expected = [
+        "│Plan                                            │",
+        "│1. Fix render                                   │",
+        "│2. Write tests                                  │",
+        "││ keep it small                                 │",
+        "│                                                │",
+        "│▎ let x = 1; // one                             │",
+        "│                                                │",
+        "│                                                │",
+        "│                                                │",
+        "│                                                │",
+        "└────────────────────────────────────────────────┘",
+        "CAPTAIN'S LOG | V1.0.0 | ↓: SCROLL | PGDN: PAGE | ",
+    ]
+    .join("\n");
+    assert_eq!(rows[2..].join("\n"), expected);
+    Ok(())
+}
+
+fn mouse(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+    MouseEvent {
+        kind,
+        column,
+        row,
+        modifiers: KeyModifiers::NONE,
+    }
+}
+
+fn click(column: u16, row: u16) -> MouseEvent {
+    mouse(MouseEventKind::Down(MouseButton::Left), column, row)
+}
+
+#[test]
+fn test_click_selects_then_opens_entries() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let mut app = fixture(&dir);
+    app.logs = (0..20)
+        .map(|i| entry(60 - i, &format!("entry {}", i)))
+        .collect();
+    let screen = Rect::new(0, 0, 80, 28);
+    // Selecting the newest scrolls the list; clicks must follow it
+    app.select_previous();
+    let y_of = |app: &App, name: &str| -> Result<u16, Box<dyn std::error::Error>> {
+        let rows = draw(app, 80, 28)?;
+        Ok(rows.iter().position(|row| row.contains(name)).unwrap() as u16)
+    };
+    let y = y_of(&app, "entry 15")?;
+    app.handle_mouse(click(20, y), screen);
+    assert_eq!(app.selected_entry().unwrap().content, "entry 15");
+    assert_eq!(app.screen, Screen::Journal);
+
+    // Borders and the input are not entries
+    app.handle_mouse(click(0, y), screen);
+    app.handle_mouse(click(20, 25), screen);
+    assert_eq!(app.selected_entry().unwrap().content, "entry 15");
+
+    // A second click on the selected entry opens it
+    app.handle_mouse(click(20, y_of(&app, "entry 15")?), screen);
+    assert_eq!(app.screen, Screen::Detail);
+    Ok(())
+}
+
+#[test]
+fn test_wheel_moves_selection_and_scrolls() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let mut app = fixture(&dir);
+    app.logs = vec![entry(2, "older"), entry(1, "newer")];
+    let screen = Rect::new(0, 0, 80, 28);
+
+    app.handle_mouse(mouse(MouseEventKind::ScrollUp, 10, 20), screen);
+    assert_eq!(app.selected_entry().unwrap().content, "newer");
+    app.handle_mouse(mouse(MouseEventKind::ScrollUp, 10, 20), screen);
+    assert_eq!(app.selected_entry().unwrap().content, "older");
+    app.handle_mouse(mouse(MouseEventKind::ScrollDown, 10, 20), screen);
+    assert_eq!(app.selected_entry().unwrap().content, "newer");
+
+    app.open_detail();
+    app.handle_mouse(mouse(MouseEventKind::ScrollDown, 10, 10), screen);
+    app.handle_mouse(mouse(MouseEventKind::ScrollDown, 10, 10), screen);
+    app.handle_mouse(mouse(MouseEventKind::ScrollUp, 10, 10), screen);
+    assert_eq!(app.detail_scroll, 1);
+    Ok(())
+}
+
+#[test]
+fn test_click_on_chart_shows_activity() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let mut app = fixture(&dir);
+    app.activity_stream = ramp();
+    let screen = Rect::new(0, 0, 80, 28);
+    assert_eq!(
+        ui::target_at(&app, screen, 78, 6),
+        ui::Target::Chart {
+            column: 77,
+            width: 78
+        }
+    );
+
+    // The last second runs at 12 keystrokes per second
+    app.handle_mouse(click(78, 6), screen);
+    let notice = app.notice.clone().unwrap();
+    assert!(notice.ends_with(": 12 KEYSTROKES, 720 LPM"), "{}", notice);
+    let noon: DateTime<Local> = noon().into();
+    assert!(notice.contains(&noon.format("%H:%M:%S").to_string()));
+
+    app.handle_mouse(click(1, 6), screen);
+    assert!(app.notice.unwrap().ends_with(": 0 KEYSTROKES, 0 LPM"));
+    Ok(())
+}
+
+#[test]
+fn test_click_dismisses_alert() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let mut app = fixture(&dir);
+    app.alert_active = true;
+    let screen = Rect::new(0, 0, 80, 28);
+    let rows = draw(&app, 80, 28)?;
+    let y = rows
+        .iter()
+        .position(|row| row.contains("ACTIVITY LOW!"))
+        .unwrap();
+
+    // Clicks elsewhere leave it up
+    app.handle_mouse(click(2, 1), screen);
+    assert!(app.alert_active);
+    app.handle_mouse(click(40, y as u16), screen);
+    assert!(!app.alert_active);
+    Ok(())
+}
+
+#[test]
+fn test_footer_hints_follow_the_keymap() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let mut app = fixture(&dir);
+    app.unsaved.push(entry(1, "pending"));
+    app.error = Some("JOURNAL NOT SAVED".to_string());
+    app.search = Some("pending".to_string());
+    let rows = draw(&app, 120, 30)?;
+    assert!(
+        rows.iter()
+            .any(|row| row.contains("JOURNAL NOT SAVED | F5: RETRY"))
+    );
+    assert!(rows.iter().any(|row| row.contains("| ESC: CLEAR")));
+
+    let keys: captains_log::keymap::KeysConfig = toml::from_str(
+        "style = \"emacs\"\n[global]\nf5 = \"none\"\nf12 = \"retry_saving\"\n\
+         [normal]\nesc = \"none\"",
+    )?;
+    app.keymap = keys.keymap()?;
+    let rows = draw(&app, 120, 30)?;
+    assert!(
+        rows.iter()
+            .any(|row| row.contains("JOURNAL NOT SAVED | F12: RETRY"))
+    );
+    assert!(rows.iter().any(|row| row.contains("| CTRL+G: CLEAR")));
+    Ok(())
+}